@@ -0,0 +1,25 @@
+//! Compare serial and parallel page extraction on a PDF:
+//! `cargo run --release --features parallel --example parallel_pages -- file.pdf`
+use pdf_text_extract::Document;
+use std::time::Instant;
+
+fn main() {
+    let path = std::env::args().nth(1).expect("usage: parallel_pages <pdf-file>");
+    let data = std::fs::read(&path).expect("Failed to read file");
+
+    let mut doc = Document::parse(&data).expect("Failed to parse PDF");
+    let start = Instant::now();
+    let serial: Vec<_> = doc.pages().collect::<Result<_, _>>().expect("Failed to extract");
+    let serial_time = start.elapsed();
+
+    let mut doc = Document::parse(&data).expect("Failed to parse PDF");
+    let start = Instant::now();
+    let parallel = doc.extract_all_pages_parallel().expect("Failed to extract");
+    let parallel_time = start.elapsed();
+
+    let count = |pages: &[Vec<_>]| pages.iter().map(Vec::len).sum::<usize>();
+    assert_eq!(count(&serial), count(&parallel));
+    println!("{} pages, {} spans", serial.len(), count(&serial));
+    println!("serial:   {:?}", serial_time);
+    println!("parallel: {:?}", parallel_time);
+}
@@ -0,0 +1,135 @@
+/// Direction of a character: `Some(true)` right-to-left, `Some(false)`
+/// left-to-right, `None` neutral (spaces, punctuation)
+fn direction(c: char) -> Option<bool> {
+    match c as u32 {
+        // Hebrew, Arabic, Syriac, Thaana, NKo, Samaritan, Mandaic, Arabic
+        // Extended and the Hebrew/Arabic presentation forms
+        0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF => Some(true),
+        _ if c.is_alphanumeric() => Some(false),
+        _ => None,
+    }
+}
+
+/// Bracket that faces the other way, as right-to-left text shows it
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        _ => c,
+    }
+}
+
+/// Put one line, drawn as `pieces` from left to right on the page, into
+/// logical order. The line is right-to-left when most of its characters
+/// are; each piece is then reordered in that direction, and the pieces
+/// themselves are reversed. Lines without right-to-left text are left
+/// alone.
+pub(crate) fn line_to_logical<T>(pieces: &mut [T], text: impl Fn(&mut T) -> &mut String) {
+    let (mut rtl, mut ltr) = (0, 0);
+    for piece in pieces.iter_mut() {
+        for dir in text(piece).chars().filter_map(direction) {
+            if dir {
+                rtl += 1;
+            } else {
+                ltr += 1;
+            }
+        }
+    }
+    if rtl == 0 {
+        return;
+    }
+
+    let line_rtl = rtl > ltr;
+    for piece in pieces.iter_mut() {
+        let text = text(piece);
+        *text = visual_to_logical(text, line_rtl);
+    }
+    if line_rtl {
+        pieces.reverse();
+    }
+}
+
+/// Reorder text from visual (left to right on the page) to logical order,
+/// in a line that is right-to-left if `line_rtl`. Runs of right-to-left
+/// characters are reversed, and in a right-to-left line so is the order of
+/// the runs; numbers and Latin text inside keep their order. Neutral
+/// characters take the direction of the run around them, or of the line
+/// between runs.
+fn visual_to_logical(text: &str, line_rtl: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let dirs: Vec<Option<bool>> = chars.iter().map(|&c| direction(c)).collect();
+
+    // Resolve neutrals, then split into runs of one direction
+    let mut resolved = Vec::with_capacity(chars.len());
+    for (i, dir) in dirs.iter().enumerate() {
+        resolved.push(dir.unwrap_or_else(|| {
+            let before = dirs[..i].iter().rev().find_map(|d| *d);
+            let after = dirs[i + 1..].iter().find_map(|d| *d);
+            match (before, after) {
+                (Some(before), Some(after)) if before == after => before,
+                _ => line_rtl,
+            }
+        }));
+    }
+    let mut runs: Vec<(bool, Vec<char>)> = Vec::new();
+    for (&c, &rtl) in chars.iter().zip(&resolved) {
+        match runs.last_mut() {
+            Some((dir, run)) if *dir == rtl => run.push(c),
+            _ => runs.push((rtl, vec![c])),
+        }
+    }
+
+    if line_rtl {
+        runs.reverse();
+    }
+    runs.into_iter()
+        .flat_map(|(rtl, run)| {
+            if rtl {
+                run.into_iter().rev().map(mirror).collect::<Vec<_>>()
+            } else {
+                run
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Logical order of a line drawn as the given pieces
+    fn logical(pieces: &[&str]) -> Vec<String> {
+        let mut line: Vec<String> = pieces.iter().map(|p| p.to_string()).collect();
+        line_to_logical(&mut line, |piece| piece);
+        line
+    }
+
+    #[test]
+    fn test_visual_to_logical() {
+        // "shalom olam", as laid out from right to left
+        assert_eq!(logical(&["םלוע םולש"]), ["שלום עולם"]);
+        // A number keeps its digit order at the start of a Hebrew line
+        assert_eq!(logical(&["123 םולש"]), ["שלום 123"]);
+        // A Hebrew word inside an English sentence
+        assert_eq!(logical(&["say םולש (hi)"]), ["say שלום (hi)"]);
+        assert_eq!(logical(&["(םולש)"]), ["(שלום)"]);
+        assert_eq!(logical(&["plain text"]), ["plain text"]);
+    }
+
+    #[test]
+    fn test_line_of_several_pieces() {
+        // The words of a Hebrew line drawn as separate pieces come back
+        // right to left
+        assert_eq!(logical(&["םלוע", "םולש"]), ["שלום", "עולם"]);
+        // The number piece keeps its digits, as in a single piece
+        assert_eq!(logical(&["123", "םולש"]), ["שלום", "123"]);
+        // A Hebrew piece in an English line stays in place
+        assert_eq!(logical(&["say", "םולש", "(hi)"]), ["say", "שלום", "(hi)"]);
+    }
+}
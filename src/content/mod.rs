@@ -1,3 +1,7 @@
+mod bidi;
 mod parser;
 
-pub use parser::{ContentParser, TextSpan};
+pub use parser::{
+    ContentParser, FormXObject, FormXObjects, MergeConfig, ParseReport, TextSpan, Word,
+};
+pub(crate) use parser::{device_color_components, MAX_FORM_DEPTH};
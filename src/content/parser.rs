@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use crate::error::{PdfError, Result};
-use crate::font::FontEncoding;
+use crate::font::{FontEncoding, expand_ligatures};
+use super::bidi::line_to_logical;
+use crate::types::PdfObject;
 
 /// Extracted text with position information
 #[derive(Debug, Clone)]
@@ -8,13 +10,123 @@ pub struct TextSpan {
     pub text: String,
     pub x: f64,
     pub y: f64,
+    /// Rendered font size: the Tf size scaled by the text matrix and CTM
     pub font_size: f64,
     pub font_name: Option<String>,
+    /// Text rise (Ts) already included in `y`; positive for superscripts
+    pub rise: f64,
+    /// Advance width of the shown text; 0 when unknown
+    pub width: f64,
+    /// Ascent-based height above the baseline; 0 when unknown
+    pub height: f64,
+    /// Fill color as RGB; `None` when the content stream never set one, so
+    /// the text is drawn in the default black, or when it was set in a
+    /// color space that doesn't convert to RGB (e.g. Separation, Lab)
+    pub color: Option<[u8; 3]>,
+    /// Set in a bold font
+    pub bold: bool,
+    /// Set in an italic or oblique font
+    pub italic: bool,
+    /// Set in a fixed-pitch font
+    pub monospace: bool,
+}
+
+impl TextSpan {
+    /// Right edge of the span, estimating half the font size per character
+    /// when the width is unknown
+    pub fn right(&self) -> f64 {
+        if self.width > 0.0 {
+            self.x + self.width
+        } else {
+            self.x + self.text.chars().count() as f64 * self.font_size * 0.5
+        }
+    }
+
+    /// Baseline y position, excluding any text rise
+    pub fn baseline(&self) -> f64 {
+        self.y - self.rise
+    }
+}
+
+/// A single word with its position, for search highlighting and similar
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word {
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+}
+
+/// How deeply Form XObjects may draw other forms; deeper ones are skipped,
+/// which also stops forms that draw themselves
+pub(crate) const MAX_FORM_DEPTH: usize = 8;
+
+/// The Form XObjects a page can draw with `Do`, each loaded once
+#[derive(Debug, Clone, Default)]
+pub struct FormXObjects {
+    pub forms: Vec<FormXObject>,
+    /// The page's resource names -> index in `forms`
+    pub names: HashMap<String, usize>,
+}
+
+/// A Form XObject's content and what is needed to draw it
+#[derive(Debug, Clone)]
+pub struct FormXObject {
+    /// Decoded content stream
+    pub content: Vec<u8>,
+    /// Form space to user space, from the form's /Matrix
+    pub matrix: [f64; 6],
+    /// Fonts from the form's own /Resources, or `None` to use the fonts of
+    /// the content that draws it
+    pub fonts: Option<HashMap<String, FontEncoding>>,
+    /// Forms the form draws by resource name, like
+    /// [`FormXObjects::names`], or `None` to use those of the content that
+    /// draws it
+    pub names: Option<HashMap<String, usize>>,
+    /// Color spaces from the form's own /Resources, like
+    /// [`ContentParser::with_color_spaces`], or `None` to use those of the
+    /// content that draws it
+    pub color_spaces: Option<HashMap<String, usize>>,
+}
+
+/// Problems noticed while parsing a content stream that did not stop it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseReport {
+    /// Font name -> number of codes shown without a Unicode mapping. Only
+    /// counted with [`ContentParser::replace_unmapped`].
+    pub unmapped: HashMap<String, usize>,
+    /// Malformed operators that were applied as best as possible, and
+    /// operators that were not recognized
+    pub warnings: Vec<String>,
+}
+
+/// Thresholds used when merging raw spans into runs of text, as multiples of
+/// the font size (line tolerance) or of the estimated character width (gaps)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MergeConfig {
+    /// Largest baseline difference still treated as the same line
+    pub line_tolerance_ratio: f64,
+    /// Gaps below this are joined without a space
+    pub word_gap_ratio: f64,
+    /// Gaps at or above this start a new span (e.g. another column)
+    pub new_span_gap_ratio: f64,
+}
+
+impl Default for MergeConfig {
+    fn default() -> Self {
+        Self {
+            line_tolerance_ratio: 0.3,
+            word_gap_ratio: 0.8,
+            new_span_gap_ratio: 2.0,
+        }
+    }
 }
 
 /// Graphics state for text positioning
 #[derive(Debug, Clone)]
 struct GraphicsState {
+    // Current transformation matrix: user space to device space
+    ctm: [f64; 6],
     // Text matrix components [a, b, c, d, e, f]
     // Maps text space to user space
     text_matrix: [f64; 6],
@@ -24,29 +136,52 @@ struct GraphicsState {
     font_size: f64,
     // Current font name
     font_name: Option<String>,
-    // Text leading (line spacing)
-    leading: f64,
+    // Text leading (line spacing), once TL or TD sets it
+    leading: Option<f64>,
     // Character spacing
     char_spacing: f64,
     // Word spacing
     word_spacing: f64,
+    // Horizontal scaling, in percent
+    horizontal_scaling: f64,
+    // Text rise (baseline offset)
+    text_rise: f64,
+    // Text rendering mode (0-7)
+    render_mode: i64,
+    // Nonstroking color as RGB components in 0-1, once one is set
+    fill_color: Option<[f64; 3]>,
+    // Number of components of the nonstroking color space, or None if its
+    // colors don't convert to RGB
+    fill_components: Option<usize>,
 }
 
 impl Default for GraphicsState {
     fn default() -> Self {
         Self {
+            ctm: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
             text_matrix: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
             line_matrix: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
             font_size: 12.0,
             font_name: None,
-            leading: 0.0,
+            leading: None,
             char_spacing: 0.0,
             word_spacing: 0.0,
+            horizontal_scaling: 100.0,
+            text_rise: 0.0,
+            render_mode: 0,
+            fill_color: None,
+            fill_components: Some(1),
         }
     }
 }
 
 impl GraphicsState {
+    /// Leading for `'` and `"`: producers that never set TL rely on lines
+    /// being spaced by the font size, while an explicit `0 TL` is kept
+    fn show_leading(&self) -> f64 {
+        self.leading.unwrap_or(self.font_size)
+    }
+
     /// Get current x position
     fn x(&self) -> f64 {
         self.text_matrix[4]
@@ -56,6 +191,110 @@ impl GraphicsState {
     fn y(&self) -> f64 {
         self.text_matrix[5]
     }
+
+    /// Move to the start of a new line offset by (tx, ty) in text space
+    fn translate_line(&mut self, tx: f64, ty: f64) {
+        self.line_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, tx, ty], &self.line_matrix);
+        self.text_matrix = self.line_matrix;
+    }
+
+    /// Advance the text position horizontally by `tx` in text space
+    fn advance(&mut self, tx: f64) {
+        self.text_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, tx, 0.0], &self.text_matrix);
+    }
+
+    /// Map a user space vector (no translation) to device space
+    fn device_vector(&self, dx: f64, dy: f64) -> (f64, f64) {
+        let m = &self.ctm;
+        (dx * m[0] + dy * m[2], dx * m[1] + dy * m[3])
+    }
+
+    /// Uniform scale of text space in device space, from the area scale of
+    /// the text matrix combined with the CTM
+    fn text_scale(&self) -> f64 {
+        let det = |m: &[f64; 6]| m[0] * m[3] - m[1] * m[2];
+        let scale = (det(&self.text_matrix) * det(&self.ctm)).abs().sqrt();
+        if scale.is_finite() && scale > 0.0 { scale } else { 1.0 }
+    }
+
+    /// Map a user space point to device space through the CTM
+    fn to_device(&self, x: f64, y: f64) -> (f64, f64) {
+        let m = &self.ctm;
+        (x * m[0] + y * m[2] + m[4], x * m[1] + y * m[3] + m[5])
+    }
+}
+
+/// Multiply two affine matrices in PDF order (`a` applied first, then `b`)
+fn multiply(a: &[f64; 6], b: &[f64; 6]) -> [f64; 6] {
+    [
+        a[0] * b[0] + a[1] * b[2],
+        a[0] * b[1] + a[1] * b[3],
+        a[2] * b[0] + a[3] * b[2],
+        a[2] * b[1] + a[3] * b[3],
+        a[4] * b[0] + a[5] * b[2] + b[4],
+        a[4] * b[1] + a[5] * b[3] + b[5],
+    ]
+}
+
+/// How a raw span continues the text before it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Join {
+    /// Same word: append directly
+    Concat,
+    /// Word boundary: append after a space
+    WordBreak,
+    /// Different line, font or column
+    NewSpan,
+}
+
+/// Whether `span` is on the line of `last`, within the line tolerance
+fn same_line(config: &MergeConfig, last: &TextSpan, span: &TextSpan) -> bool {
+    let y_tolerance = last.font_size * config.line_tolerance_ratio;
+    (span.baseline() - last.baseline()).abs() <= y_tolerance
+}
+
+/// Decide whether `span` continues `last`, given where the previous raw span
+/// ended and how many characters it had
+fn join_kind(
+    config: &MergeConfig,
+    last: &TextSpan,
+    span: &TextSpan,
+    last_raw_right: f64,
+    last_raw_char_count: usize,
+) -> Join {
+    if !same_line(config, last, span)
+        || last.font_name != span.font_name
+        || last.rise != span.rise
+        || last.color != span.color
+    {
+        return Join::NewSpan;
+    }
+
+    let char_width = last.font_size * 0.5;
+    let gap = span.x - last_raw_right;
+
+    // Check if we're in per-character mode (individual Td+Tj per glyph)
+    // vs multi-character mode (TJ arrays or multi-char Tj strings)
+    let per_char_mode = last_raw_char_count <= 1 && span.text.chars().count() <= 1;
+
+    if per_char_mode {
+        // Per-character mode: word boundaries come from preserved
+        // space glyphs, so we just need to concatenate nearby chars.
+        if gap < char_width * 3.0 && gap > -char_width * 3.0 {
+            Join::Concat
+        } else {
+            Join::NewSpan
+        }
+    } else if gap < char_width * config.word_gap_ratio && gap > -char_width * 2.0 {
+        // Small gap - just append
+        Join::Concat
+    } else if gap < char_width * config.new_span_gap_ratio {
+        // Word boundary - append with space
+        Join::WordBreak
+    } else {
+        // Large gap - new span (different column)
+        Join::NewSpan
+    }
 }
 
 /// Content stream parser
@@ -67,6 +306,49 @@ pub struct ContentParser<'a> {
     spans: Vec<TextSpan>,
     /// Font name -> encoding mapping
     font_encodings: HashMap<String, FontEncoding>,
+    /// Keep text drawn with an invisible rendering mode (e.g. OCR layers)
+    include_invisible: bool,
+    /// Expand ligature characters such as `ﬁ` into plain letters
+    normalize_ligatures: bool,
+    /// Thresholds for merging raw spans
+    config: MergeConfig,
+    /// Open BMC/BDC sections, innermost last
+    marked_content: Vec<MarkedContent>,
+    /// Decode codes without a Unicode mapping to U+FFFD
+    replace_unmapped: bool,
+    /// Put right-to-left text shown in visual order into logical order
+    reorder_rtl: bool,
+    /// Font name -> number of codes shown without a Unicode mapping
+    unmapped_counts: HashMap<String, usize>,
+    /// Number of Tm operators without exactly six numeric operands
+    malformed_tm: usize,
+    /// Number of operators outside the PDF operator set, not counting those
+    /// in BX/EX compatibility sections
+    unknown_operators: usize,
+    /// Number of open BX/EX compatibility sections
+    compatibility_depth: usize,
+    /// Form XObjects that `Do` can draw
+    forms: Option<&'a FormXObjects>,
+    /// Names `Do` finds forms by: the page's, or the form's being drawn
+    form_names: Option<&'a HashMap<String, usize>>,
+    /// Fonts of the form being drawn, when it has its own
+    form_fonts: Option<&'a HashMap<String, FontEncoding>>,
+    /// Resource name -> components of the color spaces `cs` can select
+    /// whose colors convert to RGB
+    color_spaces: HashMap<String, usize>,
+    /// Color spaces of the form being drawn, when it has its own
+    form_color_spaces: Option<&'a HashMap<String, usize>>,
+    /// Number of forms being drawn around the current content
+    form_depth: usize,
+}
+
+/// An open marked-content section
+#[derive(Debug, Clone)]
+struct MarkedContent {
+    /// Replacement text for the glyphs shown inside the section
+    actual_text: Option<String>,
+    /// Index of the first span shown inside the section
+    first_span: usize,
 }
 
 impl<'a> ContentParser<'a> {
@@ -78,6 +360,22 @@ impl<'a> ContentParser<'a> {
             state_stack: Vec::new(),
             spans: Vec::new(),
             font_encodings: HashMap::new(),
+            include_invisible: false,
+            normalize_ligatures: false,
+            config: MergeConfig::default(),
+            marked_content: Vec::new(),
+            replace_unmapped: false,
+            reorder_rtl: false,
+            unmapped_counts: HashMap::new(),
+            malformed_tm: 0,
+            unknown_operators: 0,
+            compatibility_depth: 0,
+            forms: None,
+            form_names: None,
+            form_fonts: None,
+            color_spaces: HashMap::new(),
+            form_color_spaces: None,
+            form_depth: 0,
         }
     }
 
@@ -90,11 +388,230 @@ impl<'a> ContentParser<'a> {
             state_stack: Vec::new(),
             spans: Vec::new(),
             font_encodings,
+            include_invisible: false,
+            normalize_ligatures: false,
+            config: MergeConfig::default(),
+            marked_content: Vec::new(),
+            replace_unmapped: false,
+            reorder_rtl: false,
+            unmapped_counts: HashMap::new(),
+            malformed_tm: 0,
+            unknown_operators: 0,
+            compatibility_depth: 0,
+            forms: None,
+            form_names: None,
+            form_fonts: None,
+            color_spaces: HashMap::new(),
+            form_color_spaces: None,
+            form_depth: 0,
         }
     }
 
+    /// Color spaces of the page's /ColorSpace resources by name, with the
+    /// number of components of those whose colors convert to RGB. Colors
+    /// set in other spaces, and in unknown ones, come out as `None`.
+    pub fn with_color_spaces(mut self, color_spaces: HashMap<String, usize>) -> Self {
+        self.color_spaces = color_spaces;
+        self
+    }
+
+    /// Start from a base transformation instead of the identity, e.g. to
+    /// undo a page's /Rotate so text comes out upright
+    pub fn with_transform(mut self, matrix: [f64; 6]) -> Self {
+        self.state.ctm = matrix;
+        self
+    }
+
+    /// Draw the text of Form XObjects shown with `Do`; without them `Do` is
+    /// skipped
+    pub fn with_forms(mut self, forms: &'a FormXObjects) -> Self {
+        self.forms = Some(forms);
+        self.form_names = Some(&forms.names);
+        self
+    }
+
+    /// Use custom span merging thresholds, e.g. tighter gaps for dense
+    /// financial statements
+    pub fn with_config(mut self, config: MergeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Also extract text drawn invisibly (render modes 3 and 7), such as the
+    /// OCR layer of a scanned document
+    pub fn include_invisible_text(mut self, include: bool) -> Self {
+        self.include_invisible = include;
+        self
+    }
+
+    /// Expand ligatures (U+FB00-FB06) into their letters so that e.g. "ﬁnd"
+    /// reads as "find". Off by default to keep the text faithful.
+    pub fn normalize_ligatures(mut self, normalize: bool) -> Self {
+        self.normalize_ligatures = normalize;
+        self
+    }
+
+    /// Decode character codes that have no Unicode mapping to U+FFFD rather
+    /// than guessing from their bytes, counting them per font. Off by
+    /// default.
+    pub fn replace_unmapped(mut self, replace: bool) -> Self {
+        self.replace_unmapped = replace;
+        self
+    }
+
+    /// Put right-to-left text (Hebrew, Arabic) into logical order line by
+    /// line, as it is usually stored in the order it appears on the page:
+    /// the text of merged spans and words is reordered, and those of a
+    /// mostly right-to-left line come out right to left. Off by default.
+    pub fn reorder_rtl(mut self, reorder: bool) -> Self {
+        self.reorder_rtl = reorder;
+        self
+    }
+
     /// Parse content stream and extract text spans
-    pub fn parse(mut self) -> Result<Vec<TextSpan>> {
+    pub fn parse(self) -> Result<Vec<TextSpan>> {
+        self.parse_with_report().map(|(spans, _)| spans)
+    }
+
+    /// Like [`ContentParser::parse`], also returning the problems that were
+    /// worked around, such as codes without a Unicode mapping
+    pub fn parse_with_report(mut self) -> Result<(Vec<TextSpan>, ParseReport)> {
+        self.run()?;
+
+        // Merge adjacent spans on the same line
+        let spans = self.merge_adjacent_spans();
+        let mut warnings = Vec::new();
+        if self.malformed_tm > 0 {
+            warnings.push(format!(
+                "{} Tm operators without six numeric operands",
+                self.malformed_tm
+            ));
+        }
+        if self.unknown_operators > 0 {
+            warnings.push(format!("{} unknown operators", self.unknown_operators));
+        }
+        let report = ParseReport {
+            unmapped: std::mem::take(&mut self.unmapped_counts),
+            warnings,
+        };
+        Ok((spans, report))
+    }
+
+    /// Parse content stream and return one span per text-showing operator,
+    /// in content stream order and without merging. Useful for debugging
+    /// the merge heuristics.
+    pub fn parse_raw(mut self) -> Result<Vec<TextSpan>> {
+        self.run()?;
+        Ok(self.spans)
+    }
+
+    /// Parse content stream and split the shown text into positioned words.
+    /// Word boundaries are the gaps that span merging treats as spaces, plus
+    /// space glyphs inside the shown strings.
+    pub fn parse_words(mut self) -> Result<Vec<Word>> {
+        self.run()?;
+
+        let mut words: Vec<Word> = Vec::new();
+        // Index of the first word of each line
+        let mut line_starts = vec![0];
+        let mut current: Option<Word> = None;
+        let mut prev: Option<TextSpan> = None;
+        let mut last_raw_right: f64 = 0.0;
+        let mut last_raw_char_count: usize = 0;
+
+        for span in self.sorted_spans() {
+            let join = match &prev {
+                Some(last) => {
+                    join_kind(&self.config, last, &span, last_raw_right, last_raw_char_count)
+                },
+                None => Join::NewSpan,
+            };
+            if join != Join::Concat {
+                words.extend(current.take());
+            }
+            if prev.as_ref().is_some_and(|last| !same_line(&self.config, last, &span)) {
+                line_starts.push(words.len());
+            }
+
+            // Glyph boundaries inside a span are not kept, so spread its
+            // extent evenly over its characters
+            let char_count = span.text.chars().count();
+            let char_width = (span.right() - span.x) / char_count.max(1) as f64;
+            for (i, c) in span.text.chars().enumerate() {
+                if c.is_whitespace() {
+                    words.extend(current.take());
+                    continue;
+                }
+                let cx = span.x + i as f64 * char_width;
+                let word = current.get_or_insert_with(|| Word {
+                    text: String::new(),
+                    x: cx,
+                    y: span.y,
+                    width: 0.0,
+                });
+                word.text.push(c);
+                word.width = cx + char_width - word.x;
+            }
+
+            last_raw_right = span.right();
+            last_raw_char_count = char_count;
+            prev = Some(span);
+        }
+        words.extend(current);
+
+        if self.reorder_rtl {
+            line_starts.push(words.len());
+            for line in line_starts.windows(2) {
+                line_to_logical(&mut words[line[0]..line[1]], |word| &mut word.text);
+            }
+        }
+        Ok(words)
+    }
+
+    /// Run a Form XObject's content in place of the current stream, under a
+    /// copy of the graphics state. Names that are not forms (e.g. images)
+    /// are ignored.
+    fn draw_form(&mut self, name: &str) -> Result<()> {
+        let Some(forms) = self.forms else {
+            return Ok(());
+        };
+        let Some(form) = self
+            .form_names
+            .and_then(|names| names.get(name))
+            .and_then(|&index| forms.forms.get(index))
+        else {
+            return Ok(());
+        };
+        if self.form_depth >= MAX_FORM_DEPTH {
+            return Ok(());
+        }
+
+        let outer =
+            (self.data, self.pos, self.form_names, self.form_fonts, self.form_color_spaces);
+        let state = self.state.clone();
+        let state_stack = std::mem::take(&mut self.state_stack);
+        let open_sections = self.marked_content.len();
+
+        self.data = &form.content;
+        self.pos = 0;
+        self.form_names = form.names.as_ref().or(self.form_names);
+        self.form_fonts = form.fonts.as_ref().or(self.form_fonts);
+        self.form_color_spaces = form.color_spaces.as_ref().or(self.form_color_spaces);
+        self.state.ctm = multiply(&form.matrix, &self.state.ctm);
+        self.form_depth += 1;
+        let result = self.run();
+        self.form_depth -= 1;
+
+        (self.data, self.pos, self.form_names, self.form_fonts, self.form_color_spaces) =
+            outer;
+        self.state = state;
+        self.state_stack = state_stack;
+        self.marked_content.truncate(open_sections);
+        result
+    }
+
+    /// Execute every operator in the content stream, collecting raw spans
+    fn run(&mut self) -> Result<()> {
         while self.pos < self.data.len() {
             self.skip_whitespace();
 
@@ -120,104 +637,81 @@ impl<'a> ContentParser<'a> {
                     break;
                 }
 
-                // Parse operand
+                // Parse operand; a stray `]` or `>` is skipped
+                let start = self.pos;
                 if let Some(operand) = self.parse_operand()? {
                     operands.push(operand);
                 } else {
+                    self.pos = self.pos.max(start + 1);
                     break;
                 }
             }
         }
 
-        // Merge adjacent spans on the same line
-        Ok(self.merge_adjacent_spans())
+        Ok(())
     }
 
-    /// Merge adjacent text spans that are on the same line and close together
-    fn merge_adjacent_spans(&self) -> Vec<TextSpan> {
-        if self.spans.is_empty() {
-            return Vec::new();
-        }
-
-        // Sort spans by y (descending = top to bottom) then x (ascending = left to right)
+    /// Raw spans in reading order: top to bottom, then left to right
+    fn sorted_spans(&self) -> Vec<TextSpan> {
         let mut sorted_spans = self.spans.clone();
         sorted_spans.sort_by(|a, b| {
             // First compare y (with tolerance for same line)
-            let y_diff = b.y - a.y;
-            if y_diff.abs() > a.font_size * 0.3 {
+            let y_diff = b.baseline() - a.baseline();
+            if y_diff.abs() > a.font_size * self.config.line_tolerance_ratio {
                 return y_diff.partial_cmp(&0.0).unwrap_or(std::cmp::Ordering::Equal);
             }
             // Same line - sort by x
             a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal)
         });
+        sorted_spans
+    }
 
+    /// Merge adjacent text spans that are on the same line and close together
+    fn merge_adjacent_spans(&self) -> Vec<TextSpan> {
         let mut merged: Vec<TextSpan> = Vec::new();
-        // Track position of the last raw span to avoid cumulative error
+        // Index of the first merged span of each line
+        let mut line_starts = vec![0];
+        // Track the end of the last raw span to avoid cumulative error
         // from estimating positions based on the entire merged text length
-        let mut last_raw_x: f64 = 0.0;
+        let mut last_raw_right: f64 = 0.0;
         let mut last_raw_char_count: usize = 0;
 
-        for span in sorted_spans {
-            if let Some(last) = merged.last_mut() {
-                // Check if this span is on the same line (within tolerance)
-                let y_tolerance = last.font_size * 0.3;
-                let same_line = (span.y - last.y).abs() <= y_tolerance;
-
-                if same_line && last.font_name == span.font_name {
-                    let char_width = last.font_size * 0.5;
-
-                    // Check if we're in per-character mode (individual Td+Tj per glyph)
-                    // vs multi-character mode (TJ arrays or multi-char Tj strings)
-                    let per_char_mode = last_raw_char_count <= 1 && span.text.chars().count() <= 1;
-
-                    if per_char_mode {
-                        // Per-character mode: use last raw span position to avoid
-                        // cumulative error. Word boundaries come from preserved
-                        // space glyphs, so we just need to concatenate nearby chars.
-                        let expected_end = last_raw_x + (last_raw_char_count as f64 * char_width);
-                        let gap = span.x - expected_end;
-
-                        if gap < char_width * 3.0 && gap > -char_width * 3.0 {
-                            // Same text run - merge
-                            last.text.push_str(&span.text);
-                        } else {
-                            // Large gap - new span (different column)
-                            merged.push(span.clone());
-                        }
-                    } else {
-                        // Multi-character mode: use gap-based space detection
-                        // Position estimation uses last raw span, not full merged text
-                        let expected_end = last_raw_x + (last_raw_char_count as f64 * char_width);
-                        let gap = span.x - expected_end;
-
-                        if gap < char_width * 0.8 && gap > -char_width * 2.0 {
-                            // Small gap - just append
-                            last.text.push_str(&span.text);
-                        } else if gap < char_width * 2.0 {
-                            // Word boundary - append with space
-                            last.text.push(' ');
-                            last.text.push_str(&span.text);
-                        } else {
-                            // Large gap - new span
-                            merged.push(span.clone());
-                        }
+        for span in self.sorted_spans() {
+            let join = match merged.last() {
+                Some(last) => {
+                    join_kind(&self.config, last, &span, last_raw_right, last_raw_char_count)
+                },
+                None => Join::NewSpan,
+            };
+            last_raw_right = span.right();
+            last_raw_char_count = span.text.chars().count();
+
+            match (join, merged.last_mut()) {
+                (Join::Concat | Join::WordBreak, Some(last)) => {
+                    if join == Join::WordBreak {
+                        last.text.push(' ');
+                    }
+                    last.text.push_str(&span.text);
+                    if last.width > 0.0 && span.width > 0.0 {
+                        last.width = last.width.max(span.x + span.width - last.x);
+                    }
+                    last.height = last.height.max(span.height);
+                }
+                (_, last) => {
+                    if last.is_some_and(|last| !same_line(&self.config, last, &span)) {
+                        line_starts.push(merged.len());
                     }
-
-                    last_raw_x = span.x;
-                    last_raw_char_count = span.text.chars().count();
-                } else {
-                    // Different line or font - new span
-                    last_raw_x = span.x;
-                    last_raw_char_count = span.text.chars().count();
                     merged.push(span);
                 }
-            } else {
-                last_raw_x = span.x;
-                last_raw_char_count = span.text.chars().count();
-                merged.push(span);
             }
         }
 
+        if self.reorder_rtl {
+            line_starts.push(merged.len());
+            for line in line_starts.windows(2) {
+                line_to_logical(&mut merged[line[0]..line[1]], |span| &mut span.text);
+            }
+        }
         merged
     }
 
@@ -240,7 +734,9 @@ impl<'a> ContentParser<'a> {
         let start = self.pos;
         while self.pos < self.data.len() {
             let b = self.data[self.pos];
-            if b.is_ascii_alphabetic() || b == b'*' || b == b'\'' || b == b'"' {
+            // Digits after the first character belong to the operator (d0, d1)
+            let digit = b.is_ascii_digit() && self.pos > start;
+            if b.is_ascii_alphabetic() || digit || b == b'*' || b == b'\'' || b == b'"' {
                 self.pos += 1;
             } else {
                 break;
@@ -273,9 +769,8 @@ impl<'a> ContentParser<'a> {
             b'<' => {
                 self.pos += 1;
                 if self.pos < self.data.len() && self.data[self.pos] == b'<' {
-                    // It's a dictionary - skip it
-                    self.skip_dict()?;
-                    Ok(None)
+                    let dict = self.read_dict()?;
+                    Ok(Some(Operand::Dict(dict)))
                 } else {
                     let s = self.read_hex_string()?;
                     Ok(Some(Operand::String(s)))
@@ -365,7 +860,7 @@ impl<'a> ContentParser<'a> {
                         b'\\' => result.push(b'\\'),
                         b'0'..=b'7' => {
                             // Octal
-                            let mut val = (escaped - b'0') as u8;
+                            let mut val = escaped - b'0';
                             for _ in 0..2 {
                                 if self.pos < self.data.len() {
                                     let d = self.data[self.pos];
@@ -464,26 +959,124 @@ impl<'a> ContentParser<'a> {
         Ok(items)
     }
 
-    fn skip_dict(&mut self) -> Result<()> {
+    /// Read a dictionary such as a BDC property list. Keyword values like
+    /// `true` are kept as names.
+    fn read_dict(&mut self) -> Result<HashMap<String, Operand>> {
         self.pos += 1; // Skip second '<'
-        let mut depth = 1;
+        let mut dict = HashMap::new();
 
-        while self.pos < self.data.len() && depth > 0 {
-            if self.pos + 1 < self.data.len() {
-                if self.data[self.pos] == b'<' && self.data[self.pos + 1] == b'<' {
-                    depth += 1;
-                    self.pos += 2;
-                    continue;
-                }
-                if self.data[self.pos] == b'>' && self.data[self.pos + 1] == b'>' {
-                    depth -= 1;
-                    self.pos += 2;
-                    continue;
+        loop {
+            self.skip_whitespace();
+            if self.pos >= self.data.len() {
+                break;
+            }
+            if self.data[self.pos..].starts_with(b">>") {
+                self.pos += 2;
+                break;
+            }
+
+            let start = self.pos;
+            let Some(Operand::Name(key)) = self.parse_operand()? else {
+                self.pos = self.pos.max(start + 1); // Skip unknown
+                continue;
+            };
+            self.skip_whitespace();
+            let value = match self.data.get(self.pos) {
+                Some(b) if b.is_ascii_alphabetic() => Some(Operand::Name(self.read_operator())),
+                _ => self.parse_operand()?,
+            };
+            if let Some(value) = value {
+                dict.insert(key, value);
+            }
+        }
+
+        Ok(dict)
+    }
+
+    /// Skip an inline image after its BI operator. The dictionary runs up to
+    /// ID and is followed by a single whitespace byte and the image data.
+    /// Unfiltered data has a known size and is skipped exactly; otherwise the
+    /// data ends at an EI with whitespace before it and a delimiter after.
+    fn skip_inline_image(&mut self) -> Result<()> {
+        let mut dict: HashMap<String, Operand> = HashMap::new();
+        loop {
+            self.skip_whitespace();
+            if self.pos >= self.data.len() {
+                return Ok(());
+            }
+            if self.data[self.pos].is_ascii_alphabetic() {
+                if self.read_operator() == "ID" {
+                    break;
                 }
+                continue;
+            }
+            let start = self.pos;
+            let Some(Operand::Name(key)) = self.parse_operand()? else {
+                // Not a key; make sure stray delimiters are consumed
+                self.pos = self.pos.max(start + 1);
+                continue;
+            };
+            self.skip_whitespace();
+            let value = match self.data.get(self.pos) {
+                // Keyword values such as `true`; a missing value runs into ID
+                Some(b) if b.is_ascii_alphabetic() => match self.read_operator() {
+                    word if word == "ID" => break,
+                    word => Some(Operand::Name(word)),
+                },
+                _ => self.parse_operand()?,
+            };
+            if let Some(value) = value {
+                dict.insert(inline_image_key(&key).to_string(), value);
+            }
+        }
+        self.pos += 1;
+
+        // Unfiltered data: rows of width * components * bits, padded to
+        // bytes. A size that overflows is treated as unknown.
+        let number = |key: &str| match dict.get(key) {
+            Some(Operand::Number(n)) if *n >= 0.0 => Some(*n as usize),
+            _ => None,
+        };
+        let image_mask = matches!(dict.get("ImageMask"), Some(Operand::Name(b)) if b == "true");
+        let components = match dict.get("ColorSpace") {
+            _ if image_mask => Some(1),
+            Some(Operand::Name(cs)) => match cs.as_str() {
+                "I" | "Indexed" => Some(1),
+                cs => device_color_components(cs),
+            },
+            Some(Operand::Array(cs)) => match cs.first() {
+                Some(Operand::Name(family)) if family == "I" || family == "Indexed" => Some(1),
+                _ => None,
+            },
+            _ => None,
+        };
+        let bits = if image_mask { Some(1) } else { number("BitsPerComponent") };
+        if !dict.contains_key("Filter") {
+            let len = match (number("Width"), number("Height"), components, bits) {
+                (Some(w), Some(h), Some(c), Some(bits)) => w
+                    .checked_mul(c)
+                    .and_then(|row| row.checked_mul(bits))
+                    .and_then(|row| row.div_ceil(8).checked_mul(h)),
+                _ => None,
+            };
+            if let Some(len) = len {
+                self.pos = self.pos.saturating_add(len).min(self.data.len());
             }
-            self.pos += 1;
         }
 
+        let is_white = |b: u8| matches!(b, b' ' | b'\t' | b'\n' | b'\r' | 0x0C | 0x00);
+        while self.pos + 2 <= self.data.len() {
+            let before = self.pos == 0 || is_white(self.data[self.pos - 1]);
+            let after = self.data.get(self.pos + 2).is_none_or(|&b| {
+                is_white(b) || b"()<>[]{}/%".contains(&b)
+            });
+            if before && after && &self.data[self.pos..self.pos + 2] == b"EI" {
+                self.pos += 2;
+                return Ok(());
+            }
+            self.pos += 1;
+        }
+        self.pos = self.data.len();
         Ok(())
     }
 
@@ -499,6 +1092,32 @@ impl<'a> ContentParser<'a> {
                 }
             }
 
+            // Concatenate to CTM: a b c d e f cm
+            "cm" => {
+                let nums: Vec<f64> = operands
+                    .iter()
+                    .filter_map(|o| {
+                        if let Operand::Number(n) = o {
+                            Some(*n)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+
+                if nums.len() >= 6 {
+                    let m = [nums[0], nums[1], nums[2], nums[3], nums[4], nums[5]];
+                    self.state.ctm = multiply(&m, &self.state.ctm);
+                }
+            }
+
+            // Draw an XObject: name Do
+            "Do" => {
+                if let Some(Operand::Name(name)) = operands.last() {
+                    self.draw_form(name)?;
+                }
+            }
+
             // Text state operators
             "BT" => {
                 // Begin text - reset text matrix
@@ -510,21 +1129,19 @@ impl<'a> ContentParser<'a> {
             }
 
             // Font: /FontName size Tf
-            "Tf" => {
-                if operands.len() >= 2 {
-                    if let Operand::Name(name) = &operands[operands.len() - 2] {
-                        self.state.font_name = Some(name.clone());
-                    }
-                    if let Operand::Number(size) = &operands[operands.len() - 1] {
-                        self.state.font_size = *size;
-                    }
+            "Tf" if operands.len() >= 2 => {
+                if let Operand::Name(name) = &operands[operands.len() - 2] {
+                    self.state.font_name = Some(name.clone());
+                }
+                if let Operand::Number(size) = &operands[operands.len() - 1] {
+                    self.state.font_size = *size;
                 }
             }
 
             // Text leading: leading TL
             "TL" => {
                 if let Some(Operand::Number(leading)) = operands.last() {
-                    self.state.leading = *leading;
+                    self.state.leading = Some(*leading);
                 }
             }
 
@@ -542,60 +1159,81 @@ impl<'a> ContentParser<'a> {
                 }
             }
 
+            // Horizontal scaling: scale Tz
+            "Tz" => {
+                if let Some(Operand::Number(scale)) = operands.last() {
+                    self.state.horizontal_scaling = *scale;
+                }
+            }
+
+            // Text rendering mode: mode Tr
+            "Tr" => {
+                if let Some(Operand::Number(mode)) = operands.last() {
+                    self.state.render_mode = *mode as i64;
+                }
+            }
+
+            // Text rise: rise Ts
+            "Ts" => {
+                if let Some(Operand::Number(rise)) = operands.last() {
+                    self.state.text_rise = *rise;
+                }
+            }
+
             // Text positioning: tx ty Td
-            "Td" => {
-                if operands.len() >= 2 {
-                    if let (Operand::Number(tx), Operand::Number(ty)) =
-                        (&operands[operands.len() - 2], &operands[operands.len() - 1])
-                    {
-                        // Translate from line matrix
-                        self.state.line_matrix[4] += tx;
-                        self.state.line_matrix[5] += ty;
-                        self.state.text_matrix = self.state.line_matrix;
-                    }
+            "Td" if operands.len() >= 2 => {
+                if let (Operand::Number(tx), Operand::Number(ty)) =
+                    (&operands[operands.len() - 2], &operands[operands.len() - 1])
+                {
+                    // Translate from line matrix
+                    self.state.translate_line(*tx, *ty);
                 }
             }
 
             // Text positioning with leading: tx ty TD
-            "TD" => {
-                if operands.len() >= 2 {
-                    if let (Operand::Number(tx), Operand::Number(ty)) =
-                        (&operands[operands.len() - 2], &operands[operands.len() - 1])
-                    {
-                        self.state.leading = -ty;
-                        self.state.line_matrix[4] += tx;
-                        self.state.line_matrix[5] += ty;
-                        self.state.text_matrix = self.state.line_matrix;
-                    }
+            "TD" if operands.len() >= 2 => {
+                if let (Operand::Number(tx), Operand::Number(ty)) =
+                    (&operands[operands.len() - 2], &operands[operands.len() - 1])
+                {
+                    self.state.leading = Some(-ty);
+                    self.state.translate_line(*tx, *ty);
                 }
             }
 
             // Set text matrix: a b c d e f Tm
             "Tm" => {
-                if operands.len() >= 6 {
-                    let nums: Vec<f64> = operands
-                        .iter()
-                        .filter_map(|o| {
-                            if let Operand::Number(n) = o {
-                                Some(*n)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
+                let nums: Vec<f64> = operands
+                    .iter()
+                    .filter_map(|o| {
+                        if let Operand::Number(n) = o {
+                            Some(*n)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                if nums.len() != 6 || operands.len() != 6 {
+                    self.malformed_tm += 1;
+                }
 
-                    if nums.len() >= 6 {
-                        self.state.text_matrix = [nums[0], nums[1], nums[2], nums[3], nums[4], nums[5]];
-                        self.state.line_matrix = self.state.text_matrix;
+                // Stray tokens come before the matrix, so use the last six
+                // numbers; with fewer, keep only a translation from the
+                // last two (missing ones count as 0)
+                self.state.text_matrix = match nums.len() {
+                    0 => return Ok(()),
+                    len if len >= 6 => {
+                        let m = &nums[len - 6..];
+                        [m[0], m[1], m[2], m[3], m[4], m[5]]
                     }
-                }
+                    1 => [1.0, 0.0, 0.0, 1.0, 0.0, nums[0]],
+                    len => [1.0, 0.0, 0.0, 1.0, nums[len - 2], nums[len - 1]],
+                };
+                self.state.line_matrix = self.state.text_matrix;
             }
 
             // Move to next line: T*
             "T*" => {
-                self.state.line_matrix[4] = self.state.line_matrix[4];
-                self.state.line_matrix[5] -= self.state.leading;
-                self.state.text_matrix = self.state.line_matrix;
+                self.state.translate_line(0.0, -self.state.leading.unwrap_or(0.0));
             }
 
             // Show text: (string) Tj
@@ -624,12 +1262,14 @@ impl<'a> ContentParser<'a> {
                                 }
                                 combined_bytes.extend(bytes);
                                 // Advance text position
-                                let advance = bytes.len() as f64 * self.state.font_size * 0.5;
-                                self.state.text_matrix[4] += advance;
+                                self.state.advance(self.string_advance(bytes));
                             }
                             Operand::Number(n) => {
                                 // Adjust position (negative = move right)
-                                let adjust = -n / 1000.0 * self.state.font_size;
+                                let adjust = -n / 1000.0
+                                    * self.state.font_size
+                                    * self.state.horizontal_scaling
+                                    / 100.0;
 
                                 // If adjustment is large (> 200 units = word space), flush current span
                                 if n.abs() > 200.0 && has_content {
@@ -638,7 +1278,7 @@ impl<'a> ContentParser<'a> {
                                     has_content = false;
                                 }
 
-                                self.state.text_matrix[4] += adjust;
+                                self.state.advance(adjust);
                             }
                             _ => {}
                         }
@@ -654,8 +1294,7 @@ impl<'a> ContentParser<'a> {
             // Move to next line and show: (string) '
             "'" => {
                 // T* then Tj
-                self.state.line_matrix[5] -= self.state.leading;
-                self.state.text_matrix = self.state.line_matrix;
+                self.state.translate_line(0.0, -self.state.show_leading());
 
                 if let Some(Operand::String(bytes)) = operands.last() {
                     self.add_text_span(bytes);
@@ -673,16 +1312,90 @@ impl<'a> ContentParser<'a> {
                     }
                 }
 
-                self.state.line_matrix[5] -= self.state.leading;
-                self.state.text_matrix = self.state.line_matrix;
+                self.state.translate_line(0.0, -self.state.show_leading());
 
                 if let Some(Operand::String(bytes)) = operands.last() {
                     self.add_text_span(bytes);
                 }
             }
 
+            // Marked content: tag BMC, tag properties BDC, EMC
+            "BMC" | "BDC" => {
+                // Properties given by name live in the resources; only an
+                // inline /ActualText is used
+                let actual_text = match operands.get(1) {
+                    Some(Operand::Dict(props)) => match props.get("ActualText") {
+                        Some(Operand::String(bytes)) => {
+                            PdfObject::String(bytes.clone()).as_text_string()
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                self.marked_content.push(MarkedContent {
+                    actual_text,
+                    first_span: self.spans.len(),
+                });
+            }
+            "EMC" => {
+                if let Some(marked) = self.marked_content.pop() {
+                    if let Some(text) = marked.actual_text {
+                        self.replace_spans_with(marked.first_span, text);
+                    }
+                }
+            }
+
+            // Nonstroking color: gray g, r g b rg, c m y k k, or components
+            // in the current color space with sc/scn, converted only when the
+            // space is device-like and they match its number of components
+            "g" | "rg" | "k" | "sc" | "scn" => {
+                let nums: Vec<f64> = operands
+                    .iter()
+                    .filter_map(|o| match o {
+                        Operand::Number(n) => Some(*n),
+                        _ => None,
+                    })
+                    .collect();
+                if matches!(op, "g" | "rg" | "k") {
+                    if let Some(rgb) = rgb_from_components(&nums) {
+                        self.state.fill_color = Some(rgb);
+                        self.state.fill_components = Some(nums.len());
+                    }
+                } else if self.state.fill_components == Some(nums.len()) {
+                    self.state.fill_color = rgb_from_components(&nums);
+                } else {
+                    self.state.fill_color = None;
+                }
+            }
+
+            // Nonstroking color space; its initial color is black
+            "cs" => {
+                let components = match operands.last() {
+                    Some(Operand::Name(name)) => self.color_space_components(name),
+                    _ => None,
+                };
+                self.state.fill_components = components;
+                self.state.fill_color = components.map(|_| [0.0; 3]);
+            }
+
+            // Inline image: BI <dict> ID <data> EI
+            "BI" => self.skip_inline_image()?,
+
+            // Type3 glyph metrics, graphics state and color spaces don't
+            // affect text extraction; their operands are dropped here
+            "d0" | "d1" | "gs" | "CS" => {}
+
+            // Compatibility sections: unknown operators inside are expected
+            // and ignored without a warning
+            "BX" => self.compatibility_depth += 1,
+            "EX" => self.compatibility_depth = self.compatibility_depth.saturating_sub(1),
+
             _ => {
-                // Unknown operator - ignore
+                // Unsupported operators are ignored; their operands are
+                // dropped with them
+                if self.compatibility_depth == 0 && !is_pdf_operator(op) {
+                    self.unknown_operators += 1;
+                }
             }
         }
 
@@ -692,24 +1405,136 @@ impl<'a> ContentParser<'a> {
     fn add_text_span(&mut self, bytes: &[u8]) {
         let x = self.state.x();
         let y = self.state.y();
+        self.state.advance(self.string_advance(bytes));
         self.add_text_span_at(bytes, x, y);
+    }
+
+    /// Horizontal advance of a shown string, using the font's glyph widths
+    /// when known and half the font size per glyph otherwise. Character
+    /// spacing (Tc) is added after every glyph, and word spacing (Tw) after
+    /// each single-byte space code.
+    fn string_advance(&self, bytes: &[u8]) -> f64 {
+        let scaling = self.state.horizontal_scaling / 100.0;
+        let font_size = self.state.font_size * scaling;
+        let encoding = self.current_encoding();
+
+        let (glyphs, widths): (usize, f64) = match encoding {
+            Some(encoding) => {
+                let codes = encoding.char_codes(bytes);
+                let widths = codes
+                    .iter()
+                    .map(|&code| encoding.glyph_width(code).unwrap_or(500.0) / 1000.0 * font_size)
+                    .sum();
+                (codes.len(), widths)
+            }
+            None => (bytes.len(), bytes.len() as f64 * font_size * 0.5),
+        };
+        let spaces = self.single_byte_spaces(bytes, false);
+        let spacing = glyphs as f64 * self.state.char_spacing
+            + spaces as f64 * self.state.word_spacing;
+        widths + spacing * scaling
+    }
+
+    /// Encoding of the current font, if it was loaded
+    fn current_encoding(&self) -> Option<&FontEncoding> {
+        let fonts = self.form_fonts.unwrap_or(&self.font_encodings);
+        self.state.font_name.as_ref().and_then(|name| fonts.get(name))
+    }
+
+    /// Number of components of the color space `cs` selects by `name`, if
+    /// its colors convert to RGB
+    fn color_space_components(&self, name: &str) -> Option<usize> {
+        let spaces = self.form_color_spaces.unwrap_or(&self.color_spaces);
+        spaces.get(name).copied().or_else(|| device_color_components(name))
+    }
+
+    /// Number of space codes (byte 32) in a string shown with a simple font,
+    /// or only those at its end with `trailing`; composite fonts have none
+    fn single_byte_spaces(&self, bytes: &[u8], trailing: bool) -> usize {
+        if self.current_encoding().is_some_and(|e| e.is_multibyte()) {
+            return 0;
+        }
+        if trailing {
+            bytes.iter().rev().take_while(|&&b| b == b' ').count()
+        } else {
+            bytes.iter().filter(|&&b| b == b' ').count()
+        }
+    }
 
-        // Advance text position (simplified - doesn't account for actual glyph widths)
-        let advance = bytes.len() as f64 * self.state.font_size * 0.5;
-        self.state.text_matrix[4] += advance;
+    /// Replace the spans shown since `first` with a single span carrying
+    /// `text`, placed where the first of them starts
+    fn replace_spans_with(&mut self, first: usize, text: String) {
+        let shown: Vec<TextSpan> = self.spans.drain(first.min(self.spans.len())..).collect();
+        let Some(start) = shown.iter().find(|s| !s.text.trim().is_empty()) else {
+            return;
+        };
+        let right = shown.iter().map(|s| s.right()).fold(start.right(), f64::max);
+        let text = if self.normalize_ligatures { expand_ligatures(&text) } else { text };
+        self.spans.push(TextSpan {
+            text: text.trim().to_string(),
+            width: if start.width > 0.0 { right - start.x } else { 0.0 },
+            height: shown.iter().map(|s| s.height).fold(0.0, f64::max),
+            ..start.clone()
+        });
     }
 
+    /// Record a span that starts at (x, y) in user space and ends at the
+    /// current text position
     fn add_text_span_at(&mut self, bytes: &[u8], x: f64, y: f64) {
+        // Modes 3 (invisible) and 7 (clip only) paint nothing
+        if matches!(self.state.render_mode, 3 | 7) && !self.include_invisible {
+            return;
+        }
+
         // Decode bytes using font encoding if available
-        let text = if let Some(font_name) = &self.state.font_name {
-            if let Some(encoding) = self.font_encodings.get(font_name) {
-                encoding.decode_bytes(bytes)
-            } else {
-                self.decode_default(bytes)
+        let fonts = self.form_fonts.unwrap_or(&self.font_encodings);
+        let encoding = self.state.font_name.as_ref().and_then(|name| fonts.get(name));
+        let mut text = match encoding {
+            Some(encoding) if self.replace_unmapped => {
+                let (text, unmapped) = encoding.decode_bytes_strict(bytes);
+                if unmapped > 0 {
+                    let name = self.state.font_name.clone().unwrap_or_default();
+                    *self.unmapped_counts.entry(name).or_default() += unmapped;
+                }
+                text
             }
-        } else {
-            self.decode_default(bytes)
+            Some(encoding) => encoding.decode_bytes(bytes),
+            None => self.decode_default(bytes),
+        };
+        if self.normalize_ligatures {
+            text = expand_ligatures(&text);
+        }
+        let ascent = encoding.and_then(|e| e.ascent()).unwrap_or(1000.0);
+        let bold = encoding.is_some_and(|e| e.is_bold());
+        let italic = encoding.is_some_and(|e| e.is_italic());
+        let monospace = encoding.is_some_and(|e| e.is_monospace());
+
+        // Offset by the rise along the text y axis, then map to device space
+        let rise = self.state.text_rise;
+        let tm = self.state.text_matrix;
+        let (base_x, base_y) = self.state.to_device(x, y);
+        let (x, y) = self.state.to_device(x + rise * tm[2], y + rise * tm[3]);
+        let rise = y - base_y;
+
+        // Box size: distance travelled along the baseline, and the ascent
+        // scaled by the text and transformation matrices. Trailing spaces
+        // are trimmed from the text, so they don't count toward the width
+        // and a wide word spacing shows up as a gap before the next span.
+        let trailing = match self.single_byte_spaces(bytes, true) {
+            n if n < bytes.len() => self.string_advance(&bytes[bytes.len() - n..]),
+            _ => 0.0,
         };
+        let (end_x, end_y) = self
+            .state
+            .to_device(self.state.x() - trailing * tm[0], self.state.y() - trailing * tm[1]);
+        let width = (end_x - base_x).hypot(end_y - base_y);
+        let (up_x, up_y) = self.state.device_vector(tm[2], tm[3]);
+        let height = self.state.font_size * ascent / 1000.0 * up_x.hypot(up_y);
+        let font_size = self.state.font_size * self.state.text_scale();
+        let color = self
+            .state
+            .fill_color
+            .map(|rgb| rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
 
         // Check if original text was whitespace-only before trimming
         let is_whitespace_only = !text.is_empty() && text.trim().is_empty();
@@ -720,8 +1545,15 @@ impl<'a> ContentParser<'a> {
                 text: trimmed,
                 x,
                 y,
-                font_size: self.state.font_size,
+                font_size,
                 font_name: self.state.font_name.clone(),
+                rise,
+                width,
+                height,
+                color,
+                bold,
+                italic,
+                monospace,
             });
         } else if is_whitespace_only {
             // Preserve space characters as word boundary markers
@@ -729,8 +1561,15 @@ impl<'a> ContentParser<'a> {
                 text: " ".to_string(),
                 x,
                 y,
-                font_size: self.state.font_size,
+                font_size,
                 font_name: self.state.font_name.clone(),
+                rise,
+                width,
+                height,
+                color,
+                bold,
+                italic,
+                monospace,
             });
         }
     }
@@ -740,7 +1579,7 @@ impl<'a> ContentParser<'a> {
         bytes
             .iter()
             .map(|&b| {
-                if b >= 32 && b < 127 {
+                if (32..127).contains(&b) {
                     b as char
                 } else if b >= 160 {
                     // Latin-1 supplement
@@ -753,6 +1592,56 @@ impl<'a> ContentParser<'a> {
     }
 }
 
+/// Number of components of a device color space, by its full name or its
+/// inline image abbreviation
+pub(crate) fn device_color_components(name: &str) -> Option<usize> {
+    match name {
+        "DeviceGray" | "G" => Some(1),
+        "DeviceRGB" | "RGB" => Some(3),
+        "DeviceCMYK" | "CMYK" => Some(4),
+        _ => None,
+    }
+}
+
+/// RGB equivalent of gray (1 component), RGB (3) or CMYK (4) color values
+fn rgb_from_components(components: &[f64]) -> Option<[f64; 3]> {
+    match *components {
+        [gray] => Some([gray; 3]),
+        [r, g, b] => Some([r, g, b]),
+        [c, m, y, k] => Some([(1.0 - c) * (1.0 - k), (1.0 - m) * (1.0 - k), (1.0 - y) * (1.0 - k)]),
+        _ => None,
+    }
+}
+
+/// Whether `op` is one of the operators defined for content streams
+fn is_pdf_operator(op: &str) -> bool {
+    matches!(
+        op,
+        "b" | "B" | "b*" | "B*" | "BDC" | "BI" | "BMC" | "BT" | "BX" | "c" | "cm" | "CS" | "cs"
+            | "d" | "d0" | "d1" | "Do" | "DP" | "EI" | "EMC" | "ET" | "EX" | "f" | "F" | "f*"
+            | "G" | "g" | "gs" | "h" | "i" | "ID" | "j" | "J" | "K" | "k" | "l" | "m" | "M"
+            | "MP" | "n" | "q" | "Q" | "re" | "RG" | "rg" | "ri" | "s" | "S" | "SC" | "sc"
+            | "SCN" | "scn" | "sh" | "T*" | "Tc" | "Td" | "TD" | "Tf" | "Tj" | "TJ" | "TL"
+            | "Tm" | "Tr" | "Ts" | "Tw" | "Tz" | "v" | "w" | "W" | "W*" | "y" | "'" | "\""
+    )
+}
+
+/// Full name of an abbreviated inline image dictionary key
+fn inline_image_key(key: &str) -> &str {
+    match key {
+        "BPC" => "BitsPerComponent",
+        "CS" => "ColorSpace",
+        "D" => "Decode",
+        "DP" => "DecodeParms",
+        "F" => "Filter",
+        "H" => "Height",
+        "IM" => "ImageMask",
+        "I" => "Interpolate",
+        "W" => "Width",
+        _ => key,
+    }
+}
+
 /// Operand types in content stream
 #[derive(Debug, Clone)]
 enum Operand {
@@ -760,6 +1649,7 @@ enum Operand {
     String(Vec<u8>),
     Name(String),
     Array(Vec<Operand>),
+    Dict(HashMap<String, Operand>),
 }
 
 fn hex_val(b: u8) -> u8 {
@@ -800,6 +1690,28 @@ mod tests {
         assert_eq!(spans[1].y, 480.0); // 500 - 20
     }
 
+    #[test]
+    fn test_parse_raw_keeps_operator_spans() {
+        let content = b"BT /F1 10 Tf 50 400 Td (Hel) Tj (lo) Tj 0 100 Td (Top) Tj ET";
+        let raw = ContentParser::new(content).parse_raw().unwrap();
+        let texts: Vec<_> = raw.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["Hel", "lo", "Top"]);
+
+        let merged = ContentParser::new(content).parse().unwrap();
+        let texts: Vec<_> = merged.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["Top", "Hello"]);
+    }
+
+    #[test]
+    fn test_operands_do_not_leak_past_operators() {
+        let content = b"BT /F1 10 Tf 50 500 Td (First) Tj 1 2 (x) foo 750 0 d0 /GS1 gs \
+            /CS0 cs /Pattern CS 0 -20 Td (Second) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+
+        let texts: Vec<_> = spans.iter().map(|s| (s.text.as_str(), s.y)).collect();
+        assert_eq!(texts, [("First", 500.0), ("Second", 480.0)]);
+    }
+
     #[test]
     fn test_tj_array() {
         let content = b"BT /F1 12 Tf 100 700 Td [(Hello) -100 (World)] TJ ET";
@@ -822,4 +1734,453 @@ mod tests {
         assert_eq!(spans[0].text, "Hello");
         assert_eq!(spans[1].text, "World");
     }
+
+    #[test]
+    fn test_advance_uses_glyph_widths() {
+        let mut encoding = FontEncoding::win_ansi();
+        encoding.set_widths(65, &[1000.0, 1000.0]);
+        let mut fonts = HashMap::new();
+        fonts.insert("F1".to_string(), encoding);
+
+        // "AB" is 2 * 1000/1000 * 12 = 24 wide, so "C" follows directly
+        let content = b"BT /F1 12 Tf 100 700 Td (AB) Tj (C) Tj ET";
+        let spans = ContentParser::with_fonts(content, fonts.clone()).parse().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "ABC");
+        // "C" has no width entry and falls back to 500
+        assert_eq!(spans[0].width, 30.0);
+
+        let content = b"BT /F1 12 Tf 100 700 Td [(AB) -2000 (C)] TJ ET";
+        let spans = ContentParser::with_fonts(content, fonts).parse().unwrap();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[1].text, "C");
+        assert_eq!(spans[1].x, 148.0);
+        assert_eq!(spans[0].width, 24.0);
+        assert_eq!(spans[0].height, 12.0);
+    }
+
+    #[test]
+    fn test_tj_advance_matches_glyph_widths() {
+        let mut encoding = FontEncoding::win_ansi();
+        encoding.set_widths(65, &[600.0, 400.0]);
+        let mut fonts = HashMap::new();
+        fonts.insert("F1".to_string(), encoding);
+
+        // A=6, B=4 at 10pt: (6 + 4) + 1 + (4 + 6) = 21, so "D" starts at 121
+        // and ends 5 later with the 500 fallback width
+        let content = b"BT /F1 10 Tf 100 700 Td [(AB) -100 (BA)] TJ (D) Tj ET";
+        let spans = ContentParser::with_fonts(content, fonts).parse().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "ABBAD");
+        assert_eq!(spans[0].width, 26.0);
+
+        // Two-byte codes advance once per code, not once per byte
+        let mut encoding = FontEncoding::identity();
+        encoding.set_two_byte(true);
+        encoding.set_width(1, 1000.0);
+        encoding.set_width(2, 500.0);
+        let mut fonts = HashMap::new();
+        fonts.insert("F2".to_string(), encoding);
+
+        let content = b"BT /F2 10 Tf 100 700 Td [<00010002> -500 <0001>] TJ ET";
+        let mut parser = ContentParser::with_fonts(content, fonts);
+        parser.run().unwrap();
+        assert_eq!(parser.state.x(), 130.0);
+    }
+
+    #[test]
+    fn test_horizontal_scaling_halves_advance() {
+        // (4 * 500 + 5000) / 1000 * 12 = 84 at full width
+        let content = b"BT /F1 12 Tf 100 700 Td [(AAAA) -5000 (B)] TJ ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans[1].x, 184.0);
+
+        let content = b"BT /F1 12 Tf 50 Tz 100 700 Td [(AAAA) -5000 (B)] TJ ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans[1].text, "B");
+        assert_eq!(spans[1].x, 142.0);
+    }
+
+    #[test]
+    fn test_word_spacing_widens_space_advance() {
+        // Without Tw the space is an ordinary word gap
+        let content = b"BT /F1 12 Tf 100 700 Td (Hello ) Tj (World) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "Hello World");
+
+        // A wide Tw separates the words, but only after the space code
+        let content = b"BT /F1 12 Tf 20 Tw 100 700 Td (Hello ) Tj (World) Tj ET";
+        let spans = ContentParser::new(content).parse_raw().unwrap();
+        assert_eq!(spans[0].width, 30.0);
+        assert_eq!(spans[1].x, 156.0);
+        let spans = ContentParser::new(content).parse().unwrap();
+        let texts: Vec<_> = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hello", "World"]);
+
+        // Two-byte codes never get word spacing
+        let mut encoding = FontEncoding::identity();
+        encoding.set_two_byte(true);
+        let fonts = HashMap::from([("F2".to_string(), encoding)]);
+        let content = b"BT /F2 10 Tf 50 Tw 100 700 Td <00200020> Tj ET";
+        let mut parser = ContentParser::with_fonts(content, fonts);
+        parser.run().unwrap();
+        assert_eq!(parser.state.x(), 110.0);
+    }
+
+    #[test]
+    fn test_char_spacing_adds_to_every_glyph() {
+        let end_x = |content: &[u8]| {
+            let mut parser = ContentParser::new(content);
+            parser.run().unwrap();
+            parser.state.x()
+        };
+        // Five glyphs of 6 units at 12pt
+        assert_eq!(end_x(b"BT /F1 12 Tf 0 Tc 100 700 Td (A B C) Tj ET"), 130.0);
+        assert_eq!(end_x(b"BT /F1 12 Tf 5 Tc 100 700 Td (A B C) Tj ET"), 155.0);
+        // Spaces get both, and horizontal scaling applies to the spacing
+        assert_eq!(end_x(b"BT /F1 12 Tf 5 Tc 2 Tw 100 700 Td (A B C) Tj ET"), 159.0);
+        assert_eq!(end_x(b"BT /F1 12 Tf 5 Tc 50 Tz 100 700 Td (A B C) Tj ET"), 127.5);
+
+        // Expanded text stays one span: the spacing is part of its width
+        let content = b"BT /F1 12 Tf 5 Tc 100 700 Td (Total) Tj (s) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "Totals");
+    }
+
+    #[test]
+    fn test_text_rise() {
+        let content = b"BT /F1 12 Tf 100 700 Td (x) Tj 4 Ts (2) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+
+        let rise = spans.iter().find(|s| s.rise != 0.0).unwrap();
+        assert_eq!(rise.y, 704.0);
+        assert_eq!(rise.baseline(), 700.0);
+    }
+
+    #[test]
+    fn test_fill_color() {
+        let content = b"BT /F1 12 Tf 100 700 Td (Total) Tj 1 0 0 rg (-5.00) Tj \
+                        0.5 g 0 -20 Td (Gray) Tj 0 0 0 1 k 0 -20 Td (Black) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+
+        let colors: Vec<_> = spans.iter().map(|s| (s.text.as_str(), s.color)).collect();
+        assert_eq!(
+            colors,
+            vec![
+                ("Total", None),
+                ("-5.00", Some([255, 0, 0])),
+                ("Gray", Some([128, 128, 128])),
+                ("Black", Some([0, 0, 0])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fill_color_space() {
+        let content = b"BT /F1 12 Tf 100 700 Td /CS0 cs 1 scn (Spot) Tj \
+                        0 -20 Td /CS1 cs 0 0 1 scn (Icc) Tj 0 -20 Td /DeviceCMYK cs (Initial) Tj \
+                        0 -20 Td 0 0 0 sc (Short) Tj 0 -20 Td 0.5 g (Gray) Tj ET";
+        let spans = ContentParser::new(content)
+            .with_color_spaces(HashMap::from([("CS1".to_string(), 3)]))
+            .parse()
+            .unwrap();
+
+        let colors: Vec<_> = spans.iter().map(|s| (s.text.as_str(), s.color)).collect();
+        assert_eq!(
+            colors,
+            vec![
+                ("Spot", None),
+                ("Icc", Some([0, 0, 255])),
+                ("Initial", Some([0, 0, 0])),
+                ("Short", None),
+                ("Gray", Some([128, 128, 128])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invisible_text_skipped() {
+        let content = b"BT /F1 12 Tf 100 700 Td (Visible) Tj 3 Tr 0 -20 Td (Hidden) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "Visible");
+
+        let spans = ContentParser::new(content)
+            .include_invisible_text(true)
+            .parse()
+            .unwrap();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[1].text, "Hidden");
+    }
+
+    #[test]
+    fn test_ctm_applied_to_positions() {
+        let content = b"2 0 0 2 10 20 cm BT /F1 12 Tf 100 300 Td (Hi) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans[0].x, 210.0);
+        assert_eq!(spans[0].y, 620.0);
+    }
+
+    #[test]
+    fn test_ctm_restored_by_q() {
+        let content = b"q 1 0 0 1 50 50 cm Q BT /F1 12 Tf 100 700 Td (Hi) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans[0].x, 100.0);
+        assert_eq!(spans[0].y, 700.0);
+    }
+
+    #[test]
+    fn test_advance_follows_text_matrix_scale() {
+        // Text space is scaled 10x, so a 6 unit advance moves 60 points
+        let content = b"BT /F1 1 Tf 10 0 0 10 100 700 Tm [(AB) -5000 (C)] TJ ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans[1].text, "C");
+        assert_eq!(spans[1].x, 160.0);
+    }
+
+    #[test]
+    fn test_words_split_at_gaps_and_spaces() {
+        // "Hello world" in one string, then "again" after a word-sized gap
+        let content = b"BT /F1 10 Tf 100 700 Td (Hello world) Tj 68 0 Td (again) Tj ET";
+        let words = ContentParser::new(content).parse_words().unwrap();
+
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hello", "world", "again"]);
+        assert_eq!(words[0].x, 100.0);
+        assert_eq!(words[0].width, 25.0);
+        assert_eq!(words[1].x, 130.0);
+        assert_eq!(words[2].x, 168.0);
+        assert!(words.iter().all(|w| w.y == 700.0));
+    }
+
+    #[test]
+    fn test_words_from_per_glyph_positioning() {
+        let content = b"BT /F1 10 Tf 1 0 0 1 100 700 Tm (H) Tj 1 0 0 1 105 700 Tm (i) Tj \
+                        1 0 0 1 140 700 Tm (y) Tj ET";
+        let words = ContentParser::new(content).parse_words().unwrap();
+
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hi", "y"]);
+        assert_eq!(words[0].width, 10.0);
+    }
+
+    #[test]
+    fn test_merge_config_word_gap() {
+        // 4pt gap between "Net" and "income" at 10pt: a word space by default
+        let content = b"BT /F1 10 Tf 100 700 Td (Net) Tj 19 0 Td (income) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "Net income");
+
+        // Treating anything above half a character as a column break splits them
+        let config = MergeConfig {
+            new_span_gap_ratio: 0.5,
+            word_gap_ratio: 0.2,
+            ..MergeConfig::default()
+        };
+        let spans = ContentParser::new(content).with_config(config).parse().unwrap();
+        assert_eq!(spans.len(), 2);
+    }
+
+    #[test]
+    fn test_normalize_ligatures() {
+        let cmap = b"beginbfchar\n<0001> <FB01>\n<006E> <006E>\n<0064> <0064>\nendbfchar";
+        let map = crate::font::parse_tounicode_cmap(cmap).unwrap();
+        let mut fonts = HashMap::new();
+        fonts.insert("F1".to_string(), FontEncoding::from_cid_map(map));
+        let content = b"BT /F1 12 Tf 100 700 Td <0001006E0064> Tj ET";
+
+        let spans = ContentParser::with_fonts(content, fonts.clone()).parse().unwrap();
+        assert_eq!(spans[0].text, "\u{FB01}nd");
+
+        let spans = ContentParser::with_fonts(content, fonts)
+            .normalize_ligatures(true)
+            .parse()
+            .unwrap();
+        assert_eq!(spans[0].text, "find");
+    }
+
+    #[test]
+    fn test_inline_image_is_skipped() {
+        // Unfiltered data is skipped by size, even though it contains " EI("
+        let content = b"BT /F1 12 Tf 100 700 Td (Before) Tj ET \
+            BI /W 4 /H 1 /BPC 8 /CS /G ID \x00 EI( EI \
+            BT /F1 12 Tf 100 600 Td (After) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        let texts: Vec<&str> = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Before", "After"]);
+
+        // Image masks are one bit per pixel
+        let content = b"BI /IM true /W 8 /H 2 ID EI EI (After) Tj";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "After");
+
+        // A size too large to compute is scanned for EI like filtered data
+        let content = b"BI /W 99999999999999999999 /H 2 /BPC 8 /CS /G ID \x00 EI (After) Tj";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "After");
+
+        // Filtered data is scanned for a delimited EI
+        let content = b"BT /F1 12 Tf 100 700 Td (Before) Tj ET \
+            BI /W 2 /H 2 /BPC 8 /CS /RGB /F /AHx ID 0EI1 (Tj) EIx> EI \
+            BT /F1 12 Tf 100 600 Td (After) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        let texts: Vec<&str> = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Before", "After"]);
+    }
+
+    #[test]
+    fn test_marked_content_actual_text() {
+        let content = b"BT /F1 12 Tf 100 700 Td \
+            /Span <</ActualText <FEFF0066006900660074> /Lang (en)>> BDC (X) Tj \
+            /P <</MCID 3>> BDC (t) Tj EMC EMC \
+            /Artifact BMC 0 -20 Td (one) Tj EMC \
+            /Span <</ActualText (unused)>> BDC EMC ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        let texts: Vec<&str> = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["fift", "one"]);
+        assert_eq!(spans[0].x, 100.0);
+
+        // An unbalanced EMC is ignored
+        let content = b"BT /F1 12 Tf 100 700 Td EMC (Text) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans[0].text, "Text");
+    }
+
+    #[test]
+    fn test_stray_delimiters_skipped() {
+        let content = b"] BT /F1 12 Tf > 100 700 Td ]> (Text) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans[0].text, "Text");
+    }
+
+    #[test]
+    fn test_effective_font_size_from_text_matrix() {
+        let content = b"BT /F1 24 Tf 0.5 0 0 0.5 100 700 Tm (Small) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!(spans[0].font_size, 12.0);
+
+        // Sizes set through the matrix rather than Tf still find headings
+        let content = b"BT /F1 1 Tf 24 0 0 24 50 700 Tm (Title) Tj \
+            12 0 0 12 50 670 Tm (Body text set at twelve points.) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        let elements = crate::extract::classify_spans(spans);
+        assert!(matches!(&elements[0], crate::extract::PageElement::Heading { level: 1, .. }));
+    }
+
+    #[test]
+    fn test_malformed_tm_operands() {
+        // A stray name before the matrix
+        let content = b"BT /F1 12 Tf /X 1 0 0 1 100 500 Tm (Hi) Tj ET";
+        let (spans, report) = ContentParser::new(content).parse_with_report().unwrap();
+        assert_eq!((spans[0].x, spans[0].y), (100.0, 500.0));
+        assert_eq!(report.warnings, ["1 Tm operators without six numeric operands"]);
+
+        // Too few numbers keep just the translation
+        let content = b"BT /F1 12 Tf 2 0 0 200 300 Tm (Hi) Tj ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert_eq!((spans[0].x, spans[0].y, spans[0].font_size), (200.0, 300.0, 12.0));
+
+        let content = b"BT /F1 12 Tf 1 0 0 1 100 500 Tm (Hi) Tj ET";
+        let (_, report) = ContentParser::new(content).parse_with_report().unwrap();
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_compatibility_section() {
+        // The vendor operator's operands don't reach the Td after it
+        let content = b"BT /F1 12 Tf 100 700 Td (Before) Tj \
+            BX /Vendor 3 4 5 vendorOp BX 1 nestedOp EX EX \
+            0 -20 Td (After) Tj ET";
+        let (spans, report) = ContentParser::new(content).parse_with_report().unwrap();
+        let texts: Vec<_> = spans.iter().map(|s| (s.text.as_str(), s.x, s.y)).collect();
+        assert_eq!(texts, [("Before", 100.0, 700.0), ("After", 100.0, 680.0)]);
+        assert!(report.warnings.is_empty());
+
+        // Outside a section the same operator is reported
+        let content = b"BT /F1 12 Tf 100 700 Td 3 4 vendorOp (Text) Tj ET";
+        let (spans, report) = ContentParser::new(content).parse_with_report().unwrap();
+        assert_eq!(spans[0].text, "Text");
+        assert_eq!(report.warnings, ["1 unknown operators"]);
+    }
+
+    #[test]
+    fn test_form_drawing_itself_stops() {
+        let forms = FormXObjects {
+            forms: vec![FormXObject {
+                content: b"BT /F1 10 Tf (x) Tj ET 1 0 0 1 0 20 cm /Fm0 Do".to_vec(),
+                matrix: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+                fonts: None,
+                names: None,
+                color_spaces: None,
+            }],
+            names: HashMap::from([("Fm0".to_string(), 0)]),
+        };
+        let content = b"/Fm0 Do BT /F1 10 Tf 0 -20 Td (after) Tj ET";
+        let spans = ContentParser::new(content).with_forms(&forms).parse().unwrap();
+        assert_eq!(spans.len(), MAX_FORM_DEPTH + 1);
+        assert_eq!(spans[0].y, 20.0 * (MAX_FORM_DEPTH - 1) as f64);
+        // The form's cm does not leak out
+        assert_eq!(spans.last().map(|s| (s.text.as_str(), s.y)), Some(("after", -20.0)));
+    }
+
+    #[test]
+    fn test_reorder_rtl() {
+        // "shalom olam" drawn glyph by glyph from right to left, with a font
+        // whose codes are the Unicode values
+        let content = b"BT /F1 10 Tf 200 700 Td <05E9> Tj -6 0 Td <05DC> Tj -6 0 Td <05D5> Tj \
+                        -6 0 Td <05DD> Tj -6 0 Td <0020> Tj -6 0 Td <05E2> Tj \
+                        -6 0 Td <05D5> Tj -6 0 Td <05DC> Tj -6 0 Td <05DD> Tj ET";
+        let mut font = FontEncoding::from_cid_map(
+            "\u{5e9}\u{5dc}\u{5d5}\u{5dd}\u{5e2} ".chars().map(|c| (c as u16, c)).collect(),
+        );
+        font.set_two_byte(true);
+        let fonts = HashMap::from([("F1".to_string(), font)]);
+
+        let spans = ContentParser::with_fonts(content, fonts.clone()).parse().unwrap();
+        assert_eq!(spans[0].text, "\u{5dd}\u{5dc}\u{5d5}\u{5e2} \u{5dd}\u{5d5}\u{5dc}\u{5e9}");
+
+        let spans = ContentParser::with_fonts(content, fonts).reorder_rtl(true).parse().unwrap();
+        assert_eq!(spans[0].text, "\u{5e9}\u{5dc}\u{5d5}\u{5dd} \u{5e2}\u{5d5}\u{5dc}\u{5dd}");
+    }
+
+    #[test]
+    fn test_reorder_rtl_line_of_several_spans() {
+        // "shalom" and "olam" as separate spans, the first word on the right
+        let content = b"BT /F1 10 Tf 100 700 Td <05DD05DC05D505E2> Tj \
+                        100 0 Td <05DD05D505DC05E9> Tj 0 -20 Td <05E9> Tj ET";
+        let mut font = FontEncoding::from_cid_map(
+            "\u{5e9}\u{5dc}\u{5d5}\u{5dd}\u{5e2}".chars().map(|c| (c as u16, c)).collect(),
+        );
+        font.set_two_byte(true);
+        let fonts = HashMap::from([("F1".to_string(), font)]);
+
+        let (shalom, olam) = ("\u{5e9}\u{5dc}\u{5d5}\u{5dd}", "\u{5e2}\u{5d5}\u{5dc}\u{5dd}");
+
+        let parser = ContentParser::with_fonts(content, fonts.clone()).reorder_rtl(true);
+        let spans = parser.parse().unwrap();
+        let found: Vec<_> = spans.iter().map(|s| (s.text.as_str(), s.x)).collect();
+        assert_eq!(found, [(shalom, 200.0), (olam, 100.0), ("\u{5e9}", 200.0)]);
+
+        let words = ContentParser::with_fonts(content, fonts).reorder_rtl(true).parse_words();
+        let texts: Vec<_> = words.unwrap().into_iter().map(|w| w.text).collect();
+        assert_eq!(texts, [shalom, olam, "\u{5e9}"]);
+    }
+
+    #[test]
+    fn test_quote_operators_default_leading_to_font_size() {
+        let content = b"BT /F1 10 Tf 72 700 Td (One) Tj (Two) ' 0 0 (Three) \" ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        let ys: Vec<f64> = spans.iter().map(|s| s.y).collect();
+        assert_eq!(ys, [700.0, 690.0, 680.0]);
+
+        // An explicit zero leading is kept
+        let content = b"BT /F1 10 Tf 0 TL 72 700 Td (One) Tj (Two) ' ET";
+        let spans = ContentParser::new(content).parse().unwrap();
+        assert!(spans.iter().all(|s| s.y == 700.0));
+    }
 }
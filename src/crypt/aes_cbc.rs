@@ -0,0 +1,118 @@
+use aes::cipher::block_padding::NoPadding;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes::{Aes128, Aes256};
+
+use crate::error::{PdfError, Result};
+
+/// Encrypt whole blocks of `data` with AES-128 or AES-256 (by the key
+/// length) in CBC mode, without padding; a partial last block is dropped
+pub fn cbc_encrypt(key: &[u8], iv: &[u8; 16], data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = whole_blocks(data);
+    let len = out.len();
+    let encrypted = match key.len() {
+        16 => cbc::Encryptor::<Aes128>::new(key.into(), iv.into())
+            .encrypt_padded_mut::<NoPadding>(&mut out, len)
+            .map(|_| ()),
+        32 => cbc::Encryptor::<Aes256>::new(key.into(), iv.into())
+            .encrypt_padded_mut::<NoPadding>(&mut out, len)
+            .map(|_| ()),
+        n => return Err(unsupported_key_length(n)),
+    };
+    encrypted.map_err(|_| PdfError::InvalidStructure("AES data is not whole blocks".into()))?;
+    Ok(out)
+}
+
+/// Decrypt whole blocks of `data` in CBC mode without removing padding; a
+/// partial last block is dropped
+pub fn cbc_decrypt(key: &[u8], iv: &[u8; 16], data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = whole_blocks(data);
+    let decrypted = match key.len() {
+        16 => cbc::Decryptor::<Aes128>::new(key.into(), iv.into())
+            .decrypt_padded_mut::<NoPadding>(&mut out)
+            .map(|_| ()),
+        32 => cbc::Decryptor::<Aes256>::new(key.into(), iv.into())
+            .decrypt_padded_mut::<NoPadding>(&mut out)
+            .map(|_| ()),
+        n => return Err(unsupported_key_length(n)),
+    };
+    decrypted.map_err(|_| PdfError::InvalidStructure("AES data is not whole blocks".into()))?;
+    Ok(out)
+}
+
+/// Decrypt PDF AES data: a 16-byte IV followed by CBC ciphertext with
+/// PKCS#7 padding. Bad padding is tolerated and left in place.
+pub fn aes_cbc_decrypt(key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < 16 {
+        return Ok(Vec::new());
+    }
+    let iv: [u8; 16] = std::array::from_fn(|i| data[i]);
+    let mut out = cbc_decrypt(key, &iv, &data[16..])?;
+
+    if let Some(&pad) = out.last() {
+        let pad = pad as usize;
+        if (1..=16).contains(&pad)
+            && pad <= out.len()
+            && out[out.len() - pad..].iter().all(|&b| b as usize == pad)
+        {
+            out.truncate(out.len() - pad);
+        }
+    }
+    Ok(out)
+}
+
+/// `data` cut down to a multiple of the 16-byte block size
+fn whole_blocks(data: &[u8]) -> Vec<u8> {
+    data[..data.len() - data.len() % 16].to_vec()
+}
+
+fn unsupported_key_length(len: usize) -> PdfError {
+    PdfError::InvalidStructure(format!("Unsupported AES key length: {} bytes", len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_cbc_sp800_38a_vectors() {
+        // NIST SP 800-38A F.2.1 and F.2.5, first two blocks
+        let iv: [u8; 16] = unhex("000102030405060708090a0b0c0d0e0f").try_into().unwrap();
+        let plaintext =
+            unhex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        for (key, expected) in [
+            (
+                "2b7e151628aed2a6abf7158809cf4f3c",
+                "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2",
+            ),
+            (
+                "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+                "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d",
+            ),
+        ] {
+            let key = unhex(key);
+            let ciphertext = cbc_encrypt(&key, &iv, &plaintext).unwrap();
+            assert_eq!(ciphertext, unhex(expected));
+            assert_eq!(cbc_decrypt(&key, &iv, &ciphertext).unwrap(), plaintext);
+        }
+        assert!(cbc_encrypt(&[0; 24], &iv, &plaintext).is_err());
+    }
+
+    #[test]
+    fn test_cbc_round_trip_with_padding() {
+        let key = [7u8; 16];
+        let iv = [3u8; 16];
+        let mut padded = b"hello, pdf".to_vec();
+        padded.extend([6u8; 6]);
+
+        let mut data = iv.to_vec();
+        data.extend(cbc_encrypt(&key, &iv, &padded).unwrap());
+        assert_eq!(aes_cbc_decrypt(&key, &data).unwrap(), b"hello, pdf");
+    }
+}
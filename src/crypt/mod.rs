@@ -0,0 +1,391 @@
+mod aes_cbc;
+mod rc4;
+
+use std::collections::HashMap;
+
+use md5::{Digest, Md5};
+use sha2::{Sha256, Sha384, Sha512};
+
+use crate::error::{PdfError, Result};
+use crate::types::{ObjRef, PdfObject};
+
+use aes_cbc::{aes_cbc_decrypt, cbc_decrypt, cbc_encrypt};
+use rc4::rc4;
+
+/// Padding string used to extend passwords to 32 bytes (Algorithm 2)
+const PASSWORD_PAD: [u8; 32] = [
+    0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA, 0x01, 0x08,
+    0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE, 0x64, 0x53, 0x69, 0x7A,
+];
+
+/// Cipher applied to strings or streams
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CryptMethod {
+    Identity,
+    Rc4,
+    AesV2,
+    AesV3,
+}
+
+/// Standard security handler, opened with the empty user password
+#[derive(Debug, Clone)]
+pub struct SecurityHandler {
+    key: Vec<u8>,
+    string_method: CryptMethod,
+    stream_method: CryptMethod,
+    encrypt_metadata: bool,
+}
+
+impl SecurityHandler {
+    /// Derive the file key from the /Encrypt dictionary and the first
+    /// element of the trailer /ID
+    pub fn new(encrypt: &HashMap<String, PdfObject>, id: &[u8]) -> Result<Self> {
+        let filter = encrypt.get("Filter").and_then(|f| f.as_name()).unwrap_or("");
+        if filter != "Standard" {
+            return Err(PdfError::UnsupportedEncryption(format!(
+                "security handler /{}",
+                filter
+            )));
+        }
+
+        let int = |key: &str| encrypt.get(key).and_then(|v| v.as_int());
+        let bytes = |key: &str| encrypt.get(key).and_then(|v| v.as_string()).unwrap_or(&[]);
+        let v = int("V").unwrap_or(0);
+        let r = int("R").unwrap_or(2);
+        let encrypt_metadata =
+            !matches!(encrypt.get("EncryptMetadata"), Some(PdfObject::Bool(false)));
+
+        let (string_method, stream_method, key_len) = match v {
+            1 => (CryptMethod::Rc4, CryptMethod::Rc4, 5),
+            2 | 3 => {
+                let bits = int("Length").unwrap_or(40);
+                (CryptMethod::Rc4, CryptMethod::Rc4, (bits / 8).clamp(5, 16) as usize)
+            }
+            4 | 5 => {
+                let method = |key: &str| crypt_filter_method(encrypt, key);
+                let bits = int("Length").unwrap_or(128);
+                let key_len = if v == 5 { 32 } else { (bits / 8).clamp(5, 16) as usize };
+                (method("StrF")?, method("StmF")?, key_len)
+            }
+            _ => return Err(PdfError::UnsupportedEncryption(format!("/V {}", v))),
+        };
+
+        let (o, u) = (bytes("O"), bytes("U"));
+        let key = match r {
+            2..=4 => {
+                let p = int("P").unwrap_or(0) as u32;
+                let key = file_key_r4(b"", o, p, id, r, key_len, encrypt_metadata);
+                if !user_password_matches_r4(&key, u, id, r) {
+                    return Err(PdfError::PasswordRequired);
+                }
+                key
+            }
+            5 | 6 => file_key_r6(b"", u, bytes("UE"), r)?,
+            _ => return Err(PdfError::UnsupportedEncryption(format!("/R {}", r))),
+        };
+
+        Ok(Self {
+            key,
+            string_method,
+            stream_method,
+            encrypt_metadata,
+        })
+    }
+
+    /// Decrypt every string and stream in an indirect object
+    pub fn decrypt_object(&self, obj_ref: ObjRef, obj: PdfObject) -> PdfObject {
+        match obj {
+            PdfObject::String(s) => {
+                PdfObject::String(self.decrypt(obj_ref, &s, self.string_method))
+            }
+            PdfObject::Array(items) => PdfObject::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.decrypt_object(obj_ref, item))
+                    .collect(),
+            ),
+            PdfObject::Dict(dict) => PdfObject::Dict(self.decrypt_dict(obj_ref, dict)),
+            PdfObject::Stream { dict, data } => {
+                let kind = dict.get("Type").and_then(|t| t.as_name());
+                // Cross-reference streams are never encrypted; metadata
+                // streams only when /EncryptMetadata is true
+                let plain = kind == Some("XRef")
+                    || (kind == Some("Metadata") && !self.encrypt_metadata);
+                let data = if plain {
+                    data
+                } else {
+                    self.decrypt(obj_ref, &data, self.stream_method)
+                };
+                PdfObject::Stream {
+                    dict: self.decrypt_dict(obj_ref, dict),
+                    data,
+                }
+            }
+            other => other,
+        }
+    }
+
+    fn decrypt_dict(
+        &self,
+        obj_ref: ObjRef,
+        dict: HashMap<String, PdfObject>,
+    ) -> HashMap<String, PdfObject> {
+        dict.into_iter()
+            .map(|(k, v)| (k, self.decrypt_object(obj_ref, v)))
+            .collect()
+    }
+
+    fn decrypt(&self, obj_ref: ObjRef, data: &[u8], method: CryptMethod) -> Vec<u8> {
+        match method {
+            CryptMethod::Identity => data.to_vec(),
+            CryptMethod::Rc4 => rc4(&self.object_key(obj_ref, false), data),
+            CryptMethod::AesV2 => aes_cbc_decrypt(&self.object_key(obj_ref, true), data)
+                .unwrap_or_else(|_| data.to_vec()),
+            CryptMethod::AesV3 => {
+                aes_cbc_decrypt(&self.key, data).unwrap_or_else(|_| data.to_vec())
+            }
+        }
+    }
+
+    /// Per-object key (Algorithm 1): the file key salted with the object
+    /// and generation numbers
+    fn object_key(&self, obj_ref: ObjRef, aes: bool) -> Vec<u8> {
+        let mut input = self.key.clone();
+        input.extend_from_slice(&obj_ref.obj_num.to_le_bytes()[..3]);
+        input.extend_from_slice(&obj_ref.gen_num.to_le_bytes());
+        if aes {
+            input.extend_from_slice(b"sAlT");
+        }
+        let len = (self.key.len() + 5).min(16);
+        Md5::digest(&input)[..len].to_vec()
+    }
+}
+
+/// Cipher named by the /StrF or /StmF crypt filter of a V4/V5 dictionary
+fn crypt_filter_method(encrypt: &HashMap<String, PdfObject>, key: &str) -> Result<CryptMethod> {
+    let name = encrypt.get(key).and_then(|n| n.as_name()).unwrap_or("Identity");
+    if name == "Identity" {
+        return Ok(CryptMethod::Identity);
+    }
+
+    let cfm = encrypt
+        .get("CF")
+        .and_then(|cf| cf.as_dict())
+        .and_then(|cf| cf.get(name))
+        .and_then(|filter| filter.as_dict())
+        .and_then(|filter| filter.get("CFM"))
+        .and_then(|cfm| cfm.as_name())
+        .unwrap_or("None");
+
+    match cfm {
+        "None" => Ok(CryptMethod::Identity),
+        "V2" => Ok(CryptMethod::Rc4),
+        "AESV2" => Ok(CryptMethod::AesV2),
+        "AESV3" => Ok(CryptMethod::AesV3),
+        other => Err(PdfError::UnsupportedEncryption(format!("crypt filter /{}", other))),
+    }
+}
+
+/// Pad or truncate a password to 32 bytes
+fn pad_password(password: &[u8]) -> Vec<u8> {
+    let mut padded: Vec<u8> = password.iter().take(32).copied().collect();
+    padded.extend_from_slice(&PASSWORD_PAD[..32 - padded.len()]);
+    padded
+}
+
+/// File key for revisions 2-4 (Algorithm 2)
+fn file_key_r4(
+    password: &[u8],
+    o: &[u8],
+    p: u32,
+    id: &[u8],
+    r: i64,
+    key_len: usize,
+    encrypt_metadata: bool,
+) -> Vec<u8> {
+    let mut input = pad_password(password);
+    input.extend_from_slice(o);
+    input.extend_from_slice(&p.to_le_bytes());
+    input.extend_from_slice(id);
+    if r >= 4 && !encrypt_metadata {
+        input.extend_from_slice(&[0xFF; 4]);
+    }
+
+    let key_len = if r == 2 { 5 } else { key_len };
+    let mut hash = Md5::digest(&input);
+    if r >= 3 {
+        for _ in 0..50 {
+            hash = Md5::digest(&hash[..key_len]);
+        }
+    }
+    hash[..key_len].to_vec()
+}
+
+/// The /U value a key produces (Algorithms 4 and 5)
+fn user_password_hash_r4(key: &[u8], id: &[u8], r: i64) -> Vec<u8> {
+    if r == 2 {
+        return rc4(key, &PASSWORD_PAD);
+    }
+
+    let mut input = PASSWORD_PAD.to_vec();
+    input.extend_from_slice(id);
+    let mut hash = rc4(key, &Md5::digest(&input));
+    for i in 1..=19u8 {
+        let round_key: Vec<u8> = key.iter().map(|b| b ^ i).collect();
+        hash = rc4(&round_key, &hash);
+    }
+    hash
+}
+
+/// Check a revision 2-4 key against /U. Revision 3+ only fixes the first
+/// 16 bytes.
+fn user_password_matches_r4(key: &[u8], u: &[u8], id: &[u8], r: i64) -> bool {
+    let expected = user_password_hash_r4(key, id, r);
+    let n = if r == 2 { 32 } else { 16 };
+    u.len() >= n && expected.len() >= n && u[..n] == expected[..n]
+}
+
+/// File key for revisions 5 and 6: validate the password against /U, then
+/// unwrap /UE with a key derived from the password and the key salt
+fn file_key_r6(password: &[u8], u: &[u8], ue: &[u8], r: i64) -> Result<Vec<u8>> {
+    if u.len() < 48 || ue.len() < 32 {
+        return Err(PdfError::UnsupportedEncryption("malformed /U or /UE".into()));
+    }
+    let password = &password[..password.len().min(127)];
+    let (hash, validation_salt, key_salt) = (&u[..32], &u[32..40], &u[40..48]);
+
+    if hash_r6(password, validation_salt, r) != hash {
+        return Err(PdfError::PasswordRequired);
+    }
+
+    let intermediate = hash_r6(password, key_salt, r);
+    cbc_decrypt(&intermediate, &[0; 16], &ue[..32])
+}
+
+/// Password hash: plain SHA-256 for revision 5, Algorithm 2.B for 6
+fn hash_r6(password: &[u8], salt: &[u8], r: i64) -> [u8; 32] {
+    let mut input = password.to_vec();
+    input.extend_from_slice(salt);
+    if r == 5 {
+        return Sha256::digest(&input).into();
+    }
+    let mut k = Sha256::digest(&input).to_vec();
+
+    let mut round = 0usize;
+    loop {
+        let mut block = password.to_vec();
+        block.extend_from_slice(&k);
+        let k1 = block.repeat(64);
+
+        let iv: [u8; 16] = std::array::from_fn(|i| k[16 + i]);
+        let e = cbc_encrypt(&k[..16], &iv, &k1).expect("16-byte key");
+
+        k = match e[..16].iter().map(|&b| b as u32).sum::<u32>() % 3 {
+            0 => Sha256::digest(&e).to_vec(),
+            1 => Sha384::digest(&e).to_vec(),
+            _ => Sha512::digest(&e).to_vec(),
+        };
+
+        round += 1;
+        if round >= 64 && (*e.last().unwrap() as usize) <= round - 32 {
+            break;
+        }
+    }
+
+    std::array::from_fn(|i| k[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// /Encrypt dictionary for an RC4 or AESV2 file with an empty user
+    /// password
+    fn encrypt_dict_r4(aes: bool, id: &[u8]) -> HashMap<String, PdfObject> {
+        let (r, o, p) = (if aes { 4 } else { 3 }, vec![0x42u8; 32], -3904i64);
+        let key = file_key_r4(b"", &o, p as u32, id, r, 16, true);
+        let mut u = user_password_hash_r4(&key, id, r);
+        u.resize(32, 0);
+
+        let mut dict = HashMap::new();
+        dict.insert("Filter".to_string(), PdfObject::Name("Standard".into()));
+        dict.insert("V".to_string(), PdfObject::Int(if aes { 4 } else { 2 }));
+        dict.insert("R".to_string(), PdfObject::Int(r));
+        dict.insert("Length".to_string(), PdfObject::Int(128));
+        dict.insert("O".to_string(), PdfObject::String(o));
+        dict.insert("U".to_string(), PdfObject::String(u));
+        dict.insert("P".to_string(), PdfObject::Int(p));
+        if aes {
+            let mut std_cf = HashMap::new();
+            std_cf.insert("CFM".to_string(), PdfObject::Name("AESV2".into()));
+            let mut cf = HashMap::new();
+            cf.insert("StdCF".to_string(), PdfObject::Dict(std_cf));
+            dict.insert("CF".to_string(), PdfObject::Dict(cf));
+            dict.insert("StmF".to_string(), PdfObject::Name("StdCF".into()));
+            dict.insert("StrF".to_string(), PdfObject::Name("StdCF".into()));
+        }
+        dict
+    }
+
+    #[test]
+    fn test_rc4_round_trip() {
+        let id = b"0123456789abcdef";
+        let dict = encrypt_dict_r4(false, id);
+        let handler = SecurityHandler::new(&dict, id).unwrap();
+
+        let obj_ref = ObjRef::new(7, 0);
+        let ciphertext = rc4(&handler.object_key(obj_ref, false), b"secret");
+        let obj = handler.decrypt_object(obj_ref, PdfObject::String(ciphertext));
+        assert_eq!(obj, PdfObject::String(b"secret".to_vec()));
+    }
+
+    #[test]
+    fn test_aes_round_trip() {
+        let id = b"0123456789abcdef";
+        let dict = encrypt_dict_r4(true, id);
+        let handler = SecurityHandler::new(&dict, id).unwrap();
+
+        let obj_ref = ObjRef::new(4, 0);
+        let iv = [9u8; 16];
+        let mut padded = b"BT (Hi) Tj ET".to_vec();
+        padded.extend([3u8; 3]);
+        let mut data = iv.to_vec();
+        data.extend(cbc_encrypt(&handler.object_key(obj_ref, true), &iv, &padded).unwrap());
+
+        let stream = PdfObject::Stream {
+            dict: HashMap::new(),
+            data,
+        };
+        let decrypted = handler.decrypt_object(obj_ref, stream);
+        assert_eq!(decrypted.as_stream().unwrap().1, b"BT (Hi) Tj ET");
+    }
+
+    #[test]
+    fn test_user_password_required() {
+        let id = b"0123456789abcdef";
+        let mut dict = encrypt_dict_r4(false, id);
+        dict.insert("U".to_string(), PdfObject::String(vec![0; 32]));
+        assert!(matches!(
+            SecurityHandler::new(&dict, id),
+            Err(PdfError::PasswordRequired)
+        ));
+    }
+
+    #[test]
+    fn test_r6_key_unwrap() {
+        // Build /U and /UE for the empty password with a known file key
+        let file_key = [0x5Au8; 32];
+        let (validation_salt, key_salt) = ([1u8; 8], [2u8; 8]);
+        let mut u = hash_r6(b"", &validation_salt, 6).to_vec();
+        u.extend_from_slice(&validation_salt);
+        u.extend_from_slice(&key_salt);
+        let intermediate = hash_r6(b"", &key_salt, 6);
+        let ue = cbc_encrypt(&intermediate, &[0; 16], &file_key).unwrap();
+
+        assert_eq!(file_key_r6(b"", &u, &ue, 6).unwrap(), file_key);
+        assert!(matches!(
+            file_key_r6(b"", &u, &ue, 5),
+            Err(PdfError::PasswordRequired)
+        ));
+    }
+}
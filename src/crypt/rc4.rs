@@ -0,0 +1,51 @@
+/// Encrypt or decrypt `data` with RC4 (the operation is symmetric)
+pub fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    if key.is_empty() {
+        return data.to_vec();
+    }
+
+    // Key scheduling
+    let mut s: [u8; 256] = std::array::from_fn(|i| i as u8);
+    let mut j: u8 = 0;
+    for i in 0..256 {
+        j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+        s.swap(i, j as usize);
+    }
+
+    // Keystream generation
+    let (mut i, mut j) = (0u8, 0u8);
+    data.iter()
+        .map(|&b| {
+            i = i.wrapping_add(1);
+            j = j.wrapping_add(s[i as usize]);
+            s.swap(i as usize, j as usize);
+            b ^ s[s[i as usize].wrapping_add(s[j as usize]) as usize]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc4_vector() {
+        let encrypted = rc4(b"Key", b"Plaintext");
+        assert_eq!(encrypted, [0xBB, 0xF3, 0x16, 0xE8, 0xD9, 0x40, 0xAF, 0x0A, 0xD3]);
+        assert_eq!(rc4(b"Key", &encrypted), b"Plaintext");
+    }
+
+    #[test]
+    fn test_rc4_rfc6229_keystream() {
+        // 40-bit key, keystream bytes 0-31
+        let keystream = rc4(&[1, 2, 3, 4, 5], &[0; 32]);
+        assert_eq!(
+            keystream,
+            [
+                0xb2, 0x39, 0x63, 0x05, 0xf0, 0x3d, 0xc0, 0x27, 0xcc, 0xc3, 0x52, 0x4a, 0x0a,
+                0x11, 0x18, 0xa8, 0x69, 0x82, 0x94, 0x4f, 0x18, 0xfc, 0x82, 0xd5, 0x89, 0xc4,
+                0x03, 0xa4, 0x7a, 0x0d, 0x09, 0x19,
+            ]
+        );
+    }
+}
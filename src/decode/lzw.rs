@@ -0,0 +1,242 @@
+use crate::error::{PdfError, Result};
+
+const CLEAR_TABLE: usize = 256;
+const END_OF_DATA: usize = 257;
+const MAX_CODE_WIDTH: u32 = 12;
+
+/// Decompress LZW data (PDF/TIFF variant, EarlyChange = 1)
+pub fn lzw_decode(data: &[u8]) -> Result<Vec<u8>> {
+    lzw_decode_with(data, true)
+}
+
+/// Decompress LZW data, optionally switching code width one code early
+pub(crate) fn lzw_decode_with(data: &[u8], early_change: bool) -> Result<Vec<u8>> {
+    let early = early_change as usize;
+    let mut reader = BitReader::new(data);
+    let mut table = initial_table();
+    let mut width = 9;
+    let mut prev: Option<usize> = None;
+    let mut result = Vec::new();
+
+    while let Some(code) = reader.read(width) {
+        if code == CLEAR_TABLE {
+            table.truncate(END_OF_DATA + 1);
+            width = 9;
+            prev = None;
+            continue;
+        }
+        if code == END_OF_DATA {
+            break;
+        }
+
+        let entry = match prev {
+            None => {
+                // First code after a clear must be a literal byte
+                if code > 255 {
+                    return Err(PdfError::DecompressError(format!(
+                        "LZWDecode failed: unexpected code {} after clear",
+                        code
+                    )));
+                }
+                vec![code as u8]
+            }
+            Some(prev_code) => {
+                let entry = if code < table.len() {
+                    table[code].clone()
+                } else if code == table.len() {
+                    // KwKwK case: the code being defined right now
+                    let mut entry = table[prev_code].clone();
+                    entry.push(table[prev_code][0]);
+                    entry
+                } else {
+                    return Err(PdfError::DecompressError(format!(
+                        "LZWDecode failed: invalid code {} (table size {})",
+                        code,
+                        table.len()
+                    )));
+                };
+
+                if table.len() < 1 << MAX_CODE_WIDTH {
+                    let mut new_entry = table[prev_code].clone();
+                    new_entry.push(entry[0]);
+                    table.push(new_entry);
+                }
+                entry
+            }
+        };
+
+        result.extend_from_slice(&entry);
+        prev = Some(code);
+
+        if table.len() + early >= 1 << width && width < MAX_CODE_WIDTH {
+            width += 1;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Dictionary with the 256 single-byte entries plus the two control codes
+fn initial_table() -> Vec<Vec<u8>> {
+    let mut table: Vec<Vec<u8>> = (0..=255u8).map(|b| vec![b]).collect();
+    // Placeholders so table indices line up with code values
+    table.push(Vec::new());
+    table.push(Vec::new());
+    table
+}
+
+/// MSB-first bit reader over a byte slice
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    bits: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            buffer: 0,
+            bits: 0,
+        }
+    }
+
+    /// Read `width` bits, or None once the input is exhausted
+    fn read(&mut self, width: u32) -> Option<usize> {
+        while self.bits < width {
+            let byte = *self.data.get(self.pos)?;
+            self.pos += 1;
+            self.buffer = (self.buffer << 8) | byte as u32;
+            self.bits += 8;
+        }
+        self.bits -= width;
+        let code = (self.buffer >> self.bits) & ((1 << width) - 1);
+        self.buffer &= (1 << self.bits) - 1;
+        Some(code as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Reference encoder used to round-trip data across code width changes.
+    /// Code widths follow the decoder's view of the table, which trails the
+    /// encoder's by one entry.
+    struct Encoder {
+        out: Vec<u8>,
+        buffer: u64,
+        bits: u32,
+        width: u32,
+        decoder_size: usize,
+        after_clear: bool,
+    }
+
+    impl Encoder {
+        fn emit(&mut self, code: usize) {
+            self.buffer = (self.buffer << self.width) | code as u64;
+            self.bits += self.width;
+            while self.bits >= 8 {
+                self.bits -= 8;
+                self.out.push((self.buffer >> self.bits) as u8);
+            }
+            if code == CLEAR_TABLE {
+                self.width = 9;
+                self.decoder_size = 258;
+                self.after_clear = true;
+                return;
+            }
+            if !self.after_clear {
+                self.decoder_size += 1;
+            }
+            self.after_clear = false;
+            if self.decoder_size + 1 >= 1 << self.width && self.width < MAX_CODE_WIDTH {
+                self.width += 1;
+            }
+        }
+    }
+
+    fn lzw_encode(data: &[u8]) -> Vec<u8> {
+        let mut enc = Encoder {
+            out: Vec::new(),
+            buffer: 0,
+            bits: 0,
+            width: 9,
+            decoder_size: 258,
+            after_clear: true,
+        };
+        let mut dict: HashMap<Vec<u8>, usize> = HashMap::new();
+        enc.emit(CLEAR_TABLE);
+
+        let code_of = |dict: &HashMap<Vec<u8>, usize>, seq: &[u8]| {
+            if seq.len() == 1 { seq[0] as usize } else { dict[seq] }
+        };
+
+        let mut current: Vec<u8> = Vec::new();
+        for &b in data {
+            let mut candidate = current.clone();
+            candidate.push(b);
+            if candidate.len() == 1 || dict.contains_key(&candidate) {
+                current = candidate;
+                continue;
+            }
+            enc.emit(code_of(&dict, &current));
+            if dict.len() + 258 < 4000 {
+                dict.insert(candidate, dict.len() + 258);
+            } else {
+                enc.emit(CLEAR_TABLE);
+                dict.clear();
+            }
+            current = vec![b];
+        }
+        if !current.is_empty() {
+            enc.emit(code_of(&dict, &current));
+        }
+        enc.emit(END_OF_DATA);
+        if enc.bits > 0 {
+            enc.out.push((enc.buffer << (8 - enc.bits)) as u8);
+        }
+        enc.out
+    }
+
+    #[test]
+    fn test_lzw_spec_example() {
+        // Example from the PDF Reference, section 3.3.3
+        let encoded = [0x80, 0x0B, 0x60, 0x50, 0x22, 0x0C, 0x0C, 0x85, 0x01];
+        let decoded = lzw_decode(&encoded).unwrap();
+        assert_eq!(decoded, b"-----A---B");
+    }
+
+    #[test]
+    fn test_lzw_round_trip_across_width_changes() {
+        // Enough distinct sequences to push the code width to 12 bits
+        let original: Vec<u8> = (0..20_000u32).map(|i| ((i * 7) ^ (i >> 3)) as u8).collect();
+        let encoded = lzw_encode(&original);
+        assert_eq!(lzw_decode(&encoded).unwrap(), original);
+    }
+
+    #[test]
+    fn test_lzw_invalid_code() {
+        // Clear, 'A', then code 300 which has not been defined yet
+        let mut bits: u32 = 0;
+        let mut out = Vec::new();
+        let mut acc: u64 = 0;
+        for code in [256u64, 65, 300] {
+            acc = (acc << 9) | code;
+            bits += 9;
+            while bits >= 8 {
+                bits -= 8;
+                out.push((acc >> bits) as u8);
+            }
+        }
+        out.push((acc << (8 - bits)) as u8);
+
+        assert!(matches!(
+            lzw_decode(&out),
+            Err(PdfError::DecompressError(_))
+        ));
+    }
+}
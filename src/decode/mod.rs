@@ -1,10 +1,14 @@
 mod flate;
+mod lzw;
+mod predictor;
 
 use crate::error::{PdfError, Result};
 use crate::types::PdfObject;
 use std::collections::HashMap;
 
 pub use flate::flate_decode;
+pub use lzw::lzw_decode;
+pub use predictor::apply_predictor;
 
 /// Decode stream data based on Filter(s) in the stream dictionary
 pub fn decode_stream(dict: &HashMap<String, PdfObject>, data: &[u8]) -> Result<Vec<u8>> {
@@ -15,10 +19,11 @@ pub fn decode_stream(dict: &HashMap<String, PdfObject>, data: &[u8]) -> Result<V
         return Ok(data.to_vec());
     }
 
+    let parms = get_decode_parms(dict, filters.len());
     let mut result = data.to_vec();
 
-    for filter in filters {
-        result = apply_filter(&filter, &result)?;
+    for (filter, filter_parms) in filters.iter().zip(parms) {
+        result = apply_filter(filter, &result, filter_parms)?;
     }
 
     Ok(result)
@@ -41,12 +46,46 @@ fn get_filters(dict: &HashMap<String, PdfObject>) -> Result<Vec<String>> {
     }
 }
 
+/// Extract the DecodeParms dictionary for each filter.
+///
+/// DecodeParms is a single dictionary for a single filter, or an array
+/// parallel to the Filter array where `null` means "no parameters".
+fn get_decode_parms(
+    dict: &HashMap<String, PdfObject>,
+    filter_count: usize,
+) -> Vec<Option<&HashMap<String, PdfObject>>> {
+    let mut parms = match dict.get("DecodeParms") {
+        Some(PdfObject::Dict(d)) => vec![Some(d)],
+        Some(PdfObject::Array(arr)) => arr.iter().map(|p| p.as_dict()).collect(),
+        _ => Vec::new(),
+    };
+    parms.resize(filter_count, None);
+    parms
+}
+
 /// Apply a single filter
-fn apply_filter(filter: &str, data: &[u8]) -> Result<Vec<u8>> {
-    match filter {
-        "FlateDecode" => flate_decode(data),
-        "ASCIIHexDecode" => ascii_hex_decode(data),
-        other => Err(PdfError::UnsupportedFilter(other.to_string())),
+fn apply_filter(
+    filter: &str,
+    data: &[u8],
+    parms: Option<&HashMap<String, PdfObject>>,
+) -> Result<Vec<u8>> {
+    let decoded = match filter {
+        "FlateDecode" => flate_decode(data)?,
+        "LZWDecode" => {
+            let early_change = parms
+                .and_then(|p| p.get("EarlyChange"))
+                .and_then(|e| e.as_int())
+                .unwrap_or(1);
+            lzw::lzw_decode_with(data, early_change != 0)?
+        }
+        "ASCIIHexDecode" => return ascii_hex_decode(data),
+        other => return Err(PdfError::UnsupportedFilter(other.to_string())),
+    };
+
+    // Predictors only apply to the LZW and Flate filters
+    match parms {
+        Some(p) => apply_predictor(decoded, p),
+        None => Ok(decoded),
     }
 }
 
@@ -55,8 +94,7 @@ fn ascii_hex_decode(data: &[u8]) -> Result<Vec<u8>> {
     let mut result = Vec::new();
     let mut chars = data.iter().filter(|&&b| !b.is_ascii_whitespace());
 
-    loop {
-        let Some(&h1) = chars.next() else { break };
+    while let Some(&h1) = chars.next() {
         if h1 == b'>' {
             break; // End of data marker
         }
@@ -94,6 +132,33 @@ mod tests {
         assert_eq!(result, data);
     }
 
+    #[test]
+    fn test_flate_with_png_predictor() {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        // Two rows of /Columns 2 with the PNG Up filter
+        let predicted = [2u8, 5, 6, 2, 1, 1];
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&predicted).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut parms = HashMap::new();
+        parms.insert("Predictor".to_string(), PdfObject::Int(12));
+        parms.insert("Columns".to_string(), PdfObject::Int(2));
+
+        let mut dict = HashMap::new();
+        dict.insert("Filter".to_string(), PdfObject::Name("FlateDecode".into()));
+        dict.insert(
+            "DecodeParms".to_string(),
+            PdfObject::Array(vec![PdfObject::Dict(parms)]),
+        );
+
+        let result = decode_stream(&dict, &compressed).unwrap();
+        assert_eq!(result, vec![5, 6, 6, 7]);
+    }
+
     #[test]
     fn test_ascii_hex_decode() {
         let data = b"48656C6C6F>";  // "Hello"
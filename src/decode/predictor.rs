@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use crate::error::{PdfError, Result};
+use crate::types::PdfObject;
+
+/// Reverse the /Predictor transform described by a filter's DecodeParms
+pub fn apply_predictor(data: Vec<u8>, parms: &HashMap<String, PdfObject>) -> Result<Vec<u8>> {
+    let int_param = |key: &str, default: i64| parms.get(key).and_then(|v| v.as_int()).unwrap_or(default);
+
+    let predictor = int_param("Predictor", 1);
+    if predictor <= 1 {
+        return Ok(data);
+    }
+
+    let colors = int_param("Colors", 1);
+    let bits_per_component = int_param("BitsPerComponent", 8);
+    let columns = int_param("Columns", 1);
+
+    // Row sizes are computed in usize, so /Columns too large for that are
+    // rejected too
+    let row_bits = colors
+        .checked_mul(bits_per_component)
+        .and_then(|bits| bits.checked_mul(columns))
+        .and_then(|bits| usize::try_from(bits).ok());
+    if !(1..=32).contains(&colors)
+        || ![1, 2, 4, 8, 16].contains(&bits_per_component)
+        || columns < 1
+        || row_bits.is_none()
+    {
+        return Err(PdfError::DecompressError(format!(
+            "Invalid predictor parameters: Colors={} BitsPerComponent={} Columns={}",
+            colors, bits_per_component, columns
+        )));
+    }
+
+    let layout = RowLayout {
+        colors: colors as usize,
+        bits_per_component: bits_per_component as usize,
+        columns: columns as usize,
+    };
+
+    match predictor {
+        2 => Ok(tiff_predictor(data, &layout)),
+        10..=15 => png_predictor(&data, &layout),
+        other => Err(PdfError::DecompressError(format!(
+            "Unsupported predictor: {}",
+            other
+        ))),
+    }
+}
+
+/// Sample layout of one row of predicted data
+struct RowLayout {
+    colors: usize,
+    bits_per_component: usize,
+    columns: usize,
+}
+
+impl RowLayout {
+    /// Bytes per row, excluding the PNG tag byte
+    fn row_bytes(&self) -> usize {
+        (self.colors * self.bits_per_component * self.columns).div_ceil(8)
+    }
+
+    /// Bytes per complete pixel, rounded up to at least 1 (PNG's "bpp")
+    fn pixel_bytes(&self) -> usize {
+        (self.colors * self.bits_per_component).div_ceil(8)
+    }
+}
+
+/// TIFF Predictor 2: each component is stored as a difference from the
+/// same component of the pixel to its left
+fn tiff_predictor(mut data: Vec<u8>, layout: &RowLayout) -> Vec<u8> {
+    let row_bytes = layout.row_bytes();
+    let bpc = layout.bits_per_component;
+    let colors = layout.colors;
+
+    for row in data.chunks_mut(row_bytes) {
+        match bpc {
+            8 => {
+                for i in colors..row.len() {
+                    row[i] = row[i].wrapping_add(row[i - colors]);
+                }
+            }
+            16 => {
+                let stride = colors * 2;
+                let mut i = stride;
+                while i + 1 < row.len() {
+                    let left = u16::from_be_bytes([row[i - stride], row[i - stride + 1]]);
+                    let cur = u16::from_be_bytes([row[i], row[i + 1]]);
+                    let [hi, lo] = cur.wrapping_add(left).to_be_bytes();
+                    row[i] = hi;
+                    row[i + 1] = lo;
+                    i += 2;
+                }
+            }
+            _ => {
+                // Sub-byte components: walk the row bit by bit
+                let mask = (1u16 << bpc) - 1;
+                let samples = (row.len() * 8 / bpc).min(layout.columns * colors);
+                for s in colors..samples {
+                    let left = read_bits(row, (s - colors) * bpc, bpc);
+                    let cur = read_bits(row, s * bpc, bpc);
+                    write_bits(row, s * bpc, bpc, (cur + left) & mask);
+                }
+            }
+        }
+    }
+
+    data
+}
+
+fn read_bits(row: &[u8], bit_offset: usize, width: usize) -> u16 {
+    let byte = row[bit_offset / 8] as u16;
+    let shift = 8 - width - bit_offset % 8;
+    (byte >> shift) & ((1 << width) - 1)
+}
+
+fn write_bits(row: &mut [u8], bit_offset: usize, width: usize, value: u16) {
+    let shift = 8 - width - bit_offset % 8;
+    let mask = (((1u16 << width) - 1) << shift) as u8;
+    let byte = &mut row[bit_offset / 8];
+    *byte = (*byte & !mask) | ((value << shift) as u8 & mask);
+}
+
+/// PNG predictors (10-15): every row is prefixed with its own filter type byte
+fn png_predictor(data: &[u8], layout: &RowLayout) -> Result<Vec<u8>> {
+    let row_bytes = layout.row_bytes();
+    let bpp = layout.pixel_bytes();
+
+    // No row can be longer than the data, however large /Columns claims
+    let mut result = Vec::with_capacity(data.len());
+    let mut prev_row = vec![0u8; row_bytes.min(data.len())];
+
+    for chunk in data.chunks(row_bytes + 1) {
+        let (filter, encoded) = chunk.split_first().ok_or_else(|| {
+            PdfError::DecompressError("Empty PNG predictor row".into())
+        })?;
+
+        // A short trailing row is decoded as far as it goes
+        let mut row = encoded.to_vec();
+
+        for i in 0..row.len() {
+            let left = if i >= bpp { row[i - bpp] } else { 0 };
+            let up = prev_row[i];
+            let up_left = if i >= bpp { prev_row[i - bpp] } else { 0 };
+
+            row[i] = match filter {
+                0 => row[i],
+                1 => row[i].wrapping_add(left),
+                2 => row[i].wrapping_add(up),
+                3 => row[i].wrapping_add(((left as u16 + up as u16) / 2) as u8),
+                4 => row[i].wrapping_add(paeth(left, up, up_left)),
+                other => {
+                    return Err(PdfError::DecompressError(format!(
+                        "Invalid PNG predictor type: {}",
+                        other
+                    )));
+                }
+            };
+        }
+
+        result.extend_from_slice(&row);
+        prev_row[..row.len()].copy_from_slice(&row);
+    }
+
+    Ok(result)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parms(entries: &[(&str, i64)]) -> HashMap<String, PdfObject> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), PdfObject::Int(*v)))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_predictor() {
+        let data = vec![1, 2, 3];
+        let result = apply_predictor(data.clone(), &HashMap::new()).unwrap();
+        assert_eq!(result, data);
+    }
+
+    #[test]
+    fn test_png_up_predictor() {
+        // Typical xref stream: /Predictor 12 /Columns 4
+        let data = vec![
+            2, 1, 0, 16, 0, // Up on zero row = raw
+            2, 0, 0, 32, 0, // Up: add previous row
+        ];
+        let result = apply_predictor(data, &parms(&[("Predictor", 12), ("Columns", 4)])).unwrap();
+        assert_eq!(result, vec![1, 0, 16, 0, 1, 0, 48, 0]);
+    }
+
+    #[test]
+    fn test_png_sub_average_paeth() {
+        let p = parms(&[("Predictor", 15), ("Columns", 3)]);
+        let data = vec![
+            1, 10, 5, 5, // Sub: 10, 15, 20
+            3, 2, 4, 6, // Average: 2+(0+10)/2=7, 4+(7+15)/2=15, 6+(15+20)/2=23
+            4, 1, 1, 1, // Paeth
+        ];
+        let result = apply_predictor(data, &p).unwrap();
+        assert_eq!(&result[..6], &[10, 15, 20, 7, 15, 23]);
+        // Paeth row: a=left, b=up, c=up-left
+        assert_eq!(result[6], 1 + paeth(0, 7, 0));
+        assert_eq!(result[7], 1 + paeth(result[6], 15, 7));
+    }
+
+    #[test]
+    fn test_tiff_predictor_rgb() {
+        let p = parms(&[("Predictor", 2), ("Colors", 3), ("Columns", 2)]);
+        let data = vec![10, 20, 30, 1, 2, 3];
+        let result = apply_predictor(data, &p).unwrap();
+        assert_eq!(result, vec![10, 20, 30, 11, 22, 33]);
+    }
+
+    #[test]
+    fn test_tiff_predictor_sub_byte() {
+        // 4-bit gray, 4 columns: deltas 1,1,1,1 -> 1,2,3,4
+        let p = parms(&[("Predictor", 2), ("BitsPerComponent", 4), ("Columns", 4)]);
+        let result = apply_predictor(vec![0x11, 0x11], &p).unwrap();
+        assert_eq!(result, vec![0x12, 0x34]);
+    }
+
+    #[test]
+    fn test_oversized_columns() {
+        let p = parms(&[("Predictor", 12), ("Colors", 32), ("Columns", i64::MAX)]);
+        assert!(apply_predictor(vec![0, 0], &p).is_err());
+
+        // Fits in usize, but far larger than the data
+        let p = parms(&[("Predictor", 12), ("Columns", 4398046511104)]);
+        assert_eq!(apply_predictor(vec![0, 1, 2, 3], &p).unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_invalid_png_filter_type() {
+        let p = parms(&[("Predictor", 12), ("Columns", 1)]);
+        assert!(apply_predictor(vec![9, 0], &p).is_err());
+    }
+}
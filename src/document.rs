@@ -1,9 +1,19 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-use crate::content::{ContentParser, TextSpan};
+use crate::content::{
+    device_color_components, ContentParser, FormXObject, FormXObjects, MergeConfig, ParseReport,
+    TextSpan, Word, MAX_FORM_DEPTH,
+};
+use crate::crypt::SecurityHandler;
 use crate::decode::decode_stream;
 use crate::error::{PdfError, Result};
-use crate::font::{parse_tounicode_cmap, FontEncoding};
+#[cfg(feature = "serde")]
+use crate::extract::pages_to_json;
+use crate::extract::{classify_spans, LayoutOptions, PageElement, TableOptions};
+use crate::font::{
+    parse_cid_ranges, parse_codespace_ranges, parse_tounicode_cmap, CodespaceRange, FontEncoding,
+};
 use crate::parser::Parser;
 use crate::types::{ObjRef, PdfObject};
 
@@ -18,42 +28,812 @@ pub struct XRefEntry {
     pub compressed: bool,
 }
 
+/// Document metadata from the trailer's /Info dictionary
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub creator: Option<String>,
+    pub producer: Option<String>,
+    /// Creation date, normalized to RFC 3339 when parseable
+    pub creation_date: Option<String>,
+    /// Modification date, normalized to RFC 3339 when parseable
+    pub mod_date: Option<String>,
+}
+
+/// Entry in the document outline (bookmarks)
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineItem {
+    pub title: String,
+    /// 0-based index of the destination page, if it could be resolved
+    pub dest_page: Option<usize>,
+    pub children: Vec<OutlineItem>,
+}
+
+/// Link annotation on a page, found by [`Document::extract_page_links`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    /// Clickable area in PDF units: `[llx, lly, urx, ury]`
+    pub rect: [f64; 4],
+    pub target: LinkTarget,
+}
+
+/// Where a [`Link`] leads
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkTarget {
+    /// External address from a /URI action
+    Uri(String),
+    /// 0-based index of a page in this document
+    Page(usize),
+}
+
+/// Comment or markup annotation on a page, found by
+/// [`Document::extract_page_annotations`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    /// Annotation type from /Subtype, e.g. Text, Highlight or FreeText
+    pub subtype: String,
+    /// Text of the comment (/Contents)
+    pub contents: Option<String>,
+    /// Area in PDF units: `[llx, lly, urx, ury]`
+    pub rect: [f64; 4],
+    /// Author of the comment (/T)
+    pub author: Option<String>,
+}
+
+/// Interactive form field from the document's /AcroForm
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormField {
+    /// Fully qualified name: the partial names of the field and its
+    /// ancestors joined with `.`
+    pub name: String,
+    /// Text value, the on-state name of a checked box (or `Off`), or the
+    /// selected options of a choice field joined with `, `
+    pub value: Option<String>,
+    /// Field type: `Tx` (text), `Btn` (button), `Ch` (choice) or `Sig`
+    pub field_type: String,
+}
+
+/// Occurrence of a search query, found by [`Document::search`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    /// 0-based index of the page
+    pub page: usize,
+    /// The matched text as it appears on the page
+    pub text: String,
+    /// Position where the match starts, in the page's coordinates
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A non-fatal problem met while reading a document, collected by
+/// [`Document::take_diagnostics`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// 0-based index of the page being extracted, if any
+    pub page: Option<usize>,
+    pub kind: DiagnosticKind,
+    pub message: String,
+}
+
+/// What part of the document a [`Diagnostic`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A broken xref table or an object that could not be found
+    Xref,
+    /// A font, or its encoding or ToUnicode CMap, that could not be loaded
+    Font,
+    /// A page content stream that could not be read
+    Content,
+    /// A page tree node listed under more than one parent
+    PageTree,
+}
+
+/// A structural problem found by [`Document::validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    pub kind: ValidationIssueKind,
+    /// The object the problem was found in, if any
+    pub object: Option<ObjRef>,
+    pub message: String,
+}
+
+/// What check a [`ValidationIssue`] failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationIssueKind {
+    /// An xref offset that does not point at the expected `N G obj`
+    XrefOffset,
+    /// A missing or unreadable /Root, or one that is not a /Catalog
+    Catalog,
+    /// An unreadable page tree, or a /Count that disagrees with it
+    PageTree,
+    /// A page whose /Contents is not a stream or an array of streams
+    PageContents,
+}
+
+/// Page boundaries in PDF units: `[llx, lly, urx, ury]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageBox {
+    pub media_box: [f64; 4],
+    /// Visible region; same as `media_box` when the page has no /CropBox
+    pub crop_box: [f64; 4],
+}
+
+impl PageBox {
+    pub fn width(&self) -> f64 {
+        (self.media_box[2] - self.media_box[0]).abs()
+    }
+
+    pub fn height(&self) -> f64 {
+        (self.media_box[3] - self.media_box[1]).abs()
+    }
+}
+
+/// How the bytes of a [`PdfImage`] are encoded
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageFormat {
+    /// A complete JPEG file (DCTDecode), ready to be written as `.jpg`
+    Jpeg,
+    /// Decoded samples: `height` rows of `width` pixels, each with
+    /// `bits_per_component` bits per color component
+    Raw,
+    /// CCITT Group 3 or 4 fax data (CCITTFaxDecode), e.g. a scanned page.
+    /// The bytes are not decoded; the parameters are enough to wrap them in
+    /// a TIFF.
+    Ccitt(CcittParams),
+    /// Still encoded with the named filter (e.g. JPXDecode, JBIG2Decode)
+    Encoded(String),
+}
+
+/// The /DecodeParms of a CCITTFaxDecode image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CcittParams {
+    /// Coding scheme: negative for Group 4, 0 for Group 3 one-dimensional,
+    /// positive for Group 3 two-dimensional
+    pub k: i64,
+    /// Pixels per row
+    pub columns: u32,
+    /// Number of rows, if given
+    pub rows: Option<u32>,
+    /// 1 bits are black rather than white
+    pub black_is_1: bool,
+}
+
+impl CcittParams {
+    fn from_dict(parms: Option<&HashMap<String, PdfObject>>) -> Self {
+        let int = |key: &str| parms.and_then(|p| p.get(key)).and_then(|v| v.as_int());
+        CcittParams {
+            k: int("K").unwrap_or(0),
+            columns: int("Columns").map_or(1728, |c| c as u32),
+            rows: int("Rows").filter(|&r| r > 0).map(|r| r as u32),
+            black_is_1: parms
+                .and_then(|p| p.get("BlackIs1"))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// A font used on the document's pages, from [`Document::fonts`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontInfo {
+    /// PostScript name from /BaseFont, including any subset prefix; empty
+    /// for Type3 fonts, which have none
+    pub base_font: String,
+    /// Font type from /Subtype, e.g. Type1, TrueType or Type0
+    pub subtype: String,
+    /// Whether the font program is in the file: a /FontFile, /FontFile2 or
+    /// /FontFile3 in its descriptor, or glyph procedures of a Type3 font
+    pub embedded: bool,
+    /// Name of the /Encoding, the /BaseEncoding of an encoding dictionary
+    /// ("Custom" without one) or the /CMapName of an embedded CMap; empty
+    /// when the font uses its built-in encoding
+    pub encoding: String,
+}
+
+/// Raster image drawn on a page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfImage {
+    /// Name of the image in the page's /XObject resources
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    /// Color space family, e.g. DeviceRGB; ICC-based spaces are reported
+    /// as the device space with the same number of components
+    pub color_space: Option<String>,
+    pub bits_per_component: Option<u8>,
+    pub format: ImageFormat,
+    pub data: Vec<u8>,
+}
+
+/// Filters that are image codecs rather than general compression
+const IMAGE_CODECS: [&str; 4] = ["DCTDecode", "JPXDecode", "CCITTFaxDecode", "JBIG2Decode"];
+
+/// The /Encrypt settings of a document, from [`Document::encryption_info`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EncryptionInfo {
+    /// Algorithm version (/V)
+    pub version: i64,
+    /// Revision of the standard security handler (/R)
+    pub revision: i64,
+    /// Length of the file key in bits
+    pub key_bits: u32,
+    /// Whether the empty user password fails to open the file
+    pub requires_password: bool,
+}
+
+/// How a document's cross-reference data is stored, from
+/// [`Document::xref_kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XrefKind {
+    /// A classic `xref` table
+    Table,
+    /// An `xref` table with a supplementary /XRefStm stream
+    Hybrid,
+    /// A cross-reference stream (PDF 1.5+)
+    Stream,
+    /// Unreadable, so rebuilt by scanning the file for objects
+    Rebuilt,
+}
+
+/// Options for [`Document::parse_with_options`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParseOptions {
+    /// Skip unknown keywords, stray tokens and malformed xref entries, and
+    /// rebuild a broken xref table, instead of failing. On by default.
+    pub lenient: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self { lenient: true }
+    }
+}
+
+/// Options for text extraction, e.g. [`Document::extract_page_text_with`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtractOptions {
+    /// Thresholds for merging raw spans into runs of text
+    pub merge_config: MergeConfig,
+    /// Keep text drawn invisibly, such as the OCR layer of a scan
+    pub include_invisible: bool,
+    /// Expand ligatures (U+FB00-FB06) into their letters
+    pub normalize_ligatures: bool,
+    /// Rejoin words hyphenated at line breaks when classifying the layout
+    pub dehyphenate: bool,
+    /// Order text by XY-cut segmentation when classifying the layout
+    pub xy_cut: bool,
+    /// How table columns are detected when classifying the layout
+    pub table_options: TableOptions,
+    /// Decode character codes without a Unicode mapping to U+FFFD instead of
+    /// guessing from their bytes, reporting a per-font count in the
+    /// diagnostics
+    pub replace_unmapped: bool,
+    /// Put mostly right-to-left (Hebrew, Arabic) text into logical order,
+    /// as it is usually stored in the order it appears on the page
+    pub reorder_rtl: bool,
+    /// Make whole-document helpers such as [`crate::pdf_to_text_with`] fail
+    /// on the first page that cannot be extracted, instead of leaving that
+    /// page out
+    pub fail_fast: bool,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            merge_config: MergeConfig::default(),
+            include_invisible: false,
+            normalize_ligatures: false,
+            dehyphenate: true,
+            xy_cut: false,
+            table_options: TableOptions::default(),
+            replace_unmapped: false,
+            reorder_rtl: false,
+            fail_fast: false,
+        }
+    }
+}
+
+impl ExtractOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn merge_config(mut self, config: MergeConfig) -> Self {
+        self.merge_config = config;
+        self
+    }
+
+    pub fn include_invisible(mut self, include: bool) -> Self {
+        self.include_invisible = include;
+        self
+    }
+
+    pub fn normalize_ligatures(mut self, normalize: bool) -> Self {
+        self.normalize_ligatures = normalize;
+        self
+    }
+
+    pub fn dehyphenate(mut self, dehyphenate: bool) -> Self {
+        self.dehyphenate = dehyphenate;
+        self
+    }
+
+    pub fn xy_cut(mut self, xy_cut: bool) -> Self {
+        self.xy_cut = xy_cut;
+        self
+    }
+
+    pub fn table_options(mut self, options: TableOptions) -> Self {
+        self.table_options = options;
+        self
+    }
+
+    pub fn replace_unmapped(mut self, replace: bool) -> Self {
+        self.replace_unmapped = replace;
+        self
+    }
+
+    pub fn reorder_rtl(mut self, reorder: bool) -> Self {
+        self.reorder_rtl = reorder;
+        self
+    }
+
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// The layout classification part of these options
+    pub fn layout_options(&self) -> LayoutOptions {
+        LayoutOptions {
+            dehyphenate: self.dehyphenate,
+            xy_cut: self.xy_cut,
+            table: self.table_options,
+        }
+    }
+}
+
+/// How [`Document::extract_page_text_string_with`] joins spans into text
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineJoinOptions {
+    /// Placed between spans on the same line
+    pub intra_line_sep: String,
+    /// Placed between lines, e.g. `"\n\n"` for a blank line
+    pub inter_line_sep: String,
+    /// Largest baseline difference, in PDF units, still treated as one line
+    pub y_tolerance: f64,
+}
+
+impl Default for LineJoinOptions {
+    fn default() -> Self {
+        Self {
+            intra_line_sep: " ".into(),
+            inter_line_sep: "\n".into(),
+            y_tolerance: 3.0,
+        }
+    }
+}
+
 /// Parsed PDF document
 pub struct Document<'a> {
-    data: &'a [u8],
+    /// The file's bytes, borrowed by [`Document::parse`] or owned by
+    /// [`Document::parse_owned`]
+    data: Cow<'a, [u8]>,
     /// Object number -> xref entry
     xref: HashMap<u32, XRefEntry>,
     /// Trailer dictionary
     trailer: HashMap<String, PdfObject>,
+    /// The two file identifiers from the trailer /ID
+    id: Option<(Vec<u8>, Vec<u8>)>,
     /// Cache of parsed objects
     cache: HashMap<ObjRef, PdfObject>,
+    /// Recover from malformed syntax, and rebuild the xref by scanning the
+    /// file when it turns out to be broken
+    lenient: bool,
+    /// Whether the xref has already been rebuilt
+    rebuilt: bool,
+    /// How the newest xref section was stored
+    xref_kind: XrefKind,
+    /// Decrypts strings and streams of encrypted documents
+    security: Option<SecurityHandler>,
+    /// Font encodings already loaded, keyed by font object so pages that
+    /// share a font (under any resource name) parse it once
+    font_cache: HashMap<ObjRef, FontEncoding>,
+    /// Leaf pages in document order with their inherited attributes,
+    /// collected on the first page lookup
+    pages: Option<Vec<(ObjRef, PdfObject)>>,
+    /// Non-fatal problems recorded since the last `take_diagnostics`
+    diagnostics: Vec<Diagnostic>,
+    /// Page being extracted, to attribute diagnostics to
+    current_page: Option<usize>,
+}
+
+/// A document that owns its bytes, so it can be returned from functions or
+/// stored in structs without borrowing a buffer
+pub type OwnedDocument = Document<'static>;
+
+impl Document<'static> {
+    /// Parse a PDF document that takes ownership of its bytes, e.g. the
+    /// `Vec<u8>` from `fs::read` or an HTTP body. Lenient like `parse`.
+    pub fn parse_owned(data: Vec<u8>) -> Result<OwnedDocument> {
+        Self::parse_owned_with_options(data, ParseOptions::default())
+    }
+
+    /// Like [`Document::parse_owned`] with explicit options
+    pub fn parse_owned_with_options(
+        data: Vec<u8>,
+        options: ParseOptions,
+    ) -> Result<OwnedDocument> {
+        Self::open(Cow::Owned(data), options)
+    }
+
+    /// Read a whole PDF from `reader` and parse it as an owned document
+    pub fn from_reader<R: std::io::Read>(mut reader: R) -> Result<OwnedDocument> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::parse_owned(data)
+    }
 }
 
 impl<'a> Document<'a> {
-    /// Parse a PDF document from bytes
+    /// Parse a PDF document from bytes in lenient mode: malformed syntax is
+    /// skipped where possible and a broken xref table is repaired by
+    /// scanning the file for objects
     pub fn parse(data: &'a [u8]) -> Result<Self> {
+        Self::parse_with_options(data, ParseOptions::default())
+    }
+
+    /// Parse a PDF document from bytes, failing on malformed syntax or a
+    /// broken xref table instead of recovering
+    pub fn parse_strict(data: &'a [u8]) -> Result<Self> {
+        Self::parse_with_options(data, ParseOptions { lenient: false })
+    }
+
+    /// Parse a PDF document from bytes with explicit options
+    pub fn parse_with_options(data: &'a [u8], options: ParseOptions) -> Result<Self> {
+        Self::open(Cow::Borrowed(data), options)
+    }
+
+    fn open(data: Cow<'a, [u8]>, options: ParseOptions) -> Result<Self> {
+        let lenient = options.lenient;
         // Verify PDF header
         if !data.starts_with(b"%PDF-") {
             return Err(PdfError::MissingHeader);
         }
 
-        // Find startxref position
-        let startxref_pos = Self::find_startxref(data)?;
-
-        // Parse xref offset
-        let xref_offset = Self::parse_startxref(data, startxref_pos)?;
-
-        // Parse xref table and trailer
-        let (xref, trailer) = Self::parse_xref_and_trailer(data, xref_offset)?;
+        // Find startxref, then parse the xref table and trailer it points to.
+        // Junk appended after %%EOF can push startxref out of the searched
+        // tail; lenient parsing then looks for the last xref in the file.
+        let mut fallback_xref = None;
+        let offset = match Self::find_startxref(&data) {
+            Ok(pos) => Self::parse_startxref(&data, pos),
+            Err(err) if lenient => match Self::find_last_xref(&data) {
+                Some(offset) => {
+                    fallback_xref = Some(offset);
+                    Ok(offset)
+                }
+                None => Err(err),
+            },
+            Err(err) => Err(err),
+        };
+        let is_table = match &offset {
+            Ok(offset) => data.get(*offset..).is_some_and(|d| d.starts_with(b"xref")),
+            Err(_) => false,
+        };
+        let parsed =
+            offset.and_then(|offset| Self::parse_xref_and_trailer(&data, offset, lenient));
 
-        Ok(Document {
+        let mut doc = Document {
             data,
-            xref,
-            trailer,
+            xref: HashMap::new(),
+            trailer: HashMap::new(),
+            id: None,
             cache: HashMap::new(),
+            lenient,
+            rebuilt: false,
+            xref_kind: XrefKind::Stream,
+            security: None,
+            font_cache: HashMap::new(),
+            pages: None,
+            diagnostics: Vec::new(),
+            current_page: None,
+        };
+
+        match parsed {
+            Ok((xref, trailer)) => {
+                doc.xref_kind = match (is_table, trailer.contains_key("XRefStm")) {
+                    (true, true) => XrefKind::Hybrid,
+                    (true, false) => XrefKind::Table,
+                    (false, _) => XrefKind::Stream,
+                };
+                doc.xref = xref;
+                doc.trailer = trailer;
+                if let Some(offset) = fallback_xref {
+                    let message = format!("no startxref at end of file; read xref at {}", offset);
+                    doc.warn(DiagnosticKind::Xref, message);
+                }
+            }
+            Err(err) if lenient => {
+                if doc.rebuild_xref().is_err() || !doc.trailer.contains_key("Root") {
+                    return Err(err);
+                }
+                doc.warn(DiagnosticKind::Xref, format!("rebuilt unreadable xref: {}", err));
+            }
+            Err(err) => return Err(err),
+        }
+
+        doc.id = read_file_id(&doc.trailer);
+        doc.security = doc.load_security_handler()?;
+        Ok(doc)
+    }
+
+    /// Non-fatal problems met so far, such as fonts that failed to load or
+    /// a broken xref that had to be rebuilt. Clears the list.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Record a non-fatal problem against the page being extracted
+    fn warn(&mut self, kind: DiagnosticKind, message: String) {
+        self.diagnostics.push(Diagnostic {
+            page: self.current_page,
+            kind,
+            message,
+        });
+    }
+
+    /// Whether the trailer has an /Encrypt dictionary
+    pub fn is_encrypted(&self) -> bool {
+        self.trailer.contains_key("Encrypt")
+    }
+
+    /// The settings of the /Encrypt dictionary, or `None` for an
+    /// unencrypted file. Documents needing a password already fail to
+    /// parse with [`PdfError::PasswordRequired`], so an opened one reports
+    /// `requires_password: false`.
+    pub fn encryption_info(&mut self) -> Option<EncryptionInfo> {
+        let encrypt = self.trailer.get("Encrypt").cloned()?;
+        let encrypt = self.get_object(&encrypt).ok()?;
+        let dict = encrypt.as_dict()?;
+
+        let int = |key: &str| dict.get(key).and_then(|v| v.as_int());
+        let version = int("V").unwrap_or(0);
+        // Key lengths as the security handler derives them
+        let key_bits = match version {
+            1 => 40,
+            2 | 3 => int("Length").unwrap_or(40).clamp(40, 128) / 8 * 8,
+            4 => int("Length").unwrap_or(128).clamp(40, 128) / 8 * 8,
+            5 => 256,
+            _ => 0,
+        } as u32;
+        let id = self.id.as_ref().map_or(&[][..], |(first, _)| first);
+
+        Some(EncryptionInfo {
+            version,
+            revision: int("R").unwrap_or(2),
+            key_bits,
+            requires_password: matches!(
+                SecurityHandler::new(dict, id),
+                Err(PdfError::PasswordRequired)
+            ),
+        })
+    }
+
+    /// Set up decryption when the trailer has an /Encrypt dictionary.
+    /// Only the empty user password is tried.
+    fn load_security_handler(&mut self) -> Result<Option<SecurityHandler>> {
+        let Some(encrypt) = self.trailer.get("Encrypt").cloned() else {
+            return Ok(None);
+        };
+        // Resolved before the handler exists, so it is read as-is
+        let encrypt = self.get_object(&encrypt)?;
+        let dict = encrypt
+            .as_dict()
+            .ok_or_else(|| PdfError::InvalidStructure("Encrypt must be a dictionary".into()))?;
+
+        let id = self.id.as_ref().map_or(&[][..], |(first, _)| first);
+        SecurityHandler::new(dict, id).map(Some)
+    }
+
+    /// Rebuild the xref by scanning the whole file for `N G obj` headers.
+    ///
+    /// Later definitions of an object win, as with incremental updates.
+    /// Members of object streams are indexed too, and a trailer is recovered
+    /// from the last `trailer` dictionary, an xref stream, or the catalog if
+    /// the current one has no /Root.
+    pub fn rebuild_xref(&mut self) -> Result<()> {
+        let mut xref = Self::scan_objects(&self.data);
+        if xref.is_empty() {
+            return Err(PdfError::InvalidStructure(
+                "No objects found while rebuilding xref".into(),
+            ));
+        }
+        Self::scan_object_streams(&self.data, &mut xref);
+
+        if !self.trailer.contains_key("Root") {
+            if let Some(trailer) = Self::recover_trailer(&self.data, &xref) {
+                self.trailer = trailer;
+            }
+        }
+
+        self.xref = xref;
+        self.rebuilt = true;
+        self.pages = None;
+        self.cache.clear();
+        Ok(())
+    }
+
+    /// Find every `N G obj` header and record its offset
+    fn scan_objects(data: &[u8]) -> HashMap<u32, XRefEntry> {
+        let is_space = |b: u8| matches!(b, b' ' | b'\t' | b'\n' | b'\r' | 0x0C | 0x00);
+        let is_digit = |b: u8| b.is_ascii_digit();
+        let mut xref = HashMap::new();
+
+        let mut i = 0;
+        while i + 3 <= data.len() {
+            // "obj" must stand alone, preceded by whitespace
+            if &data[i..i + 3] != b"obj"
+                || i == 0
+                || !is_space(data[i - 1])
+                || data.get(i + 3).is_some_and(|b| b.is_ascii_alphanumeric())
+            {
+                i += 1;
+                continue;
+            }
+
+            // Walk back over: generation, whitespace, object number
+            let mut j = i;
+            while j > 0 && is_space(data[j - 1]) {
+                j -= 1;
+            }
+            let gen_end = j;
+            while j > 0 && is_digit(data[j - 1]) {
+                j -= 1;
+            }
+            let gen_start = j;
+            while j > 0 && is_space(data[j - 1]) {
+                j -= 1;
+            }
+            let num_end = j;
+            while j > 0 && is_digit(data[j - 1]) {
+                j -= 1;
+            }
+            let num_start = j;
+
+            let valid = gen_start < gen_end
+                && num_end < gen_start
+                && num_start < num_end
+                && (num_start == 0 || !data[num_start - 1].is_ascii_alphanumeric());
+            let digits = |range: std::ops::Range<usize>| std::str::from_utf8(&data[range]).ok();
+            if valid {
+                let num = digits(num_start..num_end).and_then(|d| d.parse::<u32>().ok());
+                let generation = digits(gen_start..gen_end).and_then(|d| d.parse::<u16>().ok());
+                if let (Some(num), Some(generation)) = (num, generation) {
+                    xref.insert(
+                        num,
+                        XRefEntry {
+                            offset: num_start,
+                            generation,
+                            in_use: true,
+                            compressed: false,
+                        },
+                    );
+                }
+            }
+            i += 3;
+        }
+
+        xref
+    }
+
+    /// Index the members of any object streams found by `scan_objects`.
+    /// Objects defined directly in the file take precedence.
+    fn scan_object_streams(data: &[u8], xref: &mut HashMap<u32, XRefEntry>) {
+        let mut stream_nums: Vec<u32> = xref
+            .iter()
+            .filter(|(_, e)| Self::object_prefix(data, e.offset).contains_name(b"/ObjStm"))
+            .map(|(&num, _)| num)
+            .collect();
+        stream_nums.sort_unstable();
+
+        for stream_num in stream_nums {
+            let Some((dict, raw)) = Self::parse_object_at(data, xref[&stream_num].offset)
+                .and_then(|obj| match obj {
+                    PdfObject::Stream { dict, data } => Some((dict, data)),
+                    _ => None,
+                })
+            else {
+                continue;
+            };
+            let Ok(decoded) = decode_stream(&dict, &raw) else {
+                continue;
+            };
+            let n = dict.get("N").and_then(|n| n.as_int()).unwrap_or(0);
+
+            let mut header = Parser::new(&decoded).lenient(true);
+            for index in 0..n {
+                let (Ok(Some(PdfObject::Int(num))), Ok(Some(PdfObject::Int(_)))) =
+                    (header.parse_object(), header.parse_object())
+                else {
+                    break;
+                };
+                xref.entry(num as u32).or_insert(XRefEntry {
+                    offset: stream_num as usize,
+                    generation: index as u16,
+                    in_use: true,
+                    compressed: true,
+                });
+            }
+        }
+    }
+
+    /// Recover a trailer dictionary for a rebuilt xref
+    fn recover_trailer(
+        data: &[u8],
+        xref: &HashMap<u32, XRefEntry>,
+    ) -> Option<HashMap<String, PdfObject>> {
+        // The last "trailer" dictionary that names a catalog
+        let keyword = b"trailer";
+        let mut end = data.len();
+        while let Some(pos) = data[..end].windows(keyword.len()).rposition(|w| w == keyword) {
+            let mut parser = Parser::new(data).lenient(true);
+            parser.seek(pos + keyword.len());
+            if let Ok(Some(PdfObject::Dict(dict))) = parser.parse_object() {
+                if dict.contains_key("Root") {
+                    return Some(dict);
+                }
+            }
+            end = pos;
+        }
+
+        // Otherwise an xref stream dictionary, or failing that the catalog
+        let mut nums: Vec<u32> = xref.keys().copied().collect();
+        nums.sort_unstable();
+        let mut catalog = None;
+        for num in nums.into_iter().rev() {
+            let entry = &xref[&num];
+            if entry.compressed {
+                continue;
+            }
+            let prefix = Self::object_prefix(data, entry.offset);
+            if prefix.contains_name(b"/XRef") {
+                let parsed = Self::parse_object_at(data, entry.offset);
+                if let Some(PdfObject::Stream { dict, .. }) = parsed {
+                    if dict.contains_key("Root") {
+                        return Some(dict);
+                    }
+                }
+            } else if catalog.is_none() && prefix.contains_name(b"/Catalog") {
+                catalog = Some(ObjRef::new(num, entry.generation));
+            }
+        }
+
+        catalog.map(|root| {
+            let mut trailer = HashMap::new();
+            trailer.insert("Root".to_string(), PdfObject::Ref(root));
+            trailer
         })
     }
 
+    /// The first bytes of the object at `offset`, enough to spot its /Type
+    fn object_prefix(data: &[u8], offset: usize) -> ObjectPrefix<'_> {
+        let start = offset.min(data.len());
+        ObjectPrefix(&data[start..(start + 512).min(data.len())])
+    }
+
+    /// Parse `N G obj ...` at `offset` without checking the object number
+    fn parse_object_at(data: &[u8], offset: usize) -> Option<PdfObject> {
+        let mut parser = Parser::new(data).lenient(true);
+        parser.seek(offset);
+        match (parser.parse_object().ok()??, parser.parse_object().ok()??) {
+            (PdfObject::Int(_), PdfObject::Int(_)) => parser.parse_object().ok()?,
+            _ => None,
+        }
+    }
+
     /// Find "startxref" by searching backwards from EOF
     fn find_startxref(data: &[u8]) -> Result<usize> {
         let search = b"startxref";
@@ -68,6 +848,25 @@ impl<'a> Document<'a> {
         Err(PdfError::MissingEof)
     }
 
+    /// Offset of the last xref section anywhere in the file: the one named
+    /// by the last `startxref`, or else the last `xref` keyword starting a
+    /// line
+    fn find_last_xref(data: &[u8]) -> Option<usize> {
+        let startxref = b"startxref";
+        if let Some(pos) = data.windows(startxref.len()).rposition(|w| w == startxref) {
+            if let Ok(offset) = Self::parse_startxref(data, pos) {
+                return Some(offset);
+            }
+        }
+
+        let keyword = b"xref";
+        (1..data.len().saturating_sub(keyword.len())).rev().find(|&i| {
+            matches!(data[i - 1], b'\n' | b'\r')
+                && data[i..].starts_with(keyword)
+                && data.get(i + keyword.len()).is_some_and(|b| b.is_ascii_whitespace())
+        })
+    }
+
     /// Parse the xref offset after "startxref"
     fn parse_startxref(data: &[u8], pos: usize) -> Result<usize> {
         let mut parser = Parser::new(data);
@@ -78,7 +877,7 @@ impl<'a> Document<'a> {
 
         // Parse the offset number
         match parser.parse_object()? {
-            Some(PdfObject::Int(offset)) => Ok(offset as usize),
+            Some(PdfObject::Int(offset)) if offset >= 0 => Ok(offset as usize),
             _ => Err(PdfError::Parse {
                 position: pos,
                 message: "Expected xref offset after startxref".into(),
@@ -90,20 +889,53 @@ impl<'a> Document<'a> {
     fn parse_xref_and_trailer(
         data: &[u8],
         offset: usize,
+        lenient: bool,
     ) -> Result<(HashMap<u32, XRefEntry>, HashMap<String, PdfObject>)> {
         let mut xref = HashMap::new();
         let mut current_offset = offset;
         let mut final_trailer: Option<HashMap<String, PdfObject>> = None;
+        let mut visited = HashSet::new();
 
         // Follow the Prev chain to collect all xref entries
         loop {
+            if !visited.insert(current_offset) {
+                return Err(PdfError::InvalidStructure("cycle detected".into()));
+            }
+
             // Check if this is a traditional xref table or xref stream
             if current_offset < data.len() && data[current_offset..].starts_with(b"xref") {
                 // Traditional xref table
-                Self::parse_traditional_xref(data, current_offset, &mut xref)?;
+                let mut section = HashMap::new();
+                Self::parse_traditional_xref(data, current_offset, &mut section, lenient)?;
 
                 // Find and parse trailer
-                let trailer = Self::find_and_parse_trailer(data, current_offset)?;
+                let trailer = Self::find_and_parse_trailer(data, current_offset, lenient)?;
+
+                // Hybrid-reference files list compressed objects in a separate
+                // xref stream. In-use entries from the table above take
+                // precedence; free ones are how the table hides those objects
+                // from older readers.
+                if let Some(stm_offset) = trailer.get("XRefStm").and_then(|o| o.as_int()) {
+                    let mut stream_entries = HashMap::new();
+                    let parsed =
+                        Self::parse_xref_stream(data, stm_offset as usize, &mut stream_entries);
+                    if let Err(err) = parsed
+                        && !lenient
+                    {
+                        return Err(err);
+                    }
+                    for (obj_num, entry) in stream_entries {
+                        if !section.get(&obj_num).is_some_and(|e: &XRefEntry| e.in_use) {
+                            section.insert(obj_num, entry);
+                        }
+                    }
+                }
+
+                // Sections are read newest first, so an entry already present,
+                // in use or free, comes from a later incremental update
+                for (obj_num, entry) in section {
+                    xref.entry(obj_num).or_insert(entry);
+                }
 
                 // Keep the most recent trailer (first one we encounter)
                 if final_trailer.is_none() {
@@ -145,6 +977,7 @@ impl<'a> Document<'a> {
         data: &[u8],
         offset: usize,
         xref: &mut HashMap<u32, XRefEntry>,
+        lenient: bool,
     ) -> Result<()> {
         let mut pos = offset + b"xref".len();
 
@@ -181,8 +1014,13 @@ impl<'a> Document<'a> {
                 break; // Not a valid subsection header, probably hit trailer
             }
 
-            let start_obj: u32 = parts[0].parse().map_err(|_| PdfError::InvalidXref)?;
-            let count: u32 = parts[1].parse().map_err(|_| PdfError::InvalidXref)?;
+            let (Ok(start_obj), Ok(count)) = (parts[0].parse::<u32>(), parts[1].parse::<u32>())
+            else {
+                if lenient {
+                    break;
+                }
+                return Err(PdfError::InvalidXref);
+            };
 
             // Move past the header line
             pos = line_end;
@@ -194,8 +1032,12 @@ impl<'a> Document<'a> {
                 pos += 1;
             }
 
-            // Parse entries - each entry is on its own line
+            // Parse entries - each entry is on its own line. A count that
+            // runs past the end of the file or the object numbers is cut off.
             for i in 0..count {
+                let Some(obj_num) = start_obj.checked_add(i).filter(|_| pos < data.len()) else {
+                    break;
+                };
                 // Find end of this entry line
                 let entry_end = data[pos..]
                     .iter()
@@ -203,48 +1045,16 @@ impl<'a> Document<'a> {
                     .map(|p| pos + p)
                     .unwrap_or(data.len());
 
-                if entry_end <= pos {
-                    return Err(PdfError::InvalidXref);
-                }
-
-                let entry_line = &data[pos..entry_end];
-
-                // Entry format: "nnnnnnnnnn ggggg f" or "nnnnnnnnnn ggggg n"
-                // Minimum 18 bytes (10 + 1 + 5 + 1 + 1)
-                if entry_line.len() < 17 {
-                    return Err(PdfError::InvalidXref);
-                }
-
-                // Parse offset (first 10 chars)
-                let offset_str = std::str::from_utf8(&entry_line[0..10])
-                    .map_err(|_| PdfError::InvalidXref)?;
-                let entry_offset: usize = offset_str
-                    .trim()
-                    .parse()
-                    .map_err(|_| PdfError::InvalidXref)?;
-
-                // Parse generation (chars 11-15)
-                let gen_str = std::str::from_utf8(&entry_line[11..16])
-                    .map_err(|_| PdfError::InvalidXref)?;
-                let generation: u16 = gen_str
-                    .trim()
-                    .parse()
-                    .map_err(|_| PdfError::InvalidXref)?;
-
-                // Parse in-use flag (char 17)
-                let flag = entry_line[17];
-                let in_use = flag == b'n';
-
-                if in_use {
-                    xref.insert(
-                        start_obj + i,
-                        XRefEntry {
-                            offset: entry_offset,
-                            generation,
-                            in_use,
-                            compressed: false,
-                        },
-                    );
+                // Entry format: "nnnnnnnnnn ggggg f" or "nnnnnnnnnn ggggg n".
+                // A malformed entry is skipped in lenient mode.
+                match Self::parse_xref_entry(&data[pos..entry_end]) {
+                    // Free entries are kept too, so they shadow the entries of
+                    // older sections for objects an update deleted
+                    Some(entry) => {
+                        xref.entry(obj_num).or_insert(entry);
+                    }
+                    None if lenient => {}
+                    None => return Err(PdfError::InvalidXref),
                 }
 
                 // Move to next line
@@ -262,10 +1072,27 @@ impl<'a> Document<'a> {
         Ok(())
     }
 
+    /// Parse one 20-byte xref table entry
+    fn parse_xref_entry(line: &[u8]) -> Option<XRefEntry> {
+        // Minimum 18 bytes (10 + 1 + 5 + 1 + 1) without the line ending
+        if line.len() < 18 {
+            return None;
+        }
+        let offset = std::str::from_utf8(&line[0..10]).ok()?.trim().parse().ok()?;
+        let generation = std::str::from_utf8(&line[11..16]).ok()?.trim().parse().ok()?;
+        Some(XRefEntry {
+            offset,
+            generation,
+            in_use: line[17] == b'n',
+            compressed: false,
+        })
+    }
+
     /// Find and parse trailer dictionary
     fn find_and_parse_trailer(
         data: &[u8],
         xref_offset: usize,
+        lenient: bool,
     ) -> Result<HashMap<String, PdfObject>> {
         // Search for "trailer" after xref
         let search = b"trailer";
@@ -283,7 +1110,7 @@ impl<'a> Document<'a> {
         }
 
         // Parse trailer dictionary
-        let mut parser = Parser::new(data);
+        let mut parser = Parser::new(data).lenient(lenient);
         parser.seek(pos + search.len());
 
         match parser.parse_object()? {
@@ -351,9 +1178,19 @@ impl<'a> Document<'a> {
             ));
         }
 
-        let w1 = w[0].as_int().unwrap_or(0) as usize;
-        let w2 = w[1].as_int().unwrap_or(0) as usize;
-        let w3 = w[2].as_int().unwrap_or(0) as usize;
+        // Each field must fit in a u64; anything wider is a corrupt /W
+        let mut widths = [0usize; 3];
+        for (width, obj) in widths.iter_mut().zip(w) {
+            let value = obj.as_int().unwrap_or(0);
+            if !(0..=8).contains(&value) {
+                return Err(PdfError::InvalidStructure(format!(
+                    "XRef stream /W entry out of range: {}",
+                    value
+                )));
+            }
+            *width = value as usize;
+        }
+        let [w1, w2, w3] = widths;
         let entry_size = w1 + w2 + w3;
 
         if entry_size == 0 {
@@ -394,7 +1231,9 @@ impl<'a> Document<'a> {
                 let field3 = Self::read_xref_field(&decoded[pos + w1 + w2..], w3, 0);
                 pos += entry_size;
 
-                let obj_num = start_obj + i;
+                let Some(obj_num) = start_obj.checked_add(i) else {
+                    break;
+                };
 
                 // Don't overwrite existing entries (most recent xref takes priority)
                 if xref.contains_key(&obj_num) {
@@ -403,7 +1242,16 @@ impl<'a> Document<'a> {
 
                 match field1 {
                     0 => {
-                        // Type 0: free object — skip
+                        // Type 0: free object, kept to shadow older sections
+                        xref.insert(
+                            obj_num,
+                            XRefEntry {
+                                offset: 0,
+                                generation: field3 as u16,
+                                in_use: false,
+                                compressed: false,
+                            },
+                        );
                     }
                     1 => {
                         // Type 1: regular object at byte offset
@@ -450,11 +1298,9 @@ impl<'a> Document<'a> {
         if width == 0 {
             return default;
         }
-        let mut val: u64 = 0;
-        for i in 0..width {
-            val = (val << 8) | data[i] as u64;
-        }
-        val
+        data.iter()
+            .take(width)
+            .fold(0u64, |val, &b| (val << 8) | b as u64)
     }
 
     /// Get the trailer dictionary
@@ -462,38 +1308,134 @@ impl<'a> Document<'a> {
         &self.trailer
     }
 
-    /// Get number of objects in xref
-    pub fn object_count(&self) -> usize {
-        self.xref.len()
-    }
+    /// The PDF version, e.g. `"1.7"`: the header's, or the catalog's
+    /// /Version when that is later
+    pub fn version(&mut self) -> String {
+        let header: String = self.data[b"%PDF-".len()..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit() || **b == b'.')
+            .map(|&b| b as char)
+            .collect();
+        let catalog = self.catalog().ok();
+        let catalog_version = catalog
+            .as_ref()
+            .and_then(|c| c.as_dict())
+            .and_then(|d| d.get("Version"))
+            .and_then(|v| v.as_name());
 
-    /// Resolve an object reference
-    pub fn resolve(&mut self, obj_ref: ObjRef) -> Result<&PdfObject> {
-        // Check cache first
-        if self.cache.contains_key(&obj_ref) {
-            return Ok(self.cache.get(&obj_ref).unwrap());
+        match catalog_version {
+            Some(version) if parse_version(version) > parse_version(&header) => version.to_string(),
+            _ => header,
         }
+    }
 
-        // Find in xref
-        let entry = self.xref.get(&obj_ref.obj_num).ok_or_else(|| {
-            PdfError::ObjectNotFound(obj_ref.obj_num, obj_ref.gen_num)
-        })?;
+    /// How the cross-reference data was stored, or [`XrefKind::Rebuilt`]
+    /// once it had to be rebuilt
+    pub fn xref_kind(&self) -> XrefKind {
+        if self.rebuilt { XrefKind::Rebuilt } else { self.xref_kind }
+    }
 
-        let entry_offset = entry.offset;
+    /// Names of the stream filters used anywhere in the file, sorted and
+    /// without duplicates. Reads every object.
+    pub fn stream_filters(&mut self) -> Vec<String> {
+        let mut refs: Vec<ObjRef> = self
+            .xref
+            .iter()
+            .filter(|(_, entry)| entry.in_use)
+            .map(|(&num, entry)| {
+                ObjRef::new(num, if entry.compressed { 0 } else { entry.generation })
+            })
+            .collect();
+        refs.sort_by_key(|r| r.obj_num);
+
+        let mut filters = BTreeSet::new();
+        for obj_ref in refs {
+            let Ok(PdfObject::Stream { dict, .. }) = self.resolve(obj_ref) else {
+                continue;
+            };
+            match dict.get("Filter") {
+                Some(PdfObject::Name(name)) => {
+                    filters.insert(name.clone());
+                }
+                Some(PdfObject::Array(items)) => {
+                    filters.extend(items.iter().filter_map(|f| f.as_name()).map(str::to_string));
+                }
+                _ => {}
+            }
+        }
+        filters.into_iter().collect()
+    }
+
+    /// The file identifiers from the trailer /ID: the permanent one given
+    /// when the file was created, and the one changed by each update.
+    /// `None` if the trailer has no well-formed /ID.
+    pub fn id(&self) -> Option<(&[u8], &[u8])> {
+        self.id.as_ref().map(|(permanent, changing)| (&permanent[..], &changing[..]))
+    }
+
+    /// Get number of in-use objects in xref
+    pub fn object_count(&self) -> usize {
+        self.xref.values().filter(|entry| entry.in_use).count()
+    }
+
+    /// Resolve an object reference
+    pub fn resolve(&mut self, obj_ref: ObjRef) -> Result<&PdfObject> {
+        // Check cache first
+        if self.cache.contains_key(&obj_ref) {
+            return Ok(self.cache.get(&obj_ref).unwrap());
+        }
+
+        // An object the xref doesn't list, or lists as free, is the null
+        // object
+        let Some(entry) = self.xref.get(&obj_ref.obj_num).filter(|e| e.in_use) else {
+            return Ok(&PdfObject::Null);
+        };
+
+        let entry_offset = entry.offset;
         let is_compressed = entry.compressed;
         let index_in_stream = entry.generation;
 
         if is_compressed {
             // Type 2: Object is compressed inside an object stream
             let obj_stream_num = entry_offset as u32;
-            let parsed_obj =
-                self.resolve_from_object_stream(obj_stream_num, index_in_stream as usize)?;
+            let parsed_obj = self.resolve_from_object_stream(
+                obj_ref.obj_num,
+                obj_stream_num,
+                index_in_stream as usize,
+            )?;
             self.cache.insert(obj_ref, parsed_obj);
             return Ok(self.cache.get(&obj_ref).unwrap());
         }
 
-        // Type 1: Regular object at byte offset
-        let mut parser = Parser::new(self.data);
+        // Type 1: Regular object at byte offset. An offset that doesn't land
+        // on the object means the xref is broken, so rebuild it and retry.
+        match self.parse_indirect_object(entry_offset, obj_ref) {
+            Ok(parsed_obj) => {
+                let parsed_obj = match &self.security {
+                    Some(security) => security.decrypt_object(obj_ref, parsed_obj),
+                    None => parsed_obj,
+                };
+                self.cache.insert(obj_ref, parsed_obj);
+                Ok(self.cache.get(&obj_ref).unwrap())
+            }
+            Err(err) if self.lenient && !self.rebuilt => {
+                self.warn(
+                    DiagnosticKind::Xref,
+                    format!("object {} not at its xref offset ({}); rebuilt xref", obj_ref, err),
+                );
+                self.rebuild_xref()?;
+                self.resolve(obj_ref)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Parse the indirect object `obj_ref` expected at `entry_offset`
+    fn parse_indirect_object(&self, entry_offset: usize, obj_ref: ObjRef) -> Result<PdfObject> {
+        let (data, xref) = (&self.data[..], &self.xref);
+        let mut parser = Parser::new(data)
+            .lenient(self.lenient)
+            .with_length_resolver(move |r| Self::indirect_length(data, xref, r));
         parser.seek(entry_offset);
 
         // Expect: obj_num gen_num obj <content> endobj
@@ -521,23 +1463,51 @@ impl<'a> Document<'a> {
 
         // Parse "obj" keyword and the actual content
         // parse_object() handles Token::Obj by recursively parsing
-        let parsed_obj = parser.parse_object()?.ok_or_else(|| PdfError::Parse {
+        parser.parse_object()?.ok_or_else(|| PdfError::Parse {
             position: parser.position(),
             message: "Expected object content".into(),
-        })?;
+        })
+    }
+
+    /// Look up an indirect stream /Length without touching the object cache.
+    /// Only uncompressed integer objects are supported.
+    fn indirect_length(
+        data: &[u8],
+        xref: &HashMap<u32, XRefEntry>,
+        obj_ref: ObjRef,
+    ) -> Option<usize> {
+        let entry = xref.get(&obj_ref.obj_num).filter(|e| e.in_use && !e.compressed)?;
+        let mut parser = Parser::new(data);
+        parser.seek(entry.offset);
 
-        // Cache and return
-        self.cache.insert(obj_ref, parsed_obj);
-        Ok(self.cache.get(&obj_ref).unwrap())
+        match (parser.parse_object().ok()??, parser.parse_object().ok()??) {
+            (PdfObject::Int(n), PdfObject::Int(_)) if n as u32 == obj_ref.obj_num => {}
+            _ => return None,
+        }
+        match parser.parse_object().ok()?? {
+            PdfObject::Int(len) => usize::try_from(len).ok(),
+            _ => None,
+        }
     }
 
     /// Resolve an object from an object stream (/ObjStm)
+    ///
+    /// The other members of the stream are parsed and cached at the same time,
+    /// so a stream holding many objects is only decoded once.
     fn resolve_from_object_stream(
         &mut self,
+        obj_num: u32,
         obj_stream_num: u32,
         index: usize,
     ) -> Result<PdfObject> {
-        // First, resolve the object stream itself (must be a regular type 1 entry)
+        // First, resolve the object stream itself. It must be a regular type 1
+        // entry; one listed inside an object stream would resolve back here.
+        if self.xref.get(&obj_stream_num).is_some_and(|e| e.compressed) {
+            return Err(PdfError::InvalidStructure(format!(
+                "Object stream {} is itself compressed",
+                obj_stream_num
+            )));
+        }
         let stream_ref = ObjRef::new(obj_stream_num, 0);
         let stream_obj = self.resolve(stream_ref)?.clone();
 
@@ -566,20 +1536,13 @@ impl<'a> Document<'a> {
             .ok_or_else(|| PdfError::InvalidStructure("ObjStm missing /First".into()))?
             as usize;
 
-        if index >= n {
-            return Err(PdfError::InvalidStructure(format!(
-                "ObjStm index {} out of range (N={})",
-                index, n
-            )));
-        }
-
         // Parse the header: pairs of (obj_num, byte_offset) for each object
         let mut header_parser = Parser::new(&decoded);
-        let mut offsets = Vec::with_capacity(n);
+        let mut members: Vec<(u32, usize)> = Vec::with_capacity(n.min(decoded.len()));
 
         for _ in 0..n {
-            let _obj_num = match header_parser.parse_object()? {
-                Some(PdfObject::Int(num)) => num,
+            let member_num = match header_parser.parse_object()? {
+                Some(PdfObject::Int(num)) => num as u32,
                 _ => {
                     return Err(PdfError::InvalidStructure(
                         "Invalid ObjStm header".into(),
@@ -594,18 +1557,57 @@ impl<'a> Document<'a> {
                     ));
                 }
             };
-            offsets.push(byte_offset);
+            members.push((member_num, byte_offset));
+        }
+
+        // The xref index is authoritative, but some writers get it wrong,
+        // so fall back to searching the header by object number
+        let target = match members.get(index) {
+            Some(&(num, _)) if num == obj_num => index,
+            _ => members
+                .iter()
+                .position(|&(num, _)| num == obj_num)
+                .ok_or_else(|| {
+                    PdfError::InvalidStructure(format!(
+                        "Object {} not found in ObjStm {} (N={})",
+                        obj_num, obj_stream_num, n
+                    ))
+                })?,
+        };
+
+        // Cache siblings that the xref still attributes to this stream
+        for (i, &(member_num, offset)) in members.iter().enumerate() {
+            if i == target {
+                continue;
+            }
+            let member_ref = ObjRef::new(member_num, 0);
+            let belongs_here = self.xref.get(&member_num).is_some_and(|e| {
+                e.compressed && e.offset == obj_stream_num as usize
+            });
+            if !belongs_here || self.cache.contains_key(&member_ref) {
+                continue;
+            }
+            let Some(member_offset) = first.checked_add(offset) else {
+                continue;
+            };
+            let mut member_parser = Parser::new(&decoded).lenient(self.lenient);
+            member_parser.seek(member_offset);
+            if let Ok(Some(obj)) = member_parser.parse_object() {
+                self.cache.insert(member_ref, obj);
+            }
         }
 
-        // Parse the object at the given index
-        let obj_offset = first + offsets[index];
-        let mut obj_parser = Parser::new(&decoded);
+        // Parse the requested object
+        let obj_offset = first.checked_add(members[target].1).ok_or_else(|| {
+            PdfError::InvalidStructure(format!("Invalid offset in ObjStm {}", obj_stream_num))
+        })?;
+        let mut obj_parser = Parser::new(&decoded).lenient(self.lenient);
         obj_parser.seek(obj_offset);
 
         obj_parser.parse_object()?.ok_or_else(|| {
             PdfError::InvalidStructure(format!(
                 "Failed to parse object at index {} in ObjStm {}",
-                index, obj_stream_num
+                target, obj_stream_num
             ))
         })
     }
@@ -618,6 +1620,37 @@ impl<'a> Document<'a> {
         }
     }
 
+    /// Resolve the object `obj_num gen_num R`. Unlike a reference, which
+    /// resolves to null, an object the xref doesn't list as in use is an
+    /// error.
+    pub fn get(&mut self, obj_num: u32, gen_num: u16) -> Result<PdfObject> {
+        if !self.xref.get(&obj_num).is_some_and(|e| e.in_use) {
+            return Err(PdfError::ObjectNotFound(obj_num, gen_num));
+        }
+        self.resolve(ObjRef::new(obj_num, gen_num)).cloned()
+    }
+
+    /// Iterate over every in-use object in the xref, in object number
+    /// order, resolving each one as it is reached
+    pub fn iter_objects(&mut self) -> Objects<'_, 'a> {
+        let mut refs: Vec<ObjRef> = self
+            .xref
+            .iter()
+            .filter(|(_, entry)| entry.in_use)
+            .map(|(&num, entry)| {
+                // Compressed entries store the index in `generation`
+                let generation = if entry.compressed { 0 } else { entry.generation };
+                ObjRef::new(num, generation)
+            })
+            .collect();
+        refs.sort_by_key(|r| r.obj_num);
+
+        Objects {
+            doc: self,
+            refs: refs.into_iter(),
+        }
+    }
+
     /// Get document catalog
     pub fn catalog(&mut self) -> Result<PdfObject> {
         let root_ref = self
@@ -630,269 +1663,4075 @@ impl<'a> Document<'a> {
         self.resolve(root_ref).cloned()
     }
 
-    /// Get page count
-    pub fn page_count(&mut self) -> Result<usize> {
-        let catalog = self.catalog()?;
-        let pages_ref = catalog
-            .as_dict()
-            .and_then(|d| d.get("Pages"))
-            .and_then(|p| p.as_ref())
-            .ok_or_else(|| PdfError::InvalidStructure("Missing Pages in catalog".into()))?;
-
-        // Use recursive collection to count actual pages instead of relying on Count field
-        let mut all_pages = Vec::new();
-        self.collect_pages(pages_ref, &mut all_pages)?;
-        Ok(all_pages.len())
-    }
+    /// Get document metadata from the /Info dictionary
+    pub fn info(&mut self) -> Result<DocumentInfo> {
+        let info = match self.trailer.get("Info").cloned() {
+            Some(info) => self.get_object(&info)?,
+            None => return Ok(DocumentInfo::default()),
+        };
+        let Some(dict) = info.as_dict() else {
+            return Ok(DocumentInfo::default());
+        };
 
-    /// Get decoded stream content from an object reference
-    pub fn get_stream_data(&mut self, obj_ref: ObjRef) -> Result<Vec<u8>> {
-        let obj = self.resolve(obj_ref)?.clone();
+        let mut field = |key: &str| -> Result<Option<String>> {
+            match dict.get(key) {
+                Some(value) => Ok(self.get_object(value)?.as_text_string()),
+                None => Ok(None),
+            }
+        };
 
-        match obj {
-            PdfObject::Stream { dict, data } => decode_stream(&dict, &data),
-            _ => Err(PdfError::InvalidStructure("Expected stream object".into())),
-        }
+        Ok(DocumentInfo {
+            title: field("Title")?,
+            author: field("Author")?,
+            subject: field("Subject")?,
+            keywords: field("Keywords")?,
+            creator: field("Creator")?,
+            producer: field("Producer")?,
+            creation_date: field("CreationDate")?.map(|d| normalize_pdf_date(&d)),
+            mod_date: field("ModDate")?.map(|d| normalize_pdf_date(&d)),
+        })
     }
 
-    /// Get a page by index (0-based)
-    pub fn get_page(&mut self, index: usize) -> Result<PdfObject> {
+    /// Get the document outline (bookmarks) as a tree
+    pub fn outline(&mut self) -> Result<Vec<OutlineItem>> {
         let catalog = self.catalog()?;
-        let pages_ref = catalog
-            .as_dict()
-            .and_then(|d| d.get("Pages"))
-            .and_then(|p| p.as_ref())
-            .ok_or_else(|| PdfError::InvalidStructure("Missing Pages in catalog".into()))?;
-
-        // Collect all pages recursively
-        let mut all_pages = Vec::new();
-        self.collect_pages(pages_ref, &mut all_pages)?;
+        let outlines = match catalog.as_dict().and_then(|d| d.get("Outlines")) {
+            Some(o) => self.get_object(o)?,
+            None => return Ok(Vec::new()),
+        };
+        let Some(first) = outlines.as_dict().and_then(|d| d.get("First")).cloned() else {
+            return Ok(Vec::new());
+        };
 
-        all_pages
-            .get(index)
-            .cloned()
-            .ok_or_else(|| PdfError::InvalidStructure(format!("Page {} not found", index)))
+        let page_refs = self.page_refs()?;
+        let mut visited = HashSet::new();
+        self.collect_outline_items(&first, &page_refs, 0, &mut visited)
     }
 
-    /// Recursively collect all Page objects from a Pages tree
-    fn collect_pages(&mut self, node_ref: ObjRef, pages: &mut Vec<PdfObject>) -> Result<()> {
-        let node = self.resolve(node_ref)?.clone();
-        let dict = node
-            .as_dict()
-            .ok_or_else(|| PdfError::InvalidStructure("Expected dict in page tree".into()))?;
-
-        // Check the Type
-        let type_name = dict
-            .get("Type")
-            .and_then(|t| t.as_name())
-            .unwrap_or("");
+    /// Get the link annotations of a page (0-indexed) that lead to a URI or
+    /// to a page of this document. Links whose target cannot be resolved
+    /// are left out.
+    pub fn extract_page_links(&mut self, page_index: usize) -> Result<Vec<Link>> {
+        let page = self.get_page(page_index)?;
+        let annots = match page.as_dict().and_then(|d| d.get("Annots")) {
+            Some(a) => self.get_object(a)?,
+            None => return Ok(Vec::new()),
+        };
 
-        match type_name {
-            "Page" => {
-                // It's a leaf page
-                pages.push(node.clone());
+        let page_refs = self.page_refs()?;
+        let mut links = Vec::new();
+        for annot in annots.as_array().cloned().unwrap_or_default() {
+            let annot = self.get_object(&annot)?;
+            let Some(dict) = annot.as_dict() else {
+                continue;
+            };
+            if dict.get("Subtype").and_then(|s| s.as_name()) != Some("Link") {
+                continue;
             }
-            "Pages" => {
-                // It's an intermediate node - recurse into Kids
-                let kids = dict
-                    .get("Kids")
-                    .and_then(|k| k.as_array())
-                    .ok_or_else(|| PdfError::InvalidStructure("Pages node missing Kids".into()))?;
+            let rect = match dict.get("Rect") {
+                Some(r) => self.read_rect(r)?,
+                None => None,
+            };
+            let Some([x1, y1, x2, y2]) = rect else {
+                continue;
+            };
 
-                for kid in kids {
-                    if let Some(kid_ref) = kid.as_ref() {
-                        self.collect_pages(kid_ref, pages)?;
-                    }
-                }
-            }
-            _ => {
-                // Unknown type - try to treat as page
-                if dict.contains_key("Contents") || dict.contains_key("MediaBox") {
-                    pages.push(node.clone());
+            let target = match (dict.get("A"), dict.get("Dest")) {
+                (Some(action), _) => self.link_action_target(action, &page_refs)?,
+                (None, Some(dest)) => {
+                    self.resolve_dest_page(dest, &page_refs)?.map(LinkTarget::Page)
                 }
+                (None, None) => None,
+            };
+            if let Some(target) = target {
+                links.push(Link {
+                    rect: [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)],
+                    target,
+                });
             }
         }
-
-        Ok(())
+        Ok(links)
     }
 
-    /// Get content stream(s) from a page
-    pub fn get_page_contents(&mut self, page: &PdfObject) -> Result<Vec<u8>> {
-        let contents = page
-            .as_dict()
-            .and_then(|d| d.get("Contents"))
-            .ok_or_else(|| PdfError::InvalidStructure("Page has no Contents".into()))?;
+    /// Get the comments and markup annotations on a page (0-based index),
+    /// such as sticky notes, highlights and free text boxes. Links, form
+    /// widgets and the popup windows that display other annotations are
+    /// left out.
+    pub fn extract_page_annotations(&mut self, page_index: usize) -> Result<Vec<Annotation>> {
+        let page = self.get_page(page_index)?;
+        let annots = match page.as_dict().and_then(|d| d.get("Annots")) {
+            Some(a) => self.get_object(a)?,
+            None => return Ok(Vec::new()),
+        };
 
-        match contents {
-            PdfObject::Ref(r) => self.get_stream_data(*r),
-            PdfObject::Array(arr) => {
-                // Multiple content streams - concatenate
-                let mut result = Vec::new();
-                for item in arr {
-                    if let Some(r) = item.as_ref() {
-                        let data = self.get_stream_data(r)?;
-                        result.extend(data);
-                        result.push(b'\n'); // Separate streams
-                    }
-                }
-                Ok(result)
-            }
-            _ => Err(PdfError::InvalidStructure("Invalid Contents type".into())),
+        let mut annotations = Vec::new();
+        for annot in annots.as_array().cloned().unwrap_or_default() {
+            let annot = self.get_object(&annot)?;
+            let Some(dict) = annot.as_dict() else {
+                continue;
+            };
+            let subtype = match dict.get("Subtype").and_then(|s| s.as_name()) {
+                Some("Link" | "Widget" | "Popup") | None => continue,
+                Some(subtype) => subtype.to_string(),
+            };
+            let rect = match dict.get("Rect") {
+                Some(r) => self.read_rect(r)?,
+                None => None,
+            };
+            let Some([x1, y1, x2, y2]) = rect else {
+                continue;
+            };
+            let contents = match dict.get("Contents") {
+                Some(c) => self.get_object(c)?.as_text_string(),
+                None => None,
+            };
+            let author = match dict.get("T") {
+                Some(t) => self.get_object(t)?.as_text_string(),
+                None => None,
+            };
+            annotations.push(Annotation {
+                subtype,
+                contents,
+                rect: [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)],
+                author,
+            });
         }
+        Ok(annotations)
     }
 
-    /// Extract text spans from a page (0-indexed)
-    pub fn extract_page_text(&mut self, page_index: usize) -> Result<Vec<TextSpan>> {
-        let page = self.get_page(page_index)?;
-        let content = self.get_page_contents(&page)?;
-
-        // Load font encodings from page resources
-        let font_encodings = self.load_font_encodings(&page)?;
-
-        let parser = ContentParser::with_fonts(&content, font_encodings);
-        parser.parse()
+    /// Target of a link's /URI or /GoTo action
+    fn link_action_target(
+        &mut self,
+        action: &PdfObject,
+        page_refs: &[ObjRef],
+    ) -> Result<Option<LinkTarget>> {
+        let action = self.get_object(action)?;
+        let Some(dict) = action.as_dict() else {
+            return Ok(None);
+        };
+        match dict.get("S").and_then(|s| s.as_name()) {
+            Some("URI") => match dict.get("URI") {
+                Some(uri) => Ok(self.get_object(uri)?.as_text_string().map(LinkTarget::Uri)),
+                None => Ok(None),
+            },
+            Some("GoTo") => match dict.get("D") {
+                Some(dest) => Ok(self.resolve_dest_page(dest, page_refs)?.map(LinkTarget::Page)),
+                None => Ok(None),
+            },
+            _ => Ok(None),
+        }
     }
 
-    /// Load font encodings from page resources
-    fn load_font_encodings(&mut self, page: &PdfObject) -> Result<HashMap<String, FontEncoding>> {
-        let mut encodings = HashMap::new();
+    /// List the fonts in the pages' /Font resources, in order of first use.
+    /// A font object shared by several pages is listed once.
+    pub fn fonts(&mut self) -> Result<Vec<FontInfo>> {
+        let pages: Vec<PdfObject> = self.page_index()?.iter().map(|(_, p)| p.clone()).collect();
+        let mut seen = HashSet::new();
+        let mut out = Vec::new();
+        for page in &pages {
+            let resources = match page.as_dict().and_then(|d| d.get("Resources")) {
+                Some(r) => self.get_object(r)?,
+                None => continue,
+            };
+            let fonts = match resources.as_dict().and_then(|d| d.get("Font")) {
+                Some(f) => self.get_object(f)?,
+                None => continue,
+            };
+            let Some(fonts) = fonts.as_dict() else {
+                continue;
+            };
+            let mut names: Vec<&String> = fonts.keys().collect();
+            names.sort();
+            for name in names {
+                let font = &fonts[name];
+                if font.as_ref().is_some_and(|font_ref| !seen.insert(font_ref)) {
+                    continue;
+                }
+                let PdfObject::Dict(font_dict) = self.get_object(font)? else {
+                    continue;
+                };
+                let info = self.font_info(&font_dict)?;
+                // A font given inline in inherited resources repeats on
+                // every page
+                if font.as_ref().is_none() && out.contains(&info) {
+                    continue;
+                }
+                out.push(info);
+            }
+        }
+        Ok(out)
+    }
 
-        // Get Resources dictionary
-        let resources = match page.as_dict().and_then(|d| d.get("Resources")) {
-            Some(r) => self.get_object(r)?,
-            None => return Ok(encodings),
+    /// Describe one font dictionary for [`Document::fonts`]
+    fn font_info(&mut self, font_dict: &HashMap<String, PdfObject>) -> Result<FontInfo> {
+        let name = |dict: &HashMap<String, PdfObject>, key: &str| {
+            dict.get(key).and_then(|n| n.as_name()).unwrap_or("").to_string()
         };
+        let subtype = name(font_dict, "Subtype");
 
-        // Get Font dictionary from Resources
-        let fonts = match resources.as_dict().and_then(|d| d.get("Font")) {
-            Some(f) => self.get_object(f)?,
-            None => return Ok(encodings),
+        let described = self.described_font(font_dict)?;
+        let embedded = match described.get("FontDescriptor") {
+            _ if subtype == "Type3" => true,
+            Some(descriptor) => self.get_object(descriptor)?.as_dict().is_some_and(|d| {
+                ["FontFile", "FontFile2", "FontFile3"].iter().any(|k| d.contains_key(*k))
+            }),
+            None => false,
         };
 
-        // Iterate over fonts
-        if let Some(font_dict) = fonts.as_dict() {
-            for (font_name, font_ref) in font_dict {
-                if let Ok(encoding) = self.load_single_font_encoding(font_ref) {
-                    encodings.insert(font_name.clone(), encoding);
+        let encoding = match font_dict.get("Encoding") {
+            Some(encoding) => match self.get_object(encoding)? {
+                PdfObject::Name(encoding) => encoding,
+                PdfObject::Dict(dict) if dict.contains_key("BaseEncoding") => {
+                    name(&dict, "BaseEncoding")
                 }
-            }
-        }
+                PdfObject::Stream { dict, .. } if dict.contains_key("CMapName") => {
+                    name(&dict, "CMapName")
+                }
+                _ => "Custom".to_string(),
+            },
+            None => String::new(),
+        };
 
-        Ok(encodings)
+        Ok(FontInfo { base_font: name(font_dict, "BaseFont"), subtype, embedded, encoding })
     }
 
-    /// Load encoding for a single font
-    fn load_single_font_encoding(&mut self, font_ref: &PdfObject) -> Result<FontEncoding> {
-        let font = self.get_object(font_ref)?;
-        let font_dict = font.as_dict().ok_or_else(|| {
-            PdfError::InvalidStructure("Font is not a dictionary".into())
-        })?;
-
-        // Check for ToUnicode CMap first (most accurate)
-        if let Some(tounicode_ref) = font_dict.get("ToUnicode") {
-            if let Some(obj_ref) = tounicode_ref.as_ref() {
-                if let Ok(cmap_data) = self.get_stream_data(obj_ref) {
-                    if let Ok(cid_map) = parse_tounicode_cmap(&cmap_data) {
-                        return Ok(FontEncoding::from_cid_map(cid_map));
+    /// The dictionary holding a font's /FontDescriptor: the font itself, or
+    /// for a composite font its descendant CIDFont
+    fn described_font(
+        &mut self,
+        font_dict: &HashMap<String, PdfObject>,
+    ) -> Result<HashMap<String, PdfObject>> {
+        if font_dict.get("Subtype").and_then(|s| s.as_name()) == Some("Type0") {
+            if let Some(descendants) = font_dict.get("DescendantFonts") {
+                let descendants = self.get_object(descendants)?;
+                if let Some(first) = descendants.as_array().and_then(|a| a.first()) {
+                    if let PdfObject::Dict(cid_dict) = self.get_object(first)? {
+                        return Ok(cid_dict);
                     }
                 }
             }
         }
+        Ok(font_dict.clone())
+    }
 
-        // Check Encoding
-        if let Some(encoding) = font_dict.get("Encoding") {
-            match encoding {
-                PdfObject::Name(name) => {
-                    return Ok(match name.as_str() {
-                        "WinAnsiEncoding" => FontEncoding::win_ansi(),
-                        "MacRomanEncoding" => FontEncoding::mac_roman(),
-                        _ => FontEncoding::win_ansi(), // Default to WinAnsi
-                    });
-                }
-                PdfObject::Dict(enc_dict) => {
-                    // Custom encoding with Differences array
-                    // Start with base encoding
-                    let encoding = if let Some(base) = enc_dict.get("BaseEncoding") {
-                        match base.as_name() {
-                            Some("WinAnsiEncoding") => FontEncoding::win_ansi(),
-                            Some("MacRomanEncoding") => FontEncoding::mac_roman(),
-                            _ => FontEncoding::win_ansi(),
-                        }
-                    } else {
-                        FontEncoding::win_ansi()
-                    };
+    /// Get the fields of the document's interactive form, in form order
+    pub fn form_fields(&mut self) -> Result<Vec<FormField>> {
+        let catalog = self.catalog()?;
+        let acro_form = match catalog.as_dict().and_then(|d| d.get("AcroForm")) {
+            Some(f) => self.get_object(f)?,
+            None => return Ok(Vec::new()),
+        };
+        let fields = match acro_form.as_dict().and_then(|d| d.get("Fields")) {
+            Some(f) => self.get_object(f)?,
+            None => return Ok(Vec::new()),
+        };
 
-                    // TODO: Apply Differences array
-                    return Ok(encoding);
-                }
-                _ => {}
-            }
+        let mut out = Vec::new();
+        let mut visited = HashSet::new();
+        for field in fields.as_array().cloned().unwrap_or_default() {
+            self.collect_form_field(&field, "", None, None, &mut visited, &mut out)?;
         }
-
-        // Default: WinAnsi encoding
-        Ok(FontEncoding::win_ansi())
+        Ok(out)
     }
 
-    /// Extract all text from a page as a single string
-    pub fn extract_page_text_string(&mut self, page_index: usize) -> Result<String> {
-        let spans = self.extract_page_text(page_index)?;
-
-        // Sort by y (descending) then x (ascending)
-        let mut spans = spans;
-        spans.sort_by(|a, b| {
-            b.y.partial_cmp(&a.y)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then(a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal))
-        });
+    /// Collect a field, or the terminal fields below it. /FT and /V are
+    /// inherited from ancestors; kids without /T are widgets of this field.
+    fn collect_form_field(
+        &mut self,
+        field: &PdfObject,
+        parent_name: &str,
+        inherited_type: Option<&str>,
+        inherited_value: Option<&PdfObject>,
+        visited: &mut HashSet<ObjRef>,
+        out: &mut Vec<FormField>,
+    ) -> Result<()> {
+        if let Some(field_ref) = field.as_ref() {
+            if !visited.insert(field_ref) {
+                return Ok(());
+            }
+        }
+        let field = self.get_object(field)?;
+        let Some(dict) = field.as_dict() else {
+            return Ok(());
+        };
 
-        // Group into lines by y position
-        let mut lines: Vec<Vec<&TextSpan>> = Vec::new();
-        let mut current_line: Vec<&TextSpan> = Vec::new();
-        let mut current_y: Option<f64> = None;
-        let tolerance = 3.0;
+        let partial = match dict.get("T") {
+            Some(t) => self.get_object(t)?.as_text_string(),
+            None => None,
+        };
+        let name = match (&partial, parent_name.is_empty()) {
+            (Some(partial), true) => partial.clone(),
+            (Some(partial), false) => format!("{}.{}", parent_name, partial),
+            (None, _) => parent_name.to_string(),
+        };
+        let field_type = dict.get("FT").and_then(|t| t.as_name()).or(inherited_type);
+        let value = dict.get("V").or(inherited_value);
 
-        for span in &spans {
-            match current_y {
-                Some(y) if (span.y - y).abs() <= tolerance => {
-                    current_line.push(span);
-                }
-                _ => {
-                    if !current_line.is_empty() {
-                        lines.push(current_line);
-                    }
-                    current_y = Some(span.y);
-                    current_line = vec![span];
+        // Kids with their own /T are child fields; the rest are widgets
+        let mut child_fields = Vec::new();
+        if let Some(kids) = dict.get("Kids") {
+            for kid in self.get_object(kids)?.as_array().cloned().unwrap_or_default() {
+                let kid_obj = self.get_object(&kid)?;
+                if kid_obj.as_dict().is_some_and(|k| k.contains_key("T")) {
+                    child_fields.push(kid);
                 }
             }
         }
-        if !current_line.is_empty() {
-            lines.push(current_line);
+
+        if !child_fields.is_empty() {
+            for kid in &child_fields {
+                self.collect_form_field(kid, &name, field_type, value, visited, out)?;
+            }
+            return Ok(());
         }
 
-        // Build text output
-        let text: String = lines
-            .iter()
-            .map(|line| {
-                line.iter()
-                    .map(|span| span.text.as_str())
+        let Some(field_type) = field_type else {
+            return Ok(());
+        };
+        let value = match value {
+            Some(v) => self.form_value(v)?,
+            None => None,
+        };
+        out.push(FormField {
+            name,
+            value,
+            field_type: field_type.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Text of a field's /V: a text string, a name (button state), or an
+    /// array of selected choice options
+    fn form_value(&mut self, value: &PdfObject) -> Result<Option<String>> {
+        Ok(match self.get_object(value)? {
+            PdfObject::Name(name) => Some(name),
+            PdfObject::Array(items) => {
+                let mut options = Vec::new();
+                for item in &items {
+                    options.extend(self.get_object(item)?.as_text_string());
+                }
+                Some(options.join(", "))
+            }
+            PdfObject::Stream { dict, data } => {
+                // Rich text values may be given as a stream
+                let decoded = decode_stream(&dict, &data)?;
+                Some(PdfObject::String(decoded).as_text_string().unwrap_or_default())
+            }
+            other => other.as_text_string(),
+        })
+    }
+
+    /// Walk a /First../Next sibling chain, recursing into children.
+    /// An item already `visited` ends its chain; an outline nested deeper
+    /// than `MAX_OUTLINE_DEPTH` is an error.
+    fn collect_outline_items(
+        &mut self,
+        first: &PdfObject,
+        page_refs: &[ObjRef],
+        depth: usize,
+        visited: &mut HashSet<ObjRef>,
+    ) -> Result<Vec<OutlineItem>> {
+        if depth > MAX_OUTLINE_DEPTH {
+            return Err(PdfError::InvalidStructure("outline too deep".into()));
+        }
+        let mut items = Vec::new();
+        let mut next = Some(first.clone());
+
+        while let Some(item_obj) = next.take() {
+            // Malformed files can link siblings into a loop
+            if let Some(item_ref) = item_obj.as_ref() {
+                if !visited.insert(item_ref) {
+                    break;
+                }
+            }
+            let item = self.get_object(&item_obj)?;
+            let Some(dict) = item.as_dict() else {
+                break;
+            };
+
+            let title = match dict.get("Title") {
+                Some(t) => self.get_object(t)?.as_text_string().unwrap_or_default(),
+                None => String::new(),
+            };
+
+            let dest = match (dict.get("Dest"), dict.get("A")) {
+                (Some(dest), _) => Some(dest.clone()),
+                (None, Some(action)) => {
+                    let action = self.get_object(action)?;
+                    action
+                        .as_dict()
+                        .filter(|a| a.get("S").and_then(|s| s.as_name()) == Some("GoTo"))
+                        .and_then(|a| a.get("D"))
+                        .cloned()
+                }
+                (None, None) => None,
+            };
+            let dest_page = match dest {
+                Some(dest) => self.resolve_dest_page(&dest, page_refs)?,
+                None => None,
+            };
+
+            let children = match dict.get("First") {
+                Some(child) => self.collect_outline_items(child, page_refs, depth + 1, visited)?,
+                None => Vec::new(),
+            };
+
+            items.push(OutlineItem {
+                title,
+                dest_page,
+                children,
+            });
+            next = dict.get("Next").cloned();
+        }
+
+        Ok(items)
+    }
+
+    /// Map a destination (explicit array, /D dictionary or name) to a page index
+    fn resolve_dest_page(
+        &mut self,
+        dest: &PdfObject,
+        page_refs: &[ObjRef],
+    ) -> Result<Option<usize>> {
+        match self.get_object(dest)? {
+            PdfObject::Array(arr) => Ok(match arr.first() {
+                Some(PdfObject::Ref(page_ref)) => page_refs.iter().position(|r| r == page_ref),
+                // Remote destinations use a page number instead of a reference
+                Some(PdfObject::Int(n)) => {
+                    usize::try_from(*n).ok().filter(|&n| n < page_refs.len())
+                }
+                _ => None,
+            }),
+            PdfObject::Dict(dict) => match dict.get("D") {
+                Some(d) => self.resolve_dest_page(d, page_refs),
+                None => Ok(None),
+            },
+            PdfObject::Name(name) => self.resolve_dests_entry(name.as_bytes(), page_refs),
+            PdfObject::String(name) => self.resolve_dests_entry(&name, page_refs),
+            _ => Ok(None),
+        }
+    }
+
+    /// Map a named destination to a 0-based page index. Looks in the
+    /// catalog's /Names /Dests name tree, then in the older /Dests dictionary.
+    pub fn resolve_named_dest(&mut self, name: &str) -> Result<Option<usize>> {
+        let page_refs = self.page_refs()?;
+        self.resolve_dests_entry(name.as_bytes(), &page_refs)
+    }
+
+    /// Look up a named destination in the catalog's /Names /Dests name tree
+    /// or /Dests dictionary
+    fn resolve_dests_entry(&mut self, name: &[u8], page_refs: &[ObjRef]) -> Result<Option<usize>> {
+        let catalog = self.catalog()?;
+        let catalog = catalog.as_dict();
+
+        let mut dest = None;
+        if let Some(names) = catalog.and_then(|d| d.get("Names")) {
+            let names = self.get_object(names)?;
+            if let Some(tree) = names.as_dict().and_then(|d| d.get("Dests")) {
+                dest = self.find_in_name_tree(tree, name, &mut HashSet::new())?;
+            }
+        }
+        if dest.is_none() {
+            if let Some(dests) = catalog.and_then(|d| d.get("Dests")) {
+                let key = String::from_utf8_lossy(name);
+                dest = self.get_object(dests)?.as_dict().and_then(|d| d.get(key.as_ref())).cloned();
+            }
+        }
+
+        match dest {
+            // Guard against a named destination that refers to itself
+            Some(PdfObject::Name(_)) | Some(PdfObject::String(_)) | None => Ok(None),
+            Some(dest) => self.resolve_dest_page(&dest, page_refs),
+        }
+    }
+
+    /// Find `key` in a name tree, descending only into /Kids whose /Limits
+    /// cover it
+    fn find_in_name_tree(
+        &mut self,
+        node: &PdfObject,
+        key: &[u8],
+        visited: &mut HashSet<ObjRef>,
+    ) -> Result<Option<PdfObject>> {
+        if let Some(node_ref) = node.as_ref() {
+            if !visited.insert(node_ref) {
+                return Ok(None);
+            }
+        }
+        let node = self.get_object(node)?;
+        let Some(dict) = node.as_dict() else {
+            return Ok(None);
+        };
+
+        if let Some(limits) = dict.get("Limits") {
+            let limits = self.get_object(limits)?;
+            if let Some([low, high]) = limits.as_array().map(Vec::as_slice) {
+                if let (Some(low), Some(high)) = (low.as_string(), high.as_string()) {
+                    if key < low || key > high {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+
+        if let Some(names) = dict.get("Names") {
+            let names = self.get_object(names)?;
+            for pair in names.as_array().map(Vec::as_slice).unwrap_or_default().chunks_exact(2) {
+                if pair[0].as_string() == Some(key) {
+                    return Ok(Some(self.get_object(&pair[1])?));
+                }
+            }
+        }
+
+        if let Some(kids) = dict.get("Kids") {
+            for kid in self.get_object(kids)?.as_array().cloned().unwrap_or_default() {
+                if let Some(found) = self.find_in_name_tree(&kid, key, visited)? {
+                    return Ok(Some(found));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get page count. This may come from the root /Count without walking
+    /// the page tree, so it can be wrong for damaged files; loops over the
+    /// pages should use [`Document::page_count_exact`] or
+    /// [`Document::pages`].
+    pub fn page_count(&mut self) -> Result<usize> {
+        if let Some(pages) = &self.pages {
+            return Ok(pages.len());
+        }
+        let pages_ref = self.pages_root()?;
+        if let Some(count) = self.trusted_page_count(pages_ref)? {
+            return Ok(count);
+        }
+
+        // Count the actual pages when /Count cannot be relied on
+        Ok(self.page_index()?.len())
+    }
+
+    /// Count the pages by walking the page tree, the number of pages that
+    /// [`Document::get_page`] can return
+    pub fn page_count_exact(&mut self) -> Result<usize> {
+        Ok(self.page_index()?.len())
+    }
+
+    /// The root page tree node's /Count, if it passes cheap sanity checks:
+    /// positive, at least one page per kid, and no more pages than the file
+    /// has objects
+    fn trusted_page_count(&mut self, pages_ref: ObjRef) -> Result<Option<usize>> {
+        let node = self.resolve(pages_ref)?.clone();
+        let Some(dict) = node.as_dict() else {
+            return Ok(None);
+        };
+        let count = match dict.get("Count") {
+            Some(count) => self.get_object(count)?.as_int(),
+            None => None,
+        };
+        let kids = match dict.get("Kids") {
+            Some(kids) => self.get_object(kids)?.as_array().map(Vec::len),
+            None => None,
+        };
+
+        Ok(match (count, kids) {
+            (Some(count), Some(kids))
+                if count > 0 && count as usize >= kids && count as usize <= self.object_count() =>
+            {
+                Some(count as usize)
+            }
+            _ => None,
+        })
+    }
+
+    /// Check the document structure and report every problem found.
+    ///
+    /// Verifies the xref offsets, the catalog, the page tree /Count and each
+    /// page's /Contents. Problems are returned rather than raised, so a
+    /// damaged file yields as complete a report as possible.
+    pub fn validate(&mut self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let issue = |kind, object, message: String| ValidationIssue {
+            kind,
+            object,
+            message,
+        };
+
+        // Offsets are checked first: resolving objects may rebuild the xref
+        let mut entries: Vec<_> = self
+            .xref
+            .iter()
+            .filter(|(_, e)| e.in_use && !e.compressed)
+            .map(|(&num, e)| (num, e.clone()))
+            .collect();
+        entries.sort_by_key(|(num, _)| *num);
+        for (num, entry) in entries {
+            let obj_ref = ObjRef::new(num, entry.generation);
+            let mut parser = Parser::new(&self.data);
+            parser.seek(entry.offset);
+            let header = (parser.parse_object(), parser.parse_object());
+            let found = match header {
+                (Ok(Some(PdfObject::Int(n))), Ok(Some(PdfObject::Int(g)))) => Some((n, g)),
+                _ => None,
+            };
+            if found != Some((num as i64, entry.generation as i64)) {
+                let message = match found {
+                    Some((n, g)) => format!(
+                        "offset {} holds object {} {}, expected {}",
+                        entry.offset, n, g, obj_ref
+                    ),
+                    None => format!("offset {} does not start an object", entry.offset),
+                };
+                issues.push(issue(ValidationIssueKind::XrefOffset, Some(obj_ref), message));
+            }
+        }
+
+        let root_ref = match self.trailer.get("Root") {
+            Some(PdfObject::Ref(r)) => *r,
+            Some(_) => {
+                let message = "/Root must be a reference".into();
+                issues.push(issue(ValidationIssueKind::Catalog, None, message));
+                return issues;
+            }
+            None => {
+                let message = "trailer has no /Root".into();
+                issues.push(issue(ValidationIssueKind::Catalog, None, message));
+                return issues;
+            }
+        };
+        let catalog = match self.resolve(root_ref) {
+            Ok(obj) => obj.clone(),
+            Err(err) => {
+                let message = format!("cannot read /Root: {}", err);
+                issues.push(issue(ValidationIssueKind::Catalog, Some(root_ref), message));
+                return issues;
+            }
+        };
+        let catalog_type = catalog
+            .as_dict()
+            .and_then(|d| d.get("Type"))
+            .and_then(|t| t.as_name());
+        if catalog_type != Some("Catalog") {
+            let message = "/Root is not a /Catalog dictionary".into();
+            issues.push(issue(ValidationIssueKind::Catalog, Some(root_ref), message));
+        }
+
+        let Some(pages_ref) = catalog
+            .as_dict()
+            .and_then(|d| d.get("Pages"))
+            .and_then(|p| p.as_ref())
+        else {
+            let message = "catalog has no /Pages reference".into();
+            issues.push(issue(ValidationIssueKind::PageTree, Some(root_ref), message));
+            return issues;
+        };
+
+        let mut pages = Vec::new();
+        let mut seen = HashSet::new();
+        let collected = self.collect_pages(
+            pages_ref,
+            &HashMap::new(),
+            0,
+            &mut HashSet::new(),
+            &mut seen,
+            &mut pages,
+        );
+        if let Err(err) = collected {
+            let message = format!("cannot read page tree: {}", err);
+            issues.push(issue(ValidationIssueKind::PageTree, Some(pages_ref), message));
+            return issues;
+        }
+
+        let count = match self.resolve(pages_ref) {
+            Ok(node) => node.as_dict().and_then(|d| d.get("Count")).cloned(),
+            Err(_) => None,
+        };
+        match count.map(|c| self.get_object(&c)) {
+            Some(Ok(PdfObject::Int(n))) if n == pages.len() as i64 => {}
+            Some(Ok(PdfObject::Int(n))) => {
+                let message = format!("/Count is {} but the tree holds {} pages", n, pages.len());
+                issues.push(issue(ValidationIssueKind::PageTree, Some(pages_ref), message));
+            }
+            _ => {
+                let message = "/Pages has no integer /Count".into();
+                issues.push(issue(ValidationIssueKind::PageTree, Some(pages_ref), message));
+            }
+        }
+
+        for (index, (page_ref, page)) in pages.iter().enumerate() {
+            let Some(contents) = page.as_dict().and_then(|d| d.get("Contents")) else {
+                continue;
+            };
+            let streams = match contents {
+                PdfObject::Array(items) => items.clone(),
+                other => vec![other.clone()],
+            };
+            for item in streams {
+                let message = match self.get_object(&item) {
+                    Ok(PdfObject::Stream { .. }) => continue,
+                    Ok(other) => format!("page {} /Contents is not a stream: {:?}", index, other),
+                    Err(err) => format!("page {} /Contents cannot be read: {}", index, err),
+                };
+                issues.push(issue(ValidationIssueKind::PageContents, Some(*page_ref), message));
+            }
+        }
+
+        issues
+    }
+
+    /// Get decoded stream content from an object reference
+    pub fn get_stream_data(&mut self, obj_ref: ObjRef) -> Result<Vec<u8>> {
+        let obj = self.resolve(obj_ref)?.clone();
+
+        match obj {
+            PdfObject::Stream { dict, data } => decode_stream(&dict, &data),
+            _ => Err(PdfError::InvalidStructure("Expected stream object".into())),
+        }
+    }
+
+    /// Get a page by index (0-based)
+    pub fn get_page(&mut self, index: usize) -> Result<PdfObject> {
+        self.page_index()?
+            .get(index)
+            .map(|(_, page)| page.clone())
+            .ok_or_else(|| PdfError::InvalidStructure(format!("Page {} not found", index)))
+    }
+
+    /// References of all leaf pages, in document order
+    fn page_refs(&mut self) -> Result<Vec<ObjRef>> {
+        Ok(self.page_index()?.iter().map(|(page_ref, _)| *page_ref).collect())
+    }
+
+    /// Reference to the root of the page tree
+    fn pages_root(&mut self) -> Result<ObjRef> {
+        let catalog = self.catalog()?;
+        catalog
+            .as_dict()
+            .and_then(|d| d.get("Pages"))
+            .and_then(|p| p.as_ref())
+            .ok_or_else(|| PdfError::InvalidStructure("Missing Pages in catalog".into()))
+    }
+
+    /// Every leaf page, walking the page tree on first use only so that
+    /// looking pages up one by one stays linear in the number of pages
+    fn page_index(&mut self) -> Result<&[(ObjRef, PdfObject)]> {
+        if self.pages.is_none() {
+            let pages_ref = self.pages_root()?;
+            let mut all_pages = Vec::new();
+            let (mut path, mut seen) = (HashSet::new(), HashSet::new());
+            let inherited = HashMap::new();
+            self.collect_pages(pages_ref, &inherited, 0, &mut path, &mut seen, &mut all_pages)?;
+            self.pages = Some(all_pages);
+        }
+        Ok(self.pages.as_deref().unwrap_or_default())
+    }
+
+    /// Recursively collect all Page objects from a Pages tree.
+    /// Inheritable attributes from ancestor nodes are merged into each leaf.
+    /// A node that is its own ancestor (`path` holds the nodes above this
+    /// one), or a tree nested deeper than `MAX_PAGE_TREE_DEPTH`, is an
+    /// error. A node already `seen` under another parent is skipped with a
+    /// warning.
+    fn collect_pages(
+        &mut self,
+        node_ref: ObjRef,
+        inherited: &HashMap<String, PdfObject>,
+        depth: usize,
+        path: &mut HashSet<ObjRef>,
+        seen: &mut HashSet<ObjRef>,
+        pages: &mut Vec<(ObjRef, PdfObject)>,
+    ) -> Result<()> {
+        if path.contains(&node_ref) {
+            return Err(PdfError::InvalidStructure("cycle detected".into()));
+        }
+        if !seen.insert(node_ref) {
+            let message = format!("page tree node {} listed twice, skipped", node_ref);
+            self.warn(DiagnosticKind::PageTree, message);
+            return Ok(());
+        }
+        if depth > MAX_PAGE_TREE_DEPTH {
+            return Err(PdfError::InvalidStructure("page tree too deep".into()));
+        }
+        let node = self.resolve(node_ref)?.clone();
+        let dict = node
+            .as_dict()
+            .ok_or_else(|| PdfError::InvalidStructure("Expected dict in page tree".into()))?;
+
+        // Check the Type
+        let type_name = dict
+            .get("Type")
+            .and_then(|t| t.as_name())
+            .unwrap_or("");
+
+        match type_name {
+            "Page" => {
+                // It's a leaf page
+                pages.push((node_ref, with_inherited(dict, inherited)));
+            }
+            "Pages" => {
+                // It's an intermediate node - recurse into Kids
+                let kids = dict
+                    .get("Kids")
+                    .and_then(|k| k.as_array())
+                    .ok_or_else(|| PdfError::InvalidStructure("Pages node missing Kids".into()))?;
+
+                let mut inherited = inherited.clone();
+                for key in INHERITABLE_PAGE_KEYS {
+                    if let Some(value) = dict.get(*key) {
+                        inherited.insert(key.to_string(), value.clone());
+                    }
+                }
+
+                path.insert(node_ref);
+                for kid in kids {
+                    if let Some(kid_ref) = kid.as_ref() {
+                        self.collect_pages(kid_ref, &inherited, depth + 1, path, seen, pages)?;
+                    }
+                }
+                path.remove(&node_ref);
+            }
+            _ => {
+                // Unknown type - try to treat as page
+                if dict.contains_key("Contents") || dict.contains_key("MediaBox") {
+                    pages.push((node_ref, with_inherited(dict, inherited)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the MediaBox and CropBox of a page (0-based)
+    pub fn page_dimensions(&mut self, index: usize) -> Result<PageBox> {
+        let page = self.get_page(index)?;
+        let dict = page.as_dict();
+
+        // US Letter when the page tree has no MediaBox at all
+        let media_box = match dict.and_then(|d| d.get("MediaBox")) {
+            Some(b) => self.read_rect(b)?.unwrap_or([0.0, 0.0, 612.0, 792.0]),
+            None => [0.0, 0.0, 612.0, 792.0],
+        };
+        let crop_box = match dict.and_then(|d| d.get("CropBox")) {
+            Some(b) => self.read_rect(b)?.unwrap_or(media_box),
+            None => media_box,
+        };
+
+        Ok(PageBox {
+            media_box,
+            crop_box,
+        })
+    }
+
+    /// Read a rectangle array of four numbers
+    fn read_rect(&mut self, obj: &PdfObject) -> Result<Option<[f64; 4]>> {
+        let obj = self.get_object(obj)?;
+        let Some(arr) = obj.as_array() else {
+            return Ok(None);
+        };
+        if arr.len() != 4 {
+            return Ok(None);
+        }
+
+        let mut rect = [0.0; 4];
+        for (value, item) in rect.iter_mut().zip(arr) {
+            match self.get_object(item)?.as_real() {
+                Some(n) => *value = n,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(rect))
+    }
+
+    /// Get content stream(s) from a page
+    pub fn get_page_contents(&mut self, page: &PdfObject) -> Result<Vec<u8>> {
+        // A page without /Contents is blank
+        let Some(contents) = page.as_dict().and_then(|d| d.get("Contents")) else {
+            return Ok(Vec::new());
+        };
+
+        match self.get_object(contents)? {
+            PdfObject::Stream { .. } => match contents.as_ref() {
+                Some(r) => self.get_stream_data(r),
+                None => Err(PdfError::InvalidStructure("Invalid Contents type".into())),
+            },
+            PdfObject::Array(arr) => {
+                // Multiple content streams - concatenate
+                let mut result = Vec::new();
+                for item in arr {
+                    if let Some(r) = item.as_ref() {
+                        // In lenient mode an unreadable part is left out
+                        let data = match self.get_stream_data(r) {
+                            Ok(data) => data,
+                            Err(err) if self.lenient => {
+                                let message = format!("skipped content stream {}: {}", r, err);
+                                self.warn(DiagnosticKind::Content, message);
+                                continue;
+                            }
+                            Err(err) => return Err(err),
+                        };
+                        // Streams break at token boundaries, so a separator is
+                        // only needed when the previous one ends mid-token
+                        if result.last().is_some_and(|&b| !is_content_delimiter(b)) {
+                            result.push(b'\n');
+                        }
+                        result.extend(data);
+                    }
+                }
+                Ok(result)
+            }
+            _ => Err(PdfError::InvalidStructure("Invalid Contents type".into())),
+        }
+    }
+
+    /// The decoded content stream of a page (0-indexed), as the content
+    /// parser sees it: multiple streams are concatenated, and a page without
+    /// contents gives an empty stream
+    pub fn page_content_stream(&mut self, page_index: usize) -> Result<Vec<u8>> {
+        let page = self.get_page(page_index)?;
+        self.get_page_contents(&page)
+    }
+
+    /// Iterate over the text spans of each page in order.
+    ///
+    /// Pages are extracted lazily, so each page's spans can be dropped before
+    /// the next one is parsed. The iterator holds a mutable borrow of the
+    /// document (object resolution fills the cache), and the spans it yields
+    /// are owned, so they may outlive both the iterator and the document.
+    pub fn pages(&mut self) -> Pages<'_, 'a> {
+        self.pages_with(ExtractOptions::default())
+    }
+
+    /// Like [`Document::pages`], extracting each page with `options`
+    pub fn pages_with(&mut self, options: ExtractOptions) -> Pages<'_, 'a> {
+        let remaining = self.page_count_exact();
+        Pages {
+            doc: self,
+            index: 0,
+            remaining,
+            options,
+        }
+    }
+
+    /// Extract text spans from a page (0-indexed)
+    pub fn extract_page_text(&mut self, page_index: usize) -> Result<Vec<TextSpan>> {
+        self.extract_page_text_with(page_index, &ExtractOptions::default())
+    }
+
+    /// Extract text spans from a page (0-indexed) with explicit options
+    pub fn extract_page_text_with(
+        &mut self,
+        page_index: usize,
+        options: &ExtractOptions,
+    ) -> Result<Vec<TextSpan>> {
+        let job = self.page_job(page_index)?;
+        let (spans, report) = job.parser(options).parse_with_report()?;
+        self.warn_parse_report(page_index, report);
+        Ok(spans)
+    }
+
+    /// Record the problems found while parsing a page's content: fonts that
+    /// showed codes without a Unicode mapping and malformed or unknown operators
+    fn warn_parse_report(&mut self, page_index: usize, report: ParseReport) {
+        let mut unmapped: Vec<_> = report.unmapped.into_iter().collect();
+        unmapped.sort();
+        let previous = self.current_page.replace(page_index);
+        for (font, count) in unmapped {
+            let message = format!("font /{}: {} codes without a Unicode mapping", font, count);
+            self.warn(DiagnosticKind::Font, message);
+        }
+        for warning in report.warnings {
+            self.warn(DiagnosticKind::Content, warning);
+        }
+        self.current_page = previous;
+    }
+
+    /// Extract the unmerged spans of a page (0-indexed): one per
+    /// text-showing operator, in content stream order
+    pub fn extract_page_spans_raw(&mut self, page_index: usize) -> Result<Vec<TextSpan>> {
+        self.page_job(page_index)?.parser(&ExtractOptions::default()).parse_raw()
+    }
+
+    /// Extract text spans from a page (0-indexed), merging raw spans with
+    /// custom thresholds
+    pub fn extract_page_text_with_config(
+        &mut self,
+        page_index: usize,
+        config: MergeConfig,
+    ) -> Result<Vec<TextSpan>> {
+        self.extract_page_text_with(page_index, &ExtractOptions::new().merge_config(config))
+    }
+
+    /// Extract the text spans of a page (0-indexed) that fall within `rect`,
+    /// e.g. a footer band or the corner holding an invoice total.
+    ///
+    /// `rect` is `[x1, y1, x2, y2]` in PDF units with the origin at the
+    /// bottom-left of the page, the same space as [`TextSpan`] positions
+    /// (upright, after any /Rotate). A span is kept when its box, from the
+    /// baseline up to its height or font size, overlaps the rectangle.
+    pub fn extract_region(&mut self, page_index: usize, rect: [f64; 4]) -> Result<Vec<TextSpan>> {
+        let [x1, y1, x2, y2] = rect;
+        let (left, right) = (x1.min(x2), x1.max(x2));
+        let (bottom, top) = (y1.min(y2), y1.max(y2));
+
+        let mut spans = self.extract_page_text(page_index)?;
+        spans.retain(|span| {
+            let height = if span.height > 0.0 { span.height } else { span.font_size };
+            let baseline = span.baseline();
+            span.x <= right
+                && span.right() >= left
+                && baseline <= top
+                && baseline + height >= bottom
+        });
+        Ok(spans)
+    }
+
+    /// Resolve everything needed to parse a page's content into an owned
+    /// work item that no longer borrows the document
+    fn page_job(&mut self, page_index: usize) -> Result<PageJob> {
+        let previous = self.current_page.replace(page_index);
+        let job = self.load_page_job(page_index);
+        self.current_page = previous;
+        job
+    }
+
+    fn load_page_job(&mut self, page_index: usize) -> Result<PageJob> {
+        let page = self.get_page(page_index)?;
+        let content = self.get_page_contents(&page)?;
+
+        // Load font encodings from page resources
+        let font_encodings = self.load_font_encodings(&page)?;
+        let color_spaces = self.load_color_spaces(&page);
+        let mut forms = FormXObjects::default();
+        forms.names = self.load_forms(&page, &mut forms, &mut HashMap::new(), 0)?;
+
+        Ok(PageJob {
+            content,
+            font_encodings,
+            color_spaces,
+            forms,
+            transform: self.page_rotation_matrix(&page)?,
+        })
+    }
+
+    /// Extract the text spans of every page, parsing the content streams of
+    /// all pages in parallel. Objects are still resolved one page at a time;
+    /// results are in page order.
+    #[cfg(feature = "parallel")]
+    pub fn extract_all_pages_parallel(&mut self) -> Result<Vec<Vec<TextSpan>>> {
+        self.extract_all_pages_parallel_with(&ExtractOptions::default())
+    }
+
+    /// Like [`Document::extract_all_pages_parallel`] with explicit options
+    #[cfg(feature = "parallel")]
+    pub fn extract_all_pages_parallel_with(
+        &mut self,
+        options: &ExtractOptions,
+    ) -> Result<Vec<Vec<TextSpan>>> {
+        self.extract_page_results_parallel(options)?.into_iter().collect()
+    }
+
+    /// The spans or error of each page, parsed in parallel
+    #[cfg(feature = "parallel")]
+    pub(crate) fn extract_page_results_parallel(
+        &mut self,
+        options: &ExtractOptions,
+    ) -> Result<Vec<Result<Vec<TextSpan>>>> {
+        use rayon::prelude::*;
+
+        let jobs: Vec<_> =
+            (0..self.page_count_exact()?).map(|page_index| self.page_job(page_index)).collect();
+        let results: Vec<_> = jobs
+            .into_par_iter()
+            .map(|job| job.and_then(|job| job.parser(options).parse_with_report()))
+            .collect();
+
+        let mut pages = Vec::with_capacity(results.len());
+        for (page_index, result) in results.into_iter().enumerate() {
+            pages.push(result.map(|(spans, report)| {
+                self.warn_parse_report(page_index, report);
+                spans
+            }));
+        }
+        Ok(pages)
+    }
+
+    /// Extract the words on a page (0-indexed) with their positions
+    pub fn extract_page_words(&mut self, page_index: usize) -> Result<Vec<Word>> {
+        self.page_job(page_index)?.parser(&ExtractOptions::default()).parse_words()
+    }
+
+    /// Find every occurrence of `query` in the document's text. Words on the
+    /// same line are joined with single spaces, so a query may cross span
+    /// boundaries; each hit is positioned at the character where it starts.
+    pub fn search(&mut self, query: &str, case_insensitive: bool) -> Result<Vec<SearchHit>> {
+        let mut hits = Vec::new();
+        if query.is_empty() {
+            return Ok(hits);
+        }
+
+        for page in 0..self.page_count_exact()? {
+            let words = self.extract_page_words(page)?;
+            hits.extend(search_words(page, words, query, case_insensitive));
+        }
+        Ok(hits)
+    }
+
+    /// Transform that turns a page's /Rotate into upright coordinates.
+    /// Values that are not a multiple of 90 are treated as 0.
+    fn page_rotation_matrix(&mut self, page: &PdfObject) -> Result<[f64; 6]> {
+        let dict = page.as_dict();
+        let rotate = match dict.and_then(|d| d.get("Rotate")) {
+            Some(r) => self.get_object(r)?.as_int().unwrap_or(0),
+            None => 0,
+        };
+        let [x0, y0, x1, y1] = match dict.and_then(|d| d.get("MediaBox")) {
+            Some(b) => self.read_rect(b)?.unwrap_or([0.0, 0.0, 612.0, 792.0]),
+            None => [0.0, 0.0, 612.0, 792.0],
+        };
+        let (w, h) = (x1 - x0, y1 - y0);
+
+        Ok(match rotate.rem_euclid(360) {
+            90 => [0.0, -1.0, 1.0, 0.0, x0 - y0, x0 + y0 + w],
+            180 => [-1.0, 0.0, 0.0, -1.0, 2.0 * x0 + w, 2.0 * y0 + h],
+            270 => [0.0, 1.0, -1.0, 0.0, x0 + y0 + h, y0 - x0],
+            _ => [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+        })
+    }
+
+    /// Extract text spans from a page (0-indexed) as a JSON array.
+    /// Each span carries its 1-based `page` number.
+    #[cfg(feature = "serde")]
+    pub fn extract_page_json(&mut self, page_index: usize) -> Result<String> {
+        let spans = self.extract_page_text(page_index)?;
+        Ok(pages_to_json(&[(page_index + 1, spans)]))
+    }
+
+    /// Load font encodings from page resources
+    fn load_font_encodings(&mut self, page: &PdfObject) -> Result<HashMap<String, FontEncoding>> {
+        let mut encodings = HashMap::new();
+
+        // Get Resources dictionary
+        let resources = match page.as_dict().and_then(|d| d.get("Resources")) {
+            Some(r) => self.get_object(r)?,
+            None => return Ok(encodings),
+        };
+
+        // Get Font dictionary from Resources
+        let fonts = match resources.as_dict().and_then(|d| d.get("Font")) {
+            Some(f) => self.get_object(f)?,
+            None => return Ok(encodings),
+        };
+
+        // Iterate over fonts
+        if let Some(font_dict) = fonts.as_dict() {
+            for (font_name, font_ref) in font_dict {
+                let cache_key = font_ref.as_ref();
+                if let Some(encoding) = cache_key.and_then(|r| self.font_cache.get(&r)) {
+                    encodings.insert(font_name.clone(), encoding.clone());
+                } else {
+                    match self.load_single_font_encoding(font_ref) {
+                        Ok(encoding) => {
+                            if let Some(r) = cache_key {
+                                self.font_cache.insert(r, encoding.clone());
+                            }
+                            encodings.insert(font_name.clone(), encoding);
+                        }
+                        Err(err) => self.warn(
+                            DiagnosticKind::Font,
+                            format!("font /{} not loaded: {}", font_name, err),
+                        ),
+                    }
+                }
+            }
+        }
+
+        Ok(encodings)
+    }
+
+    /// Load the Form XObjects in the /XObject resources of a page or form
+    /// into `forms`, returning their indexes by resource name. Each form is
+    /// loaded once (`loaded` maps its object to its index), and forms
+    /// nested deeper than can be drawn are left out.
+    fn load_forms(
+        &mut self,
+        owner: &PdfObject,
+        forms: &mut FormXObjects,
+        loaded: &mut HashMap<ObjRef, usize>,
+        depth: usize,
+    ) -> Result<HashMap<String, usize>> {
+        let mut names = HashMap::new();
+        if depth >= MAX_FORM_DEPTH {
+            return Ok(names);
+        }
+        let resources = match owner.as_dict().and_then(|d| d.get("Resources")) {
+            Some(r) => self.get_object(r)?,
+            None => return Ok(names),
+        };
+        let xobjects = match resources.as_dict().and_then(|d| d.get("XObject")) {
+            Some(x) => self.get_object(x)?,
+            None => return Ok(names),
+        };
+        let Some(xobjects) = xobjects.as_dict() else {
+            return Ok(names);
+        };
+
+        for (name, xobject) in xobjects {
+            let Some(form_ref) = xobject.as_ref() else {
+                continue;
+            };
+            if let Some(&index) = loaded.get(&form_ref) {
+                names.insert(name.clone(), index);
+                continue;
+            }
+            // Only look at the dictionary, as images can be large
+            let dict = match self.resolve(form_ref) {
+                Ok(PdfObject::Stream { dict, .. })
+                    if dict.get("Subtype").and_then(|s| s.as_name()) == Some("Form") =>
+                {
+                    dict.clone()
+                }
+                Ok(_) => continue,
+                Err(err) => {
+                    let message = format!("xobject /{} not loaded: {}", name, err);
+                    self.warn(DiagnosticKind::Content, message);
+                    continue;
+                }
+            };
+            let content = match self.get_stream_data(form_ref) {
+                Ok(content) => content,
+                Err(err) => {
+                    let message = format!("form /{} not loaded: {}", name, err);
+                    self.warn(DiagnosticKind::Content, message);
+                    continue;
+                }
+            };
+            let matrix = match dict.get("Matrix").map(|m| self.read_matrix(m)) {
+                Some(Ok(matrix)) => matrix,
+                Some(Err(err)) => {
+                    let message = format!("form /{} not loaded: {}", name, err);
+                    self.warn(DiagnosticKind::Content, message);
+                    continue;
+                }
+                None => None,
+            };
+
+            let index = forms.forms.len();
+            forms.forms.push(FormXObject {
+                content,
+                matrix: matrix.unwrap_or([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
+                fonts: None,
+                names: None,
+                color_spaces: None,
+            });
+            loaded.insert(form_ref, index);
+            names.insert(name.clone(), index);
+
+            // A form without /Resources uses those of whatever draws it. If
+            // they can't be loaded, it is drawn with those instead.
+            if dict.contains_key("Resources") {
+                let owner = PdfObject::Dict(dict);
+                let resources = self.load_font_encodings(&owner).and_then(|fonts| {
+                    let nested = self.load_forms(&owner, forms, loaded, depth + 1)?;
+                    Ok((fonts, nested))
+                });
+                match resources {
+                    Ok((fonts, nested)) => {
+                        forms.forms[index].fonts = Some(fonts);
+                        forms.forms[index].color_spaces = Some(self.load_color_spaces(&owner));
+                        forms.forms[index].names = Some(nested);
+                    }
+                    Err(err) => {
+                        let message = format!("resources of form /{} not loaded: {}", name, err);
+                        self.warn(DiagnosticKind::Content, message);
+                    }
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// The color spaces in `owner`'s /ColorSpace resources whose colors
+    /// convert to RGB, with their number of components: ICCBased by its /N,
+    /// CalGray, CalRGB and device spaces. Lab, Indexed, Separation, DeviceN
+    /// and Pattern spaces are left out, as are those that can't be read.
+    fn load_color_spaces(&mut self, owner: &PdfObject) -> HashMap<String, usize> {
+        let mut spaces = HashMap::new();
+        let resources = match owner.as_dict().and_then(|d| d.get("Resources")) {
+            Some(r) => self.get_object(r).unwrap_or(PdfObject::Null),
+            None => return spaces,
+        };
+        let color_spaces = match resources.as_dict().and_then(|d| d.get("ColorSpace")) {
+            Some(c) => self.get_object(c).unwrap_or(PdfObject::Null),
+            None => return spaces,
+        };
+        let Some(color_spaces) = color_spaces.as_dict() else {
+            return spaces;
+        };
+
+        for (name, space) in color_spaces {
+            let Ok(space) = self.get_object(space) else {
+                continue;
+            };
+            let components = match &space {
+                PdfObject::Name(name) => device_color_components(name),
+                PdfObject::Array(parts) => match parts.first().and_then(|p| p.as_name()) {
+                    Some("CalGray") => Some(1),
+                    Some("CalRGB") => Some(3),
+                    Some("ICCBased") => parts
+                        .get(1)
+                        .and_then(|stream| self.get_object(stream).ok())
+                        .and_then(|stream| stream.as_dict()?.get("N")?.as_int())
+                        .and_then(|n| usize::try_from(n).ok())
+                        .filter(|n| matches!(n, 1 | 3 | 4)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(components) = components {
+                spaces.insert(name.clone(), components);
+            }
+        }
+        spaces
+    }
+
+    /// Read a matrix array of six numbers
+    fn read_matrix(&mut self, obj: &PdfObject) -> Result<Option<[f64; 6]>> {
+        let obj = self.get_object(obj)?;
+        let Some(arr) = obj.as_array() else {
+            return Ok(None);
+        };
+        if arr.len() != 6 {
+            return Ok(None);
+        }
+
+        let mut matrix = [0.0; 6];
+        for (value, item) in matrix.iter_mut().zip(arr) {
+            match self.get_object(item)?.as_real() {
+                Some(n) => *value = n,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(matrix))
+    }
+
+    /// Extract the raster images in a page's (0-indexed) /XObject
+    /// resources, sorted by resource name
+    pub fn extract_page_images(&mut self, page_index: usize) -> Result<Vec<PdfImage>> {
+        let page = self.get_page(page_index)?;
+        let resources = match page.as_dict().and_then(|d| d.get("Resources")) {
+            Some(r) => self.get_object(r)?,
+            None => return Ok(Vec::new()),
+        };
+        let xobjects = match resources.as_dict().and_then(|d| d.get("XObject")) {
+            Some(x) => self.get_object(x)?,
+            None => return Ok(Vec::new()),
+        };
+        let Some(xobjects) = xobjects.as_dict() else {
+            return Ok(Vec::new());
+        };
+
+        let mut names: Vec<&String> = xobjects.keys().collect();
+        names.sort();
+
+        let mut images = Vec::new();
+        for name in names {
+            let PdfObject::Stream { dict, data } = self.get_object(&xobjects[name])? else {
+                continue;
+            };
+            if dict.get("Subtype").and_then(|s| s.as_name()) != Some("Image") {
+                continue;
+            }
+
+            let mut number = |key: &str| -> Result<Option<i64>> {
+                match dict.get(key) {
+                    Some(v) => Ok(self.get_object(v)?.as_int()),
+                    None => Ok(None),
+                }
+            };
+            let width = number("Width")?.unwrap_or(0) as u32;
+            let height = number("Height")?.unwrap_or(0) as u32;
+            let bits_per_component = number("BitsPerComponent")?.map(|b| b as u8);
+            let color_space = match dict.get("ColorSpace") {
+                Some(cs) => self.color_space_name(cs)?,
+                None => None,
+            };
+            let (data, format) = decode_image_data(&dict, &data)?;
+
+            images.push(PdfImage {
+                name: name.clone(),
+                width,
+                height,
+                color_space,
+                bits_per_component,
+                format,
+                data,
+            });
+        }
+
+        Ok(images)
+    }
+
+    /// Family name of a color space: a name, or the first element of an
+    /// array such as `[/Indexed ...]`
+    fn color_space_name(&mut self, color_space: &PdfObject) -> Result<Option<String>> {
+        let color_space = self.get_object(color_space)?;
+        let (family, params) = match &color_space {
+            PdfObject::Name(name) => return Ok(Some(name.clone())),
+            PdfObject::Array(items) => match items.first().and_then(|f| f.as_name()) {
+                Some(family) => (family, items.get(1)),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        if family == "ICCBased" {
+            if let Some(profile) = params {
+                let components = self
+                    .get_object(profile)?
+                    .as_stream()
+                    .and_then(|(dict, _)| dict.get("N"))
+                    .and_then(|n| n.as_int());
+                match components {
+                    Some(1) => return Ok(Some("DeviceGray".into())),
+                    Some(3) => return Ok(Some("DeviceRGB".into())),
+                    Some(4) => return Ok(Some("DeviceCMYK".into())),
+                    _ => {}
+                }
+            }
+        }
+        Ok(Some(family.to_string()))
+    }
+
+    /// Load encoding and glyph widths for a single font
+    fn load_single_font_encoding(&mut self, font_ref: &PdfObject) -> Result<FontEncoding> {
+        let font = self.get_object(font_ref)?;
+        let font_dict = font.as_dict().ok_or_else(|| {
+            PdfError::InvalidStructure("Font is not a dictionary".into())
+        })?;
+
+        let mut encoding = self.load_font_base_encoding(font_dict)?;
+        self.load_font_widths(font_dict, &mut encoding)?;
+        self.load_font_style(font_dict, &mut encoding)?;
+
+        // An embedded CMap for a composite font defines how codes are split
+        // and which CID each code selects; Identity-H/V use two-byte codes
+        // equal to the CID
+        match font_dict.get("Encoding") {
+            Some(PdfObject::Name(name)) if name == "Identity-H" || name == "Identity-V" => {
+                encoding.set_codespace_ranges(vec![CodespaceRange {
+                    low: vec![0x00, 0x00],
+                    high: vec![0xFF, 0xFF],
+                }]);
+            }
+            Some(PdfObject::Ref(cmap_ref)) => match self.get_stream_data(*cmap_ref) {
+                Ok(cmap_data) => {
+                    let ranges = parse_codespace_ranges(&cmap_data);
+                    if !ranges.is_empty() {
+                        encoding.set_codespace_ranges(ranges);
+                    }
+                    encoding.set_cid_ranges(parse_cid_ranges(&cmap_data));
+                }
+                Err(err) => {
+                    self.warn(DiagnosticKind::Font, format!("CMap {} not read: {}", cmap_ref, err))
+                }
+            },
+            _ => {}
+        }
+        Ok(encoding)
+    }
+
+    /// Load the code-to-Unicode mapping for a font
+    fn load_font_base_encoding(
+        &mut self,
+        font_dict: &HashMap<String, PdfObject>,
+    ) -> Result<FontEncoding> {
+        // Check for ToUnicode CMap first (most accurate)
+        if let Some(tounicode_ref) = font_dict.get("ToUnicode") {
+            if let Some(obj_ref) = tounicode_ref.as_ref() {
+                let cmap = self.get_stream_data(obj_ref).and_then(|cmap_data| {
+                    parse_tounicode_cmap(&cmap_data).map(|cid_map| (cmap_data, cid_map))
+                });
+                match cmap {
+                    Ok((cmap_data, cid_map)) => {
+                        let mut encoding = FontEncoding::from_cid_map(cid_map);
+                        encoding.set_codespace_ranges(parse_codespace_ranges(&cmap_data));
+                        return Ok(encoding);
+                    }
+                    Err(err) => self.warn(
+                        DiagnosticKind::Font,
+                        format!("ToUnicode CMap {} not used: {}", obj_ref, err),
+                    ),
+                }
+            }
+        }
+
+        // Type3 glyph names are arbitrary, so only /Differences entries with
+        // known names are trusted; there is no base encoding to fall back on
+        if font_dict.get("Subtype").and_then(|s| s.as_name()) == Some("Type3") {
+            let mut encoding = FontEncoding::empty();
+            if let Some(enc) = font_dict.get("Encoding") {
+                let enc = self.get_object(enc)?;
+                if let Some(differences) = enc.as_dict().and_then(|d| d.get("Differences")) {
+                    if let Some(arr) = self.get_object(differences)?.as_array() {
+                        encoding.apply_differences(arr);
+                    }
+                }
+            }
+            return Ok(encoding);
+        }
+
+        // Check Encoding
+        if let Some(encoding) = font_dict.get("Encoding") {
+            match self.get_object(encoding)? {
+                PdfObject::Name(name) => {
+                    return Ok(match name.as_str() {
+                        "WinAnsiEncoding" => FontEncoding::win_ansi(),
+                        "MacRomanEncoding" => FontEncoding::mac_roman(),
+                        _ => FontEncoding::win_ansi(), // Default to WinAnsi
+                    });
+                }
+                PdfObject::Dict(enc_dict) => {
+                    // Custom encoding with Differences array
+                    // Start with base encoding
+                    let mut encoding = if let Some(base) = enc_dict.get("BaseEncoding") {
+                        match base.as_name() {
+                            Some("WinAnsiEncoding") => FontEncoding::win_ansi(),
+                            Some("MacRomanEncoding") => FontEncoding::mac_roman(),
+                            _ => FontEncoding::win_ansi(),
+                        }
+                    } else {
+                        FontEncoding::win_ansi()
+                    };
+
+                    if let Some(differences) = enc_dict.get("Differences") {
+                        if let Some(arr) = self.get_object(differences)?.as_array() {
+                            encoding.apply_differences(arr);
+                        }
+                    }
+                    return Ok(encoding);
+                }
+                _ => {}
+            }
+        }
+
+        // Default: WinAnsi encoding
+        Ok(FontEncoding::win_ansi())
+    }
+
+    /// Load glyph widths: /Widths for simple fonts, /W and /DW for Type0 fonts
+    fn load_font_widths(
+        &mut self,
+        font_dict: &HashMap<String, PdfObject>,
+        encoding: &mut FontEncoding,
+    ) -> Result<()> {
+        if font_dict.get("Subtype").and_then(|s| s.as_name()) == Some("Type0") {
+            encoding.set_two_byte(true);
+
+            let descendant = match font_dict.get("DescendantFonts") {
+                Some(d) => self.get_object(d)?,
+                None => return Ok(()),
+            };
+            let cid_font = match descendant.as_array().and_then(|a| a.first()) {
+                Some(f) => self.get_object(f)?,
+                None => return Ok(()),
+            };
+            let Some(cid_dict) = cid_font.as_dict() else {
+                return Ok(());
+            };
+
+            // Default width is 1000 when /DW is absent
+            let default_width = match cid_dict.get("DW") {
+                Some(dw) => self.get_object(dw)?.as_real().unwrap_or(1000.0),
+                None => 1000.0,
+            };
+            encoding.set_missing_width(default_width);
+
+            if let Some(w) = cid_dict.get("W") {
+                if let Some(items) = self.get_object(w)?.as_array() {
+                    self.apply_cid_widths(items, encoding)?;
+                }
+            }
+            // /CIDToGIDMap is /Identity or a stream of big-endian glyph
+            // indices; one that can't be read is taken as /Identity
+            if let Some(PdfObject::Ref(map_ref)) = cid_dict.get("CIDToGIDMap") {
+                match self.get_stream_data(*map_ref) {
+                    Ok(map) => {
+                        let gids = map.chunks_exact(2).map(|g| u16::from_be_bytes([g[0], g[1]]));
+                        encoding.set_cid_to_gid(Some(gids.collect()));
+                    }
+                    Err(err) => {
+                        let message =
+                            format!("CIDToGIDMap {} not loaded, using Identity: {}", map_ref, err);
+                        self.warn(DiagnosticKind::Font, message);
+                    }
+                }
+            }
+            if let Some(ascent) = self.font_descriptor_number(cid_dict, "Ascent")? {
+                encoding.set_ascent(ascent);
+            }
+            return Ok(());
+        }
+
+        let first_char = match font_dict.get("FirstChar") {
+            Some(fc) => self.get_object(fc)?.as_int(),
+            None => None,
+        };
+        // Type3 widths are in glyph space, mapped to text space by /FontMatrix
+        let mut scale = 1.0;
+        if font_dict.get("Subtype").and_then(|s| s.as_name()) == Some("Type3") {
+            if let Some(matrix) = font_dict.get("FontMatrix") {
+                let matrix = self.get_object(matrix)?;
+                let a = matrix.as_array().and_then(|m| m.first()).and_then(|a| a.as_real());
+                scale = a.map_or(1.0, |a| a * 1000.0);
+            }
+        }
+        if let (Some(first_char), Some(widths)) = (first_char, font_dict.get("Widths")) {
+            if let Some(arr) = self.get_object(widths)?.as_array() {
+                let widths: Vec<f64> =
+                    arr.iter().map(|w| w.as_real().unwrap_or(0.0) * scale).collect();
+                encoding.set_widths(first_char.clamp(0, u16::MAX as i64) as u16, &widths);
+            }
+        }
+
+        if let Some(width) = self.font_descriptor_number(font_dict, "MissingWidth")? {
+            encoding.set_missing_width(width);
+        }
+        if let Some(ascent) = self.font_descriptor_number(font_dict, "Ascent")? {
+            encoding.set_ascent(ascent);
+        }
+
+        Ok(())
+    }
+
+    /// Whether a font is bold, italic or fixed-pitch, from the /Flags,
+    /// /FontWeight and /ItalicAngle of its descriptor or else from its
+    /// /BaseFont name
+    fn load_font_style(
+        &mut self,
+        font_dict: &HashMap<String, PdfObject>,
+        encoding: &mut FontEncoding,
+    ) -> Result<()> {
+        // A composite font's descriptor is on its descendant CIDFont
+        let described = self.described_font(font_dict)?;
+
+        let flags = self.font_descriptor_number(&described, "Flags")?.unwrap_or(0.0) as i64;
+        let weight = self.font_descriptor_number(&described, "FontWeight")?;
+        let angle = self.font_descriptor_number(&described, "ItalicAngle")?;
+        let name = font_dict
+            .get("BaseFont")
+            .and_then(|n| n.as_name())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let named = |words: &[&str]| words.iter().any(|w| name.contains(w));
+
+        // Flag bit 7 is Italic and bit 19 ForceBold
+        let bold = flags & (1 << 18) != 0
+            || weight.is_some_and(|w| w >= 600.0)
+            || named(&["bold", "black", "heavy"]);
+        let italic = flags & (1 << 6) != 0
+            || angle.is_some_and(|a| a != 0.0)
+            || named(&["italic", "oblique"]);
+        encoding.set_style(bold, italic);
+        // Flag bit 1 is FixedPitch
+        let monospace = flags & 1 != 0
+            || named(&["courier", "mono", "consolas"])
+            || encoding.has_uniform_widths();
+        encoding.set_monospace(monospace);
+        Ok(())
+    }
+
+    /// Read a numeric entry from a font's /FontDescriptor
+    fn font_descriptor_number(
+        &mut self,
+        font_dict: &HashMap<String, PdfObject>,
+        key: &str,
+    ) -> Result<Option<f64>> {
+        let Some(descriptor) = font_dict.get("FontDescriptor") else {
+            return Ok(None);
+        };
+        let descriptor = self.get_object(descriptor)?;
+        match descriptor.as_dict().and_then(|d| d.get(key)) {
+            Some(value) => Ok(self.get_object(value)?.as_real()),
+            None => Ok(None),
+        }
+    }
+
+    /// Apply a CID font /W array: `c [w1 w2 ...]` and `c_first c_last w` forms
+    fn apply_cid_widths(&mut self, items: &[PdfObject], encoding: &mut FontEncoding) -> Result<()> {
+        let mut i = 0;
+        while i < items.len() {
+            let Some(first) = items[i].as_int() else {
+                i += 1;
+                continue;
+            };
+            let first = first.clamp(0, u16::MAX as i64) as u16;
+
+            match items.get(i + 1).map(|next| self.get_object(next)).transpose()? {
+                Some(PdfObject::Array(widths)) => {
+                    let widths: Vec<f64> =
+                        widths.iter().map(|w| w.as_real().unwrap_or(0.0)).collect();
+                    encoding.set_widths(first, &widths);
+                    i += 2;
+                }
+                Some(last) => {
+                    let (Some(last), Some(width)) =
+                        (last.as_int(), items.get(i + 2).and_then(|w| w.as_real()))
+                    else {
+                        break;
+                    };
+                    let last = last.clamp(0, u16::MAX as i64) as u16;
+                    for code in first..=last {
+                        encoding.set_width(code, width);
+                    }
+                    i += 3;
+                }
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Extract all text from a page as a single string
+    pub fn extract_page_text_string(&mut self, page_index: usize) -> Result<String> {
+        self.extract_page_text_string_with(page_index, &LineJoinOptions::default())
+    }
+
+    /// Extract all text from a page as a single string, joining spans and
+    /// lines with the given separators
+    pub fn extract_page_text_string_with(
+        &mut self,
+        page_index: usize,
+        options: &LineJoinOptions,
+    ) -> Result<String> {
+        let spans = self.extract_page_text(page_index)?;
+        let lines: Vec<String> = text_lines(spans, options)
+            .into_iter()
+            .map(|(_, line)| line)
+            .collect();
+        Ok(lines.join(&options.inter_line_sep))
+    }
+
+    /// Extract the text of all pages, leaving out running headers and
+    /// footers such as page numbers. Pages are separated by a blank line.
+    pub fn extract_text_dedup_headers(&mut self) -> Result<String> {
+        self.extract_text_dedup_headers_with(false)
+    }
+
+    /// Like [`Document::extract_text_dedup_headers`]; with `keep_first`,
+    /// each header or footer is kept where it first appears.
+    ///
+    /// A line near the top or bottom of a page is a header or footer when
+    /// it recurs at the same height on most pages. Digits are ignored when
+    /// comparing, so "Page 1" and "Page 2" match.
+    pub fn extract_text_dedup_headers_with(&mut self, keep_first: bool) -> Result<String> {
+        let options = LineJoinOptions::default();
+        let mut pages = Vec::new();
+        for page in 0..self.page_count_exact()? {
+            pages.push(text_lines(self.extract_page_text(page)?, &options));
+        }
+
+        let repeated = repeated_edge_lines(&pages);
+        let mut seen = HashSet::new();
+        let mut texts = Vec::new();
+        for (page, lines) in pages.iter().enumerate() {
+            let kept: Vec<&str> = lines
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| match repeated.get(&(page, *i)) {
+                    Some(template) => keep_first && seen.insert(template.as_str()),
+                    None => true,
+                })
+                .map(|(_, (_, line))| line.as_str())
+                .collect();
+            texts.push(kept.join("\n"));
+        }
+        Ok(texts.join("\n\n"))
+    }
+
+    /// A short preview of a page (0-indexed), e.g. for search results: its
+    /// first heading and first paragraph, or all of its text when it has
+    /// neither, cut at a word boundary to at most `max_chars` characters
+    /// with `…` marking the cut
+    pub fn page_snippet(&mut self, page_index: usize, max_chars: usize) -> Result<String> {
+        let elements = classify_spans(self.extract_page_text(page_index)?);
+
+        let mut parts = Vec::new();
+        let (mut heading, mut paragraph) = (false, false);
+        for element in &elements {
+            match element {
+                PageElement::Heading { text, .. } if !heading => {
+                    heading = true;
+                    parts.push(text.clone());
+                }
+                PageElement::Paragraph { text, .. } if !paragraph => {
+                    paragraph = true;
+                    parts.push(text.clone());
+                }
+                _ => {}
+            }
+        }
+        if parts.is_empty() {
+            parts = elements.iter().map(element_text).collect();
+        }
+
+        let text = parts.join(" \u{2014} ").split_whitespace().collect::<Vec<_>>().join(" ");
+        Ok(truncate_at_word(&text, max_chars))
+    }
+}
+
+/// All the text of a page element, in reading order
+fn element_text(element: &PageElement) -> String {
+    match element {
+        PageElement::Heading { text, .. }
+        | PageElement::Paragraph { text, .. }
+        | PageElement::CodeBlock { text, .. } => text.clone(),
+        PageElement::List { items, .. } => items.join(" "),
+        PageElement::Table { table, .. } => {
+            table.rows.iter().map(|row| row.join(" ")).collect::<Vec<_>>().join(" ")
+        }
+    }
+}
+
+/// Shorten `text` to at most `max_chars` characters, ending at a word
+/// boundary followed by `…`. A first word longer than the limit is cut.
+fn truncate_at_word(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    if max_chars == 0 {
+        return String::new();
+    }
+
+    // Leave room for the ellipsis
+    let cut: String = text.chars().take(max_chars - 1).collect();
+    let at_boundary = text.chars().nth(max_chars - 1).is_some_and(char::is_whitespace);
+    let kept = match cut.rfind(' ') {
+        Some(end) if !at_boundary => &cut[..end],
+        _ => cut.as_str(),
+    };
+    let kept = kept.trim_end_matches(|c: char| c.is_whitespace() || ",;:".contains(c));
+    format!("{}\u{2026}", kept)
+}
+
+/// Major and minor number of a version such as `1.7`
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Sort spans top to bottom, left to right, and join them into lines of
+/// text, each with the baseline of its first span
+fn text_lines(mut spans: Vec<TextSpan>, options: &LineJoinOptions) -> Vec<(f64, String)> {
+    spans.sort_by(|a, b| {
+        b.baseline().partial_cmp(&a.baseline())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal))
+    });
+
+    let mut lines: Vec<(f64, String)> = Vec::new();
+    for span in spans {
+        match lines.last_mut() {
+            Some((y, line)) if (span.baseline() - *y).abs() <= options.y_tolerance => {
+                line.push_str(&options.intra_line_sep);
+                line.push_str(&span.text);
+            }
+            _ => lines.push((span.baseline(), span.text)),
+        }
+    }
+    lines
+}
+
+/// Find running headers and footers: lines among the first or last few of
+/// a page whose digit-insensitive text recurs at the same height on more
+/// than half the pages. Maps `(page, line index)` to the line's template.
+fn repeated_edge_lines(pages: &[Vec<(f64, String)>]) -> HashMap<(usize, usize), String> {
+    const EDGE_LINES: usize = 3;
+    const Y_TOLERANCE: f64 = 2.0;
+
+    let mut repeated = HashMap::new();
+    if pages.len() < 2 {
+        return repeated;
+    }
+
+    let mut candidates: HashMap<String, Vec<(usize, usize, f64)>> = HashMap::new();
+    for (page, lines) in pages.iter().enumerate() {
+        for (i, (y, line)) in lines.iter().enumerate() {
+            if i >= EDGE_LINES && i + EDGE_LINES < lines.len() {
+                continue;
+            }
+            let template = line_template(line);
+            if !template.is_empty() {
+                candidates.entry(template).or_default().push((page, i, *y));
+            }
+        }
+    }
+
+    for (template, occurrences) in candidates {
+        // The occurrences that agree with the most common height
+        let Some(aligned) = occurrences
+            .iter()
+            .map(|&(_, _, y)| {
+                occurrences
+                    .iter()
+                    .filter(|o| (o.2 - y).abs() <= Y_TOLERANCE)
                     .collect::<Vec<_>>()
-                    .join(" ")
             })
-            .collect::<Vec<_>>()
-            .join("\n");
+            .max_by_key(|aligned| aligned.len())
+        else {
+            continue;
+        };
+        let page_hits: HashSet<usize> = aligned.iter().map(|o| o.0).collect();
+        if page_hits.len() * 2 > pages.len() {
+            for &&(page, i, _) in &aligned {
+                repeated.insert((page, i), template.clone());
+            }
+        }
+    }
+    repeated
+}
+
+/// A line's text with whitespace collapsed and each run of digits replaced
+/// by `#`
+fn line_template(line: &str) -> String {
+    let mut template = String::new();
+    for word in line.split_whitespace() {
+        if !template.is_empty() {
+            template.push(' ');
+        }
+        let mut in_digits = false;
+        for c in word.chars() {
+            if c.is_ascii_digit() {
+                if !in_digits {
+                    template.push('#');
+                }
+                in_digits = true;
+            } else {
+                template.push(c);
+                in_digits = false;
+            }
+        }
+    }
+    template
+}
+
+/// The two strings of a trailer /ID array, literal or hex. A lone string is
+/// taken as both.
+fn read_file_id(trailer: &HashMap<String, PdfObject>) -> Option<(Vec<u8>, Vec<u8>)> {
+    let ids = trailer.get("ID")?.as_array()?;
+    let first = ids.first()?.as_string()?;
+    let second = match ids.get(1) {
+        Some(second) => second.as_string()?,
+        None => first,
+    };
+    Some((first.to_vec(), second.to_vec()))
+}
+
+/// A page's decoded content and fonts, ready to parse without the document
+struct PageJob {
+    content: Vec<u8>,
+    font_encodings: HashMap<String, FontEncoding>,
+    color_spaces: HashMap<String, usize>,
+    forms: FormXObjects,
+    transform: [f64; 6],
+}
+
+impl PageJob {
+    fn parser(&self, options: &ExtractOptions) -> ContentParser<'_> {
+        ContentParser::with_fonts(&self.content, self.font_encodings.clone())
+            .with_forms(&self.forms)
+            .with_color_spaces(self.color_spaces.clone())
+            .with_transform(self.transform)
+            .with_config(options.merge_config)
+            .include_invisible_text(options.include_invisible)
+            .normalize_ligatures(options.normalize_ligatures)
+            .replace_unmapped(options.replace_unmapped)
+            .reorder_rtl(options.reorder_rtl)
+    }
+}
+
+/// Search the words of one page line by line
+fn search_words(
+    page: usize,
+    mut words: Vec<Word>,
+    query: &str,
+    case_insensitive: bool,
+) -> Vec<SearchHit> {
+    words.sort_by(|a, b| {
+        b.y.partial_cmp(&a.y)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    let fold = |text: &str| {
+        if case_insensitive { text.to_lowercase() } else { text.to_string() }
+    };
+    let query = fold(query);
+
+    let mut hits = Vec::new();
+    let mut rest = words.as_slice();
+    while let Some(first) = rest.first() {
+        let len = rest.iter().take_while(|w| (w.y - first.y).abs() <= 3.0).count();
+        let (line, next) = rest.split_at(len);
+        rest = next;
+
+        // Line text, plus for each of its characters the folded offset where
+        // it starts and the word it belongs to (None for joining spaces)
+        let mut folded = String::new();
+        let mut chars = Vec::new();
+        for (w, word) in line.iter().enumerate() {
+            if w > 0 {
+                chars.push((folded.len(), ' ', None));
+                folded.push(' ');
+            }
+            for (c, ch) in word.text.chars().enumerate() {
+                chars.push((folded.len(), ch, Some((w, c))));
+                folded.push_str(&fold(ch.encode_utf8(&mut [0; 4])));
+            }
+        }
+
+        for (start, matched) in folded.match_indices(&query) {
+            let end = start + matched.len();
+            let text: String = chars
+                .iter()
+                .filter(|(offset, _, _)| (start..end).contains(offset))
+                .map(|(_, ch, _)| ch)
+                .collect();
+            let Some((w, c)) = chars.iter().filter(|(o, _, _)| *o >= start).find_map(|c| c.2)
+            else {
+                continue;
+            };
+            let word = &line[w];
+            let char_width = word.width / word.text.chars().count().max(1) as f64;
+            hits.push(SearchHit {
+                page,
+                text,
+                x: word.x + c as f64 * char_width,
+                y: word.y,
+            });
+        }
+    }
+    hits
+}
+
+/// Iterator over the text spans of each page, created by [`Document::pages`]
+pub struct Pages<'d, 'a> {
+    doc: &'d mut Document<'a>,
+    index: usize,
+    /// Page count, or the error from counting pages (yielded once)
+    remaining: Result<usize>,
+    options: ExtractOptions,
+}
+
+impl Iterator for Pages<'_, '_> {
+    type Item = Result<Vec<TextSpan>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &self.remaining {
+            Ok(count) if self.index < *count => {
+                let spans = self.doc.extract_page_text_with(self.index, &self.options);
+                self.index += 1;
+                Some(spans)
+            }
+            Ok(_) => None,
+            Err(_) => {
+                let err = std::mem::replace(&mut self.remaining, Ok(0));
+                err.err().map(Err)
+            }
+        }
+    }
+}
+
+/// Iterator over all in-use objects, created by [`Document::iter_objects`]
+pub struct Objects<'d, 'a> {
+    doc: &'d mut Document<'a>,
+    refs: std::vec::IntoIter<ObjRef>,
+}
+
+impl Iterator for Objects<'_, '_> {
+    type Item = (ObjRef, Result<PdfObject>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let obj_ref = self.refs.next()?;
+        Some((obj_ref, self.doc.resolve(obj_ref).cloned()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.refs.size_hint()
+    }
+}
+
+/// Page attributes that leaf pages inherit from ancestor Pages nodes
+const INHERITABLE_PAGE_KEYS: &[&str] = &["Resources", "MediaBox", "CropBox", "Rotate"];
+
+/// Levels of /Pages nodes followed before the page tree is considered broken
+const MAX_PAGE_TREE_DEPTH: usize = 256;
+
+/// Levels of nested outline items followed before the outline is considered broken
+const MAX_OUTLINE_DEPTH: usize = 256;
+
+/// Whether a content stream ending in `b` can be followed directly by the
+/// next one. `/` and `%` are left out: a name or comment would run on.
+fn is_content_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b' ' | b'\t' | b'\n' | b'\r' | 0x0C | 0x00 | b'(' | b')' | b'<' | b'>' | b'[' | b']'
+            | b'{' | b'}'
+    )
+}
+
+/// Leaf page dictionary with missing inheritable attributes filled in
+fn with_inherited(
+    dict: &HashMap<String, PdfObject>,
+    inherited: &HashMap<String, PdfObject>,
+) -> PdfObject {
+    let mut merged = dict.clone();
+    for (key, value) in inherited {
+        merged.entry(key.clone()).or_insert_with(|| value.clone());
+    }
+    PdfObject::Dict(merged)
+}
+
+/// Convert a PDF date (`D:YYYYMMDDHHmmSSOHH'mm'`) to RFC 3339.
+/// Missing fields take their spec defaults; an absent offset is reported as
+/// `-00:00` (unknown local offset). Unparseable input is returned unchanged.
+fn normalize_pdf_date(date: &str) -> String {
+    parse_pdf_date(date).unwrap_or_else(|| date.to_string())
+}
+
+fn parse_pdf_date(date: &str) -> Option<String> {
+    let s = date.trim();
+    let s = s.strip_prefix("D:").unwrap_or(s);
+    let digits_end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (digits, rest) = s.split_at(digits_end);
+
+    if digits.len() < 4 || digits.len() > 14 || digits.len() % 2 != 0 {
+        return None;
+    }
+    let part = |start: usize, default: u32| -> u32 {
+        digits
+            .get(start..start + 2)
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(default)
+    };
+    let year: u32 = digits[..4].parse().ok()?;
+    let (month, day) = (part(4, 1), part(6, 1));
+    let (hour, minute, second) = (part(8, 0), part(10, 0), part(12, 0));
+
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let offset = match rest.chars().next() {
+        None => "-00:00".to_string(),
+        Some('Z') => "Z".to_string(),
+        Some(sign @ ('+' | '-')) => {
+            let tz: String = rest[1..].chars().filter(|c| c.is_ascii_digit()).collect();
+            let tz_hour: u32 = tz.get(..2)?.parse().ok()?;
+            let tz_minute: u32 = tz.get(2..4).and_then(|m| m.parse().ok()).unwrap_or(0);
+            format!("{}{:02}:{:02}", sign, tz_hour, tz_minute)
+        }
+        Some(_) => return None,
+    };
+
+    Some(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}",
+        year, month, day, hour, minute, second, offset
+    ))
+}
+
+/// Decode an image stream, stopping before an image codec filter so JPEG
+/// data comes out verbatim
+fn decode_image_data(
+    dict: &HashMap<String, PdfObject>,
+    data: &[u8],
+) -> Result<(Vec<u8>, ImageFormat)> {
+    let filters: Vec<&str> = match dict.get("Filter") {
+        Some(PdfObject::Name(name)) => vec![name.as_str()],
+        Some(PdfObject::Array(items)) => items.iter().filter_map(|f| f.as_name()).collect(),
+        _ => Vec::new(),
+    };
+
+    match filters.split_last() {
+        Some((&codec, outer)) if IMAGE_CODECS.contains(&codec) => {
+            // Undo only the general-purpose filters wrapped around the codec
+            let mut outer_dict = dict.clone();
+            let outer_filters = outer.iter().map(|f| PdfObject::Name(f.to_string())).collect();
+            outer_dict.insert("Filter".to_string(), PdfObject::Array(outer_filters));
+            if let Some(PdfObject::Array(parms)) = outer_dict.get_mut("DecodeParms") {
+                parms.truncate(outer.len());
+            } else {
+                outer_dict.remove("DecodeParms");
+            }
+
+            let codec_parms = match dict.get("DecodeParms") {
+                Some(PdfObject::Dict(parms)) if outer.is_empty() => Some(parms),
+                Some(PdfObject::Array(parms)) => parms.get(outer.len()).and_then(|p| p.as_dict()),
+                _ => None,
+            };
+
+            let bytes = decode_stream(&outer_dict, data)?;
+            let format = match codec {
+                "DCTDecode" => ImageFormat::Jpeg,
+                "CCITTFaxDecode" => ImageFormat::Ccitt(CcittParams::from_dict(codec_parms)),
+                other => ImageFormat::Encoded(other.to_string()),
+            };
+            Ok((bytes, format))
+        }
+        _ => match decode_stream(dict, data) {
+            Ok(samples) => Ok((samples, ImageFormat::Raw)),
+            Err(PdfError::UnsupportedFilter(filter)) => {
+                Ok((data.to_vec(), ImageFormat::Encoded(filter)))
+            }
+            Err(err) => Err(err),
+        },
+    }
+}
+
+/// Leading bytes of an object, searched for a `/Type` name without parsing
+struct ObjectPrefix<'a>(&'a [u8]);
+
+impl ObjectPrefix<'_> {
+    /// Whether `name` occurs as a complete name token
+    fn contains_name(&self, name: &[u8]) -> bool {
+        self.0.windows(name.len()).enumerate().any(|(i, w)| {
+            w == name
+                && self
+                    .0
+                    .get(i + name.len())
+                    .is_none_or(|b| !b.is_ascii_alphanumeric())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_startxref() {
+        let data = b"%PDF-1.4\n%%EOF\nstartxref\n1234\n%%EOF";
+        let pos = Document::find_startxref(data).unwrap();
+        assert!(data[pos..].starts_with(b"startxref"));
+    }
+
+    /// Assemble a PDF with a classic xref table; object N is `objects[N - 1]`
+    /// and object 1 is the catalog
+    fn build_pdf(objects: &[&str]) -> Vec<u8> {
+        build_pdf_with_trailer(objects, "")
+    }
+
+    /// Like `build_pdf`, with extra entries appended to the trailer dictionary
+    fn build_pdf_with_trailer(objects: &[&str], extra: &str) -> Vec<u8> {
+        let objects: Vec<Vec<u8>> = objects.iter().map(|o| o.as_bytes().to_vec()).collect();
+        build_pdf_bytes(&objects, extra)
+    }
+
+    /// Like `build_pdf_with_trailer`, for object bodies with binary data
+    fn build_pdf_bytes(objects: &[Vec<u8>], extra: &str) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n", i + 1).bytes());
+            pdf.extend_from_slice(body);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref_offset = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+        for offset in offsets {
+            pdf.extend(format!("{:010} 00000 n \n", offset).bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R {}>>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                extra,
+                xref_offset
+            )
+            .bytes(),
+        );
+        pdf
+    }
+
+    /// Body of an uncompressed stream object
+    fn stream_obj(extra: &str, content: &str) -> String {
+        format!(
+            "<< /Length {} {} >>\nstream\n{}\nendstream",
+            content.len(),
+            extra,
+            content
+        )
+    }
+
+    /// Body of a stream object holding binary data
+    fn binary_stream_obj(extra: &str, content: &[u8]) -> Vec<u8> {
+        let mut body = format!("<< /Length {} {} >>\nstream\n", content.len(), extra).into_bytes();
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\nendstream");
+        body
+    }
+
+    /// A one-page document showing `content` with font /F1 set to `font`
+    fn single_page_pdf(font: &str, content: &str) -> Vec<u8> {
+        single_page_pdf_with(font, content, "")
+    }
+
+    /// Like `single_page_pdf`, with extra entries in the page dictionary
+    fn single_page_pdf_with(font: &str, content: &str, page_extra: &str) -> Vec<u8> {
+        let page = format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> {}>>",
+            page_extra
+        );
+        build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            &page,
+            &stream_obj("", content),
+            font,
+        ])
+    }
+
+    #[test]
+    fn test_whole_document_helpers_skip_failing_page() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 6 0 R >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 9 0 R >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 7 0 R >>",
+            &stream_obj("", "BT /F1 12 Tf 100 700 Td (First page) Tj ET"),
+            &stream_obj("", "BT /F1 12 Tf 100 700 Td (Last page) Tj ET"),
+        ]);
+        assert!(Document::parse(&pdf).unwrap().extract_page_text(1).is_err());
+
+        // The failed page is marked where it would have been
+        let text = crate::pdf_to_text(&pdf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "First page");
+        assert!(lines[2].starts_with("[page 2 could not be extracted: "), "{:?}", text);
+        assert_eq!(lines[4], "Last page");
+        let csv = crate::pdf_to_csv(&pdf).unwrap();
+        assert!(csv.contains("\n# [page 2 could not be extracted") && csv.contains("Last page"));
+
+        let options = ExtractOptions::new().fail_fast(true);
+        assert!(crate::pdf_to_text_with(&pdf, &options).is_err());
+        assert!(crate::pdf_to_markdown_with(&pdf, &options).is_err());
+        assert!(crate::pdf_to_csv_with(&pdf, &options).is_err());
+    }
+
+    #[test]
+    fn test_page_snippet() {
+        let content = "BT /F1 24 Tf 72 720 Td (Annual Report) Tj ET \
+                       BT /F1 12 Tf 72 680 Td (Revenue grew in every region, led by exports.) Tj \
+                       0 -14 Td (Costs were flat.) Tj ET \
+                       BT /F1 12 Tf 72 600 Td (A second paragraph is left out.) Tj ET";
+        let pdf = single_page_pdf("<< /Type /Font /Subtype /Type1 >>", content);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        assert_eq!(
+            doc.page_snippet(0, 200).unwrap(),
+            "Annual Report \u{2014} Revenue grew in every region, led by exports. Costs were flat."
+        );
+        assert_eq!(
+            doc.page_snippet(0, 46).unwrap(),
+            "Annual Report \u{2014} Revenue grew in every region\u{2026}"
+        );
+        assert_eq!(doc.page_snippet(0, 5).unwrap(), "Annu\u{2026}");
+    }
+
+    #[test]
+    fn test_truncate_at_word() {
+        assert_eq!(truncate_at_word("short", 10), "short");
+        assert_eq!(truncate_at_word("one two three", 8), "one two\u{2026}");
+        assert_eq!(truncate_at_word("one two three", 9), "one two\u{2026}");
+        assert_eq!(truncate_at_word("one two", 0), "");
+    }
+
+    #[test]
+    fn test_monospace_needs_a_fixed_pitch_font() {
+        let rows = [["Name", "Qty", "Price"], ["Pen", "2", "1.50"], ["Ink", "10", "4.00"]];
+        let mut content = String::from("BT /F1 12 Tf ");
+        for (r, row) in rows.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                let (x, y) = (100 + 120 * c, 700 - 20 * r);
+                content += &format!("1 0 0 1 {} {} Tm ({}) Tj ", x, y, cell);
+            }
+        }
+        content += "ET";
+
+        // Helvetica has no /Widths, so every glyph gets the same estimate
+        let helvetica = "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>";
+        let md = crate::pdf_to_markdown(&single_page_pdf(helvetica, &content)).unwrap();
+        assert!(md.starts_with("| Name"), "{}", md);
+
+        // The resource is named F1; the base font makes it monospace
+        let courier = "<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>";
+        let content = "BT /F1 10 Tf 50 700 Td (let x = 1;) Tj 0 -12 Td (x + 1) Tj ET";
+        let md = crate::pdf_to_markdown(&single_page_pdf(courier, content)).unwrap();
+        assert_eq!(md, "```\nlet x = 1;\nx + 1\n```\n");
+    }
+
+    #[test]
+    fn test_fonts() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 /Resources << /Font << /F1 5 0 R \
+             /F9 << /Subtype /Type1 /BaseFont /Courier >> >> >> >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+            "<< /Type /Page /Parent 2 0 R \
+             /Resources << /Font << /F1 5 0 R /F2 6 0 R /F3 9 0 R >> >> >>",
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+            "<< /Type /Font /Subtype /Type0 /BaseFont /ABCDEF+NotoSans /Encoding /Identity-H \
+             /DescendantFonts [7 0 R] >>",
+            "<< /Type /Font /Subtype /CIDFontType2 /FontDescriptor 8 0 R >>",
+            "<< /Type /FontDescriptor /FontFile2 10 0 R >>",
+            "<< /Type /Font /Subtype /Type3 /Encoding << /Differences [65 /a] >> >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        let fonts: Vec<_> = doc
+            .fonts()
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.base_font, f.subtype, f.embedded, f.encoding))
+            .collect();
+        let font = |name: &str, subtype: &str, embedded: bool, encoding: &str| {
+            (name.to_string(), subtype.to_string(), embedded, encoding.to_string())
+        };
+        assert_eq!(
+            fonts,
+            [
+                font("Helvetica", "Type1", false, "WinAnsiEncoding"),
+                font("Courier", "Type1", false, ""),
+                font("ABCDEF+NotoSans", "Type0", true, "Identity-H"),
+                font("", "Type3", true, "Custom"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents [4 0 R 6 0 R] \
+             /Resources << /Font << /F1 5 0 R /F2 9 0 R >> >> >>",
+            &stream_obj("", "BT /F1 12 Tf 100 700 Td (Hello) Tj ET"),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /ToUnicode 6 0 R >>",
+            &stream_obj("/Filter /JBIG2Decode", "garbage"),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        let spans = doc.extract_page_text(0).unwrap();
+        assert_eq!(spans[0].text, "Hello");
+
+        let diagnostics = doc.take_diagnostics();
+        let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind).collect();
+        assert!(kinds.contains(&DiagnosticKind::Content));
+        assert!(kinds.contains(&DiagnosticKind::Font));
+        assert!(diagnostics.iter().all(|d| d.page == Some(0)));
+        assert!(diagnostics.iter().any(|d| d.message.contains("/F2")));
+        assert!(doc.take_diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let pdf = single_page_pdf("<< /Type /Font /Subtype /Type1 >>", "BT (Ok) Tj ET");
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert!(doc.validate().is_empty());
+
+        let mut pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents [4 0 R 5 0 R] >>",
+            &stream_obj("", "BT (Hi) Tj ET"),
+            "<< /Not /AStream >>",
+        ]);
+        // Point object 5's xref entry at object 4
+        let offset_of = |pdf: &[u8], obj: &str| {
+            let at = pdf.windows(obj.len()).position(|w| w == obj.as_bytes()).unwrap();
+            format!("{:010} 00000 n", at)
+        };
+        let (four, five) = (offset_of(&pdf, "4 0 obj"), offset_of(&pdf, "5 0 obj"));
+        let at = pdf.windows(five.len()).position(|w| w == five.as_bytes()).unwrap();
+        pdf[at..at + four.len()].copy_from_slice(four.as_bytes());
+
+        let mut doc = Document::parse(&pdf).unwrap();
+        let issues = doc.validate();
+        let kinds: Vec<_> = issues.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ValidationIssueKind::XrefOffset,
+                ValidationIssueKind::PageTree,
+                ValidationIssueKind::PageContents,
+            ]
+        );
+        assert_eq!(issues[0].object, Some(ObjRef::new(5, 0)));
+        assert!(issues[1].message.contains("/Count is 2"));
+        assert_eq!(issues[2].object, Some(ObjRef::new(3, 0)));
+    }
+
+    #[test]
+    fn test_owned_document() {
+        fn open(font: &str) -> OwnedDocument {
+            let pdf = single_page_pdf(font, "BT /F1 12 Tf 100 700 Td (Owned) Tj ET");
+            Document::parse_owned(pdf).unwrap()
+        }
+
+        let mut doc = open("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>");
+        assert_eq!(doc.extract_page_text(0).unwrap()[0].text, "Owned");
+
+        let pdf = single_page_pdf("<< /Type /Font /Subtype /Type1 >>", "BT (Read) Tj ET");
+        let mut doc = Document::from_reader(std::io::Cursor::new(pdf)).unwrap();
+        assert_eq!(doc.extract_page_text_string(0).unwrap(), "Read");
+        assert!(matches!(Document::parse_owned(b"junk".to_vec()), Err(PdfError::MissingHeader)));
+    }
+
+    #[test]
+    fn test_extract_options() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding << /BaseEncoding /WinAnsiEncoding /Differences [65 /fi] >> >>",
+            "BT /F1 12 Tf 100 700 Td (Ant) Tj 0 -20 Td 3 Tr (Hidden) Tj ET",
+        );
+        let mut doc = Document::parse(&pdf).unwrap();
+        let texts = |spans: Vec<TextSpan>| spans.into_iter().map(|s| s.text).collect::<Vec<_>>();
+
+        let spans = doc.extract_page_text(0).unwrap();
+        assert_eq!(texts(spans), vec!["\u{FB01}nt"]);
+
+        let options = ExtractOptions::new().include_invisible(true).normalize_ligatures(true);
+        let spans = doc.extract_page_text_with(0, &options).unwrap();
+        assert_eq!(texts(spans), vec!["fint", "Hidden"]);
+    }
+
+    #[test]
+    fn test_type0_cid_keyed_widths() {
+        let cmap = "/CIDInit /ProcSet findresource begin 12 dict begin begincmap \
+            1 begincodespacerange <00> <ff> endcodespacerange \
+            1 begincidrange <41> <43> 100 endcidrange endcmap";
+        let mut objects: Vec<Vec<u8>> = [
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R \
+             /Resources << /Font << /F1 5 0 R >> >> >>",
+            &stream_obj("", "BT /F1 10 Tf 100 700 Td (ABC) Tj ET"),
+            "<< /Type /Font /Subtype /Type0 /BaseFont /Test /Encoding 6 0 R \
+             /DescendantFonts [7 0 R] >>",
+            &stream_obj("", cmap),
+            "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /Test /DW 1000 \
+             /W [100 [200 300] 102 102 500] /CIDToGIDMap 8 0 R >>",
+        ]
+        .iter()
+        .map(|o| o.as_bytes().to_vec())
+        .collect();
+        objects.push(binary_stream_obj("", &[0, 0, 0, 5]));
+        let pdf = build_pdf_bytes(&objects, "");
+        let mut doc = Document::parse(&pdf).unwrap();
+        let spans = doc.extract_page_text(0).unwrap();
+        // One-byte codes map to CIDs 100-102: (200 + 300 + 500) / 1000 * 10
+        assert!((spans[0].width - 10.0).abs() < 1e-9, "width {}", spans[0].width);
+
+        let encoding = &doc.font_cache[&ObjRef::new(5, 0)];
+        assert_eq!(encoding.cid(0x42), 101);
+        assert_eq!(encoding.gid(1), 5);
+
+        // An unreadable map falls back to Identity, keeping the font
+        objects[7] = binary_stream_obj("/Filter /JBIG2Decode", &[0, 0, 0, 5]);
+        let pdf = build_pdf_bytes(&objects, "");
+        let mut doc = Document::parse(&pdf).unwrap();
+        let spans = doc.extract_page_text(0).unwrap();
+        assert!((spans[0].width - 10.0).abs() < 1e-9, "width {}", spans[0].width);
+        assert_eq!(doc.font_cache[&ObjRef::new(5, 0)].gid(1), 1);
+        let diagnostics = doc.take_diagnostics();
+        assert!(diagnostics.iter().any(|d| d.message.contains("CIDToGIDMap")));
+    }
+
+    #[test]
+    fn test_type3_font_differences() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type3 /FontBBox [0 0 100 100] \
+             /FontMatrix [0.01 0 0 0.01 0 0] /CharProcs << >> /Resources << >> \
+             /Encoding << /Type /Encoding /Differences [1 /H /i 3 /exclam /g17] >> \
+             /FirstChar 1 /LastChar 3 /Widths [50 30 20] >>",
+            "BT /F1 10 Tf 100 700 Td (\\001\\002\\003) Tj 0 -20 Td (\\004A) Tj ET",
+        );
+        let mut doc = Document::parse(&pdf).unwrap();
+        let spans = doc.extract_page_text(0).unwrap();
+        assert_eq!(spans[0].text, "Hi!");
+        assert!((spans[0].width - 10.0).abs() < 1e-9);
+        // Codes without a known glyph name don't fall back to Latin-1
+        assert_eq!(spans[1].text, "\u{FFFD}\u{FFFD}");
+    }
+
+    #[test]
+    fn test_encoding_differences() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding << /BaseEncoding /WinAnsiEncoding /Differences [65 /eacute /germandbls] >> >>",
+            "BT /F1 12 Tf 100 700 Td (ABC) Tj ET",
+        );
+        let mut doc = Document::parse(&pdf).unwrap();
+        let spans = doc.extract_page_text(0).unwrap();
+        assert_eq!(spans[0].text, "\u{e9}\u{df}C");
+    }
+
+    /// Append an uncompressed XRef stream object with `/W [1 2 1]` entries
+    fn push_xref_stream(pdf: &mut Vec<u8>, obj_num: u32, entries: &[(u8, usize, u8)], extra: &str) {
+        let mut data = Vec::new();
+        for &(kind, field2, field3) in entries {
+            data.extend([kind, (field2 >> 8) as u8, field2 as u8, field3]);
+        }
+        pdf.extend(
+            format!(
+                "{} 0 obj\n<< /Type /XRef /W [1 2 1] /Length {} {} >>\nstream\n",
+                obj_num,
+                data.len(),
+                extra
+            )
+            .bytes(),
+        );
+        pdf.extend(&data);
+        pdf.extend(b"\nendstream\nendobj\n");
+    }
+
+    #[test]
+    fn test_xref_stream_with_index() {
+        let mut pdf = b"%PDF-1.5\n".to_vec();
+        let off1 = pdf.len();
+        pdf.extend(b"1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n");
+        let off2 = pdf.len();
+        pdf.extend(b"2 0 obj\n<< /Type /Pages /Kids [] /Count 0 >>\nendobj\n");
+        let off5 = pdf.len();
+        pdf.extend(b"5 0 obj\n(five)\nendobj\n");
+
+        // Two subsections: objects 1-2 and object 5
+        let xref_offset = pdf.len();
+        push_xref_stream(
+            &mut pdf,
+            6,
+            &[(1, off1, 0), (1, off2, 0), (1, off5, 0)],
+            "/Size 7 /Index [1 2 5 1] /Root 1 0 R",
+        );
+        pdf.extend(format!("startxref\n{}\n%%EOF\n", xref_offset).bytes());
+
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.xref_kind(), XrefKind::Stream);
+        assert_eq!(doc.object_count(), 3);
+        assert!(doc.trailer().contains_key("Root"));
+        assert_eq!(doc.page_count().unwrap(), 0);
+        assert_eq!(
+            doc.resolve(ObjRef::new(5, 0)).unwrap(),
+            &PdfObject::String(b"five".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_incremental_update_newest_object_wins() {
+        let mut pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [] /Count 0 >>",
+            "(original)",
+        ]);
+        let first_xref = pdf.windows(5).position(|w| w == b"xref\n").unwrap();
+
+        // The update rewrites object 3 and adds object 4
+        let off3 = pdf.len();
+        pdf.extend(b"3 0 obj\n(edited)\nendobj\n");
+        let off4 = pdf.len();
+        pdf.extend(b"4 0 obj\n42\nendobj\n");
+        let second_xref = pdf.len();
+        pdf.extend(
+            format!(
+                "xref\n3 2\n{:010} 00000 n \n{:010} 00000 n \n\
+                 trailer\n<< /Size 5 /Root 1 0 R /Prev {} >>\nstartxref\n{}\n%%EOF\n",
+                off3, off4, first_xref, second_xref
+            )
+            .bytes(),
+        );
+
+        let mut doc = Document::parse_strict(&pdf).unwrap();
+        assert_eq!(doc.get(3, 0).unwrap(), PdfObject::String(b"edited".to_vec()));
+        assert_eq!(doc.get(4, 0).unwrap(), PdfObject::Int(42));
+        assert_eq!(doc.page_count().unwrap(), 0);
+
+        // A later update deletes object 3: its free entry shadows the older
+        // in-use ones
+        let third_xref = pdf.len();
+        pdf.extend(
+            format!(
+                "xref\n3 1\n0000000000 00001 f \n\
+                 trailer\n<< /Size 5 /Root 1 0 R /Prev {} >>\nstartxref\n{}\n%%EOF\n",
+                second_xref, third_xref
+            )
+            .bytes(),
+        );
+        let mut doc = Document::parse_strict(&pdf).unwrap();
+        assert_eq!(doc.resolve(ObjRef::new(3, 0)).unwrap(), &PdfObject::Null);
+        assert!(matches!(doc.get(3, 0), Err(PdfError::ObjectNotFound(3, 0))));
+        assert_eq!(doc.object_count(), 3);
+        assert!(doc.iter_objects().all(|(obj_ref, _)| obj_ref.obj_num != 3));
+    }
+
+    #[test]
+    fn test_xref_stream_free_entry_shadows_older_section() {
+        let mut pdf = b"%PDF-1.5\n".to_vec();
+        let mut offsets = Vec::new();
+        let bodies =
+            ["<< /Type /Catalog /Pages 2 0 R >>", "<< /Type /Pages /Kids [] /Count 0 >>", "(old)"];
+        for body in bodies {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n{}\nendobj\n", offsets.len(), body).bytes());
+        }
+        let first_xref = pdf.len();
+        let entries: Vec<_> = offsets.iter().map(|&offset| (1, offset, 0)).collect();
+        push_xref_stream(&mut pdf, 4, &entries, "/Size 4 /Index [1 3] /Root 1 0 R");
+
+        // The update marks object 3 free
+        let second_xref = pdf.len();
+        push_xref_stream(
+            &mut pdf,
+            5,
+            &[(0, 0, 1)],
+            &format!("/Size 6 /Index [3 1] /Root 1 0 R /Prev {}", first_xref),
+        );
+        pdf.extend(format!("startxref\n{}\n%%EOF\n", second_xref).bytes());
+
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.resolve(ObjRef::new(3, 0)).unwrap(), &PdfObject::Null);
+        assert_eq!(doc.object_count(), 2);
+        assert!(doc.catalog().unwrap().as_dict().is_some());
+    }
+
+    #[test]
+    fn test_xref_stream_prev_chain() {
+        let mut pdf = b"%PDF-1.5\n".to_vec();
+        let off1 = pdf.len();
+        pdf.extend(b"1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n");
+        let off2 = pdf.len();
+        pdf.extend(b"2 0 obj\n<< /Type /Pages /Kids [] /Count 0 >>\nendobj\n");
+        let first_xref = pdf.len();
+        push_xref_stream(
+            &mut pdf,
+            3,
+            &[(1, off1, 0), (1, off2, 0)],
+            "/Size 3 /Index [1 2] /Root 1 0 R",
+        );
+
+        // Incremental update adds object 4 and points back at the first section
+        let off4 = pdf.len();
+        pdf.extend(b"4 0 obj\n42\nendobj\n");
+        let second_xref = pdf.len();
+        push_xref_stream(
+            &mut pdf,
+            5,
+            &[(1, off4, 0)],
+            &format!("/Size 5 /Index [4 1] /Root 1 0 R /Prev {}", first_xref),
+        );
+        pdf.extend(format!("startxref\n{}\n%%EOF\n", second_xref).bytes());
+
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.object_count(), 3);
+        assert_eq!(doc.resolve(ObjRef::new(4, 0)).unwrap(), &PdfObject::Int(42));
+        assert!(doc.catalog().unwrap().as_dict().is_some());
+    }
+
+    #[test]
+    fn test_hybrid_reference_xref_stm() {
+        let mut pdf = b"%PDF-1.5\n".to_vec();
+        let off1 = pdf.len();
+        pdf.extend(b"1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n");
+        let off2 = pdf.len();
+        pdf.extend(b"2 0 obj\n<< /Type /Pages /Kids [] /Count 0 >>\nendobj\n");
+        let off4 = pdf.len();
+        pdf.extend(b"4 0 obj\n42\nendobj\n");
+
+        // The stream also lists object 1 at a bogus offset; the table wins
+        let stm_offset = pdf.len();
+        push_xref_stream(&mut pdf, 5, &[(1, 9999, 0), (1, off4, 0)], "/Index [1 1 4 1]");
+
+        let xref_offset = pdf.len();
+        // Object 4, in the stream, is marked free for older readers
+        pdf.extend(b"xref\n0 3\n0000000000 65535 f \n");
+        for offset in [off1, off2] {
+            pdf.extend(format!("{:010} 00000 n \n", offset).bytes());
+        }
+        pdf.extend(b"4 1\n0000000000 00001 f \n");
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size 6 /Root 1 0 R /XRefStm {} >>\nstartxref\n{}\n%%EOF\n",
+                stm_offset, xref_offset
+            )
+            .bytes(),
+        );
+
+        let mut doc = Document::parse_strict(&pdf).unwrap();
+        assert_eq!(doc.xref_kind(), XrefKind::Hybrid);
+        assert_eq!(doc.resolve(ObjRef::new(4, 0)).unwrap(), &PdfObject::Int(42));
+        assert!(doc.catalog().unwrap().as_dict().is_some());
+        assert_eq!(doc.page_count().unwrap(), 0);
+    }
+
+    /// Build a PDF whose catalog and page tree live in an object stream
+    fn pdf_with_object_stream(wrong_index: bool) -> Vec<u8> {
+        let members = ["<< /Type /Catalog /Pages 2 0 R >>", "<< /Type /Pages /Kids [] /Count 0 >>"];
+        let mut header = String::new();
+        let mut body = String::new();
+        for (i, member) in members.iter().enumerate() {
+            header.push_str(&format!("{} {} ", i + 1, body.len()));
+            body.push_str(member);
+            body.push('\n');
+        }
+        let stream_data = format!("{}{}", header, body);
+
+        let mut pdf = b"%PDF-1.5\n".to_vec();
+        let off3 = pdf.len();
+        pdf.extend(
+            format!(
+                "3 0 obj\n<< /Type /ObjStm /N 2 /First {} /Length {} >>\nstream\n{}\nendstream\nendobj\n",
+                header.len(),
+                stream_data.len(),
+                stream_data
+            )
+            .bytes(),
+        );
+        let xref_offset = pdf.len();
+        let (idx1, idx2) = if wrong_index { (1, 0) } else { (0, 1) };
+        push_xref_stream(
+            &mut pdf,
+            4,
+            &[(2, 3, idx1), (2, 3, idx2), (1, off3, 0)],
+            "/Size 4 /Index [1 3] /Root 1 0 R",
+        );
+        pdf.extend(format!("startxref\n{}\n%%EOF\n", xref_offset).bytes());
+        pdf
+    }
+
+    #[test]
+    fn test_object_stream_resolution() {
+        let pdf = pdf_with_object_stream(false);
+        let mut doc = Document::parse(&pdf).unwrap();
 
-        Ok(text)
+        let catalog = doc.catalog().unwrap();
+        assert_eq!(
+            catalog.as_dict().unwrap().get("Type"),
+            Some(&PdfObject::Name("Catalog".into()))
+        );
+        // Sibling member was cached while resolving the catalog
+        assert!(doc.cache.contains_key(&ObjRef::new(2, 0)));
+        assert_eq!(doc.page_count().unwrap(), 0);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_object_stream_inside_itself() {
+        // Object 1 claims to live in object stream 1
+        let mut pdf = b"%PDF-1.5\n".to_vec();
+        let xref_offset = pdf.len();
+        let entries = [(2, 1, 0), (1, xref_offset, 0)];
+        push_xref_stream(&mut pdf, 2, &entries, "/Size 3 /Index [1 2] /Root 1 0 R");
+        pdf.extend(format!("startxref\n{}\n%%EOF\n", xref_offset).bytes());
+        let result = Document::parse_strict(&pdf).and_then(|mut doc| doc.catalog());
+        assert!(matches!(result, Err(PdfError::InvalidStructure(_))), "{:?}", result);
+        assert!(Document::parse(&pdf).and_then(|mut doc| doc.catalog()).is_err());
+    }
 
     #[test]
-    fn test_find_startxref() {
-        let data = b"%PDF-1.4\n%%EOF\nstartxref\n1234\n%%EOF";
-        let pos = Document::find_startxref(data).unwrap();
-        assert!(data[pos..].starts_with(b"startxref"));
+    fn test_object_stream_wrong_index_falls_back_to_header() {
+        let pdf = pdf_with_object_stream(true);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let pages = doc.resolve(ObjRef::new(2, 0)).unwrap().clone();
+        assert_eq!(
+            pages.as_dict().unwrap().get("Type"),
+            Some(&PdfObject::Name("Pages".into()))
+        );
+    }
+
+    #[test]
+    fn test_xref_stream_rejects_oversized_width() {
+        let mut pdf = b"%PDF-1.5\n".to_vec();
+        let xref_offset = pdf.len();
+        pdf.extend(b"1 0 obj\n<< /Type /XRef /W [1 9 1] /Size 1 /Length 0 >>\nstream\n\nendstream\nendobj\n");
+        pdf.extend(format!("startxref\n{}\n%%EOF\n", xref_offset).bytes());
+
+        assert!(Document::parse(&pdf).is_err());
+    }
+
+    #[test]
+    fn test_font_widths() {
+        let font = "<< /Type /Font /Subtype /TrueType /BaseFont /Arial /FirstChar 65 \
+                    /Widths [722 667] /FontDescriptor << /MissingWidth 250 /Ascent 905 >> >>";
+        let pdf = single_page_pdf(font, "BT /F1 12 Tf 100 700 Td (AB) Tj ET");
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let encoding = doc.load_single_font_encoding(&PdfObject::Ref(ObjRef::new(5, 0))).unwrap();
+        assert_eq!(encoding.glyph_width(65), Some(722.0));
+        assert_eq!(encoding.glyph_width(66), Some(667.0));
+        assert_eq!(encoding.glyph_width(90), Some(250.0));
+        assert_eq!(encoding.ascent(), Some(905.0));
+    }
+
+    #[test]
+    fn test_font_style() {
+        let style = |font: &str| {
+            let pdf = single_page_pdf(font, "BT /F1 12 Tf 100 700 Td (Hi) Tj ET");
+            let mut doc = Document::parse(&pdf).unwrap();
+            let span = doc.extract_page_text(0).unwrap().remove(0);
+            (span.bold, span.italic)
+        };
+
+        assert_eq!(style("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>"), (false, false));
+        assert_eq!(
+            style("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-BoldOblique >>"),
+            (true, true)
+        );
+        // Subset fonts with uninformative names rely on the descriptor
+        assert_eq!(
+            style(
+                "<< /Type /Font /Subtype /TrueType /BaseFont /ABCDEF+F0 \
+                 /FontDescriptor << /Flags 96 /FontWeight 700 >> >>"
+            ),
+            (true, true)
+        );
+        assert_eq!(
+            style(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /F0 /Encoding /Identity-H \
+                 /DescendantFonts [<< /Subtype /CIDFontType2 \
+                 /FontDescriptor << /Flags 4 /ItalicAngle -12 >> >>] >>"
+            ),
+            (false, true)
+        );
+    }
+
+    #[test]
+    fn test_cid_font_widths() {
+        let font = "<< /Type /Font /Subtype /Type0 /BaseFont /Foo /Encoding /Identity-H \
+                    /DescendantFonts [<< /Type /Font /Subtype /CIDFontType2 /DW 500 \
+                    /W [1 [600 700] 10 12 300] >>] >>";
+        let pdf = single_page_pdf(font, "BT /F1 12 Tf 100 700 Td <0001> Tj ET");
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let encoding = doc.load_single_font_encoding(&PdfObject::Ref(ObjRef::new(5, 0))).unwrap();
+        assert!(encoding.is_multibyte());
+        assert_eq!(encoding.glyph_width(1), Some(600.0));
+        assert_eq!(encoding.glyph_width(2), Some(700.0));
+        assert_eq!(encoding.glyph_width(11), Some(300.0));
+        assert_eq!(encoding.glyph_width(99), Some(500.0));
+    }
+
+    #[test]
+    fn test_replace_unmapped_cids() {
+        let font = "<< /Type /Font /Subtype /Type0 /BaseFont /Foo /Encoding /Identity-H \
+                    /DescendantFonts [<< /Type /Font /Subtype /CIDFontType2 >>] >>";
+        let pdf = single_page_pdf(font, "BT /F1 12 Tf 100 700 Td <00410042> Tj ET");
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        // By default the bytes are read as Latin-1
+        let spans = doc.extract_page_text(0).unwrap();
+        assert_eq!(spans[0].text, "\0A\0B");
+        assert!(doc.take_diagnostics().is_empty());
+
+        let options = ExtractOptions::new().replace_unmapped(true);
+        let spans = doc.extract_page_text_with(0, &options).unwrap();
+        assert_eq!(spans[0].text, "\u{FFFD}\u{FFFD}");
+        let diagnostics = doc.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].page, Some(0));
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::Font);
+        assert!(diagnostics[0].message.contains("/F1: 2 codes"));
+    }
+
+    #[test]
+    fn test_page_count_from_count() {
+        // A plausible /Count is used without visiting the (here broken) kids
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 9 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.page_count().unwrap(), 2);
+
+        // An implausible one is ignored in favor of walking the tree
+        for count in ["0", "1", "500"] {
+            let pages = format!("<< /Type /Pages /Kids [3 0 R 4 0 R] /Count {} >>", count);
+            let pdf = build_pdf(&[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                &pages,
+                "<< /Type /Page /Parent 2 0 R >>",
+                "<< /Type /Page /Parent 2 0 R >>",
+            ]);
+            let mut doc = Document::parse(&pdf).unwrap();
+            assert_eq!(doc.page_count().unwrap(), 2);
+        }
+    }
+
+    #[test]
+    fn test_page_index_built_once() {
+        // /Count understates the pages under the nested node
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 2 0 R /Rotate 90 >>",
+            "<< /Type /Pages /Parent 2 0 R /Kids [5 0 R 6 0 R] /Rotate 180 >>",
+            "<< /Type /Page /Parent 4 0 R >>",
+            "<< /Type /Page /Parent 4 0 R /Rotate 270 >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.page_count().unwrap(), 2);
+        assert!(doc.pages.is_none());
+        assert_eq!(doc.page_count_exact().unwrap(), 3);
+
+        let rotate = |page: PdfObject| page.as_dict().unwrap()["Rotate"].as_int();
+        assert_eq!(rotate(doc.get_page(1).unwrap()), Some(180));
+        assert_eq!(rotate(doc.get_page(2).unwrap()), Some(270));
+        assert!(doc.get_page(3).is_err());
+
+        assert_eq!(doc.pages.as_ref().map(Vec::len), Some(3));
+    }
+
+    #[test]
+    fn test_wrong_count_does_not_limit_iteration() {
+        let page = |text: &str| {
+            format!("<< /Type /Page /Parent 2 0 R /Contents {} >>", text)
+        };
+        let content = |text: &str| stream_obj("", &format!("BT 100 700 Td ({}) Tj ET", text));
+
+        // /Count 2 over three pages
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+            &page("5 0 R"),
+            "<< /Type /Pages /Parent 2 0 R /Kids [6 0 R 7 0 R] >>",
+            &content("One"),
+            &page("8 0 R"),
+            &page("9 0 R"),
+            &content("Two"),
+            &content("Three"),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.pages().count(), 3);
+        let text = crate::pdf_to_text(&pdf).unwrap();
+        assert!(text.contains("Three"), "{:?}", text);
+
+        // /Count 4 over one page
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 4 >>",
+            &page("4 0 R"),
+            &content("Only"),
+        ]);
+        let options = ExtractOptions::new().fail_fast(true);
+        assert_eq!(crate::pdf_to_text_with(&pdf, &options).unwrap(), "Only\n");
+        assert_eq!(Document::parse(&pdf).unwrap().page_count_exact().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_page_tree_cycle() {
+        let cycle =
+            |err: PdfError| matches!(err, PdfError::InvalidStructure(m) if m == "cycle detected");
+
+        // A node listing itself among its kids
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 2 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert!(cycle(doc.get_page(0).unwrap_err()));
+
+        // A loop through an intermediate node
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 0 >>",
+            "<< /Type /Pages /Parent 2 0 R /Kids [2 0 R] >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert!(cycle(doc.page_count().unwrap_err()));
+
+        // A page and a node listed under two parents are not a cycle; the
+        // second listing is skipped
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+            "<< /Type /Pages /Parent 2 0 R /Kids [3 0 R 6 0 R] /Count 2 >>",
+            "<< /Type /Pages /Parent 2 0 R /Kids [4 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 4 0 R >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.page_count_exact().unwrap(), 2);
+        let diagnostics = doc.take_diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.kind == DiagnosticKind::PageTree));
+    }
+
+    #[test]
+    fn test_xref_prev_cycle() {
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+        ];
+        let pdf = build_pdf(&objects);
+        let xref = pdf.windows(6).position(|w| w == b"\nxref\n").unwrap() + 1;
+        let pdf = build_pdf_with_trailer(&objects, &format!("/Prev {} ", xref));
+
+        let err = Document::parse_strict(&pdf).err().unwrap();
+        assert!(matches!(err, PdfError::InvalidStructure(m) if m == "cycle detected"));
+
+        // Lenient parsing rebuilds the xref instead
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.page_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_form_xobject_text() {
+        let content = "BT /F1 12 Tf 72 700 Td (Page) Tj ET \
+                       q 1 0 0 1 72 500 cm /Fm1 Do Q /Fm2 Do /Im1 Do";
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> \
+             /XObject << /Fm1 6 0 R /Fm2 8 0 R /Im1 9 0 R >> >> >>",
+            &stream_obj("", content),
+            "<< /Type /Font /Subtype /Type1 >>",
+            &stream_obj(
+                "/Type /XObject /Subtype /Form /BBox [0 0 100 100] /Matrix [1 0 0 1 0 50] \
+                 /Resources << /Font << /F2 7 0 R >> >>",
+                "BT /F2 10 Tf 10 0 Td (A) Tj ET",
+            ),
+            "<< /Type /Font /Subtype /Type1 /Encoding << /Differences [65 /B] >> >>",
+            // No /Resources, so the page's fonts are used
+            &stream_obj(
+                "/Type /XObject /Subtype /Form /BBox [0 0 100 100]",
+                "BT /F1 12 Tf 72 300 Td (Inherited) Tj ET",
+            ),
+            &stream_obj("/Type /XObject /Subtype /Image /Width 1 /Height 1", "\0"),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        let spans = doc.extract_page_text(0).unwrap();
+        let found: Vec<_> = spans.iter().map(|s| (s.text.as_str(), s.x, s.y)).collect();
+        assert_eq!(found, [("Page", 72.0, 700.0), ("B", 82.0, 550.0), ("Inherited", 72.0, 300.0)]);
+    }
+
+    #[test]
+    fn test_broken_unused_forms_are_skipped() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> \
+             /XObject << /Fm1 6 0 R /Fm2 7 0 R /Fm3 8 0 R >> >> >>",
+            &stream_obj("", "BT /F1 12 Tf 72 700 Td (Page) Tj ET"),
+            "<< /Type /Font /Subtype /Type1 >>",
+            "]]",
+            &stream_obj("/Subtype /Form /Matrix [1 0 0 1 0 6 0 R]", ""),
+            &stream_obj("/Subtype /Form /Resources << /Font 6 0 R >>", ""),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        let spans = doc.extract_page_text(0).unwrap();
+        assert_eq!(spans[0].text, "Page");
+
+        let diagnostics = doc.take_diagnostics();
+        for form in ["/Fm1", "/Fm2", "/Fm3"] {
+            assert!(diagnostics.iter().any(|d| d.message.contains(form)), "{}", form);
+        }
+    }
+
+    #[test]
+    fn test_fill_color_in_resource_color_spaces() {
+        let content = "BT /F1 12 Tf 72 700 Td /Icc cs 1 0 0 scn (Icc) Tj \
+                       0 -20 Td /Spot cs 1 scn (Spot) Tj 0 -20 Td /L cs 50 0 0 sc (Lab) Tj ET";
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> \
+             /ColorSpace << /Icc [/ICCBased 6 0 R] /Spot [/Separation /Gold /DeviceCMYK 7 0 R] \
+             /L [/Lab << /WhitePoint [0.95 1 1.09] >>] >> >> >>",
+            &stream_obj("", content),
+            "<< /Type /Font /Subtype /Type1 >>",
+            &stream_obj("/N 3", ""),
+            "<< /FunctionType 2 /Domain [0 1] /C0 [0 0 0 0] /C1 [0 0.2 0.9 0] /N 1 >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        let spans = doc.extract_page_text(0).unwrap();
+        let colors: Vec<_> = spans.iter().map(|s| (s.text.as_str(), s.color)).collect();
+        assert_eq!(colors, [("Icc", Some([255, 0, 0])), ("Spot", None), ("Lab", None)]);
+    }
+
+    #[test]
+    fn test_malformed_tm_diagnostic() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type1 >>",
+            "BT /F1 12 Tf /X 1 0 0 1 100 500 Tm (Hi) Tj ET",
+        );
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.extract_page_text(0).unwrap()[0].y, 500.0);
+        let diagnostics = doc.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::Content);
+        assert_eq!(diagnostics[0].page, Some(0));
+    }
+
+    #[test]
+    fn test_version_and_filters() {
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R /Version /1.7 >>",
+            "<< /Type /Pages /Kids [] /Count 0 >>",
+            &stream_obj("/Filter /FlateDecode", "x"),
+            &stream_obj("/Filter [/ASCIIHexDecode /FlateDecode]", "78>"),
+        ];
+        let pdf = build_pdf(&objects);
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.version(), "1.7");
+        assert_eq!(doc.xref_kind(), XrefKind::Table);
+        assert_eq!(doc.stream_filters(), ["ASCIIHexDecode", "FlateDecode"]);
+
+        // An older catalog /Version does not override the header
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R /Version /1.3 >>",
+            "<< /Type /Pages /Kids [] /Count 0 >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.version(), "1.4");
+        assert!(doc.stream_filters().is_empty());
+    }
+
+    #[test]
+    fn test_file_id() {
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [] /Count 0 >>",
+        ];
+        let pdf = build_pdf_with_trailer(&objects, "/ID [<A1B2C3> (v2)] ");
+        let doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.id(), Some((&[0xA1, 0xB2, 0xC3][..], &b"v2"[..])));
+
+        let pdf = build_pdf(&objects);
+        assert_eq!(Document::parse(&pdf).unwrap().id(), None);
+
+        let pdf = build_pdf_with_trailer(&objects, "/ID [1 2] ");
+        assert_eq!(Document::parse(&pdf).unwrap().id(), None);
+    }
+
+    #[test]
+    fn test_document_info() {
+        let pdf = build_pdf_with_trailer(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [] /Count 0 >>",
+                "<< /Title (Annual Report) /Author <FEFF00C90076006100200D9E> \
+                 /CreationDate (D:20240131235959+07'00') /ModDate (D:2024) >>",
+            ],
+            "/Info 3 0 R ",
+        );
+
+        let mut doc = Document::parse(&pdf).unwrap();
+        let info = doc.info().unwrap();
+        assert_eq!(info.title.as_deref(), Some("Annual Report"));
+        assert_eq!(info.author.as_deref(), Some("\u{c9}va \u{d9e}"));
+        assert_eq!(info.subject, None);
+        assert_eq!(info.creation_date.as_deref(), Some("2024-01-31T23:59:59+07:00"));
+        assert_eq!(info.mod_date.as_deref(), Some("2024-01-01T00:00:00-00:00"));
+    }
+
+    #[test]
+    fn test_normalize_pdf_date() {
+        assert_eq!(normalize_pdf_date("D:19991231120000Z"), "1999-12-31T12:00:00Z");
+        assert_eq!(normalize_pdf_date("D:20200102030405-05'30"), "2020-01-02T03:04:05-05:30");
+        assert_eq!(normalize_pdf_date("yesterday"), "yesterday");
+    }
+
+    #[test]
+    fn test_outline() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R /Outlines 5 0 R /Dests << /intro [3 0 R /Fit] >> >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] >>",
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] >>",
+            "<< /Type /Outlines /First 6 0 R /Last 7 0 R >>",
+            "<< /Title (Intro) /Dest /intro /Next 7 0 R /First 8 0 R >>",
+            "<< /Title <FEFF0054006F0063> /A << /S /GoTo /D [4 0 R /XYZ 0 792 0] >> /Prev 6 0 R >>",
+            "<< /Title (Details) /Dest [4 0 R /Fit] /Next 8 0 R >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        let outline = doc.outline().unwrap();
+
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].title, "Intro");
+        assert_eq!(outline[0].dest_page, Some(0));
+        assert_eq!(outline[0].children.len(), 1);
+        assert_eq!(outline[0].children[0].title, "Details");
+        assert_eq!(outline[0].children[0].dest_page, Some(1));
+        assert_eq!(outline[1].title, "Toc");
+        assert_eq!(outline[1].dest_page, Some(1));
+    }
+
+    #[test]
+    fn test_outline_loops_and_depth() {
+        // The child of item 6 links back to it, and 7 is its own sibling
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R /Outlines 4 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+            "<< /Type /Outlines /First 5 0 R >>",
+            "<< /Title (A) /First 6 0 R /Next 7 0 R >>",
+            "<< /Title (B) /First 5 0 R >>",
+            "<< /Title (C) /Next 7 0 R >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        let outline = doc.outline().unwrap();
+        let titles: Vec<_> = outline.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["A", "C"]);
+        assert_eq!(outline[0].children[0].title, "B");
+        assert!(outline[0].children[0].children.is_empty());
+
+        // Every item is the only child of the one before
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R /Outlines 4 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R >>".to_string(),
+            "<< /Type /Outlines /First 5 0 R >>".to_string(),
+        ];
+        for num in 5..(MAX_OUTLINE_DEPTH + 10) {
+            objects.push(format!("<< /Title (x) /First {} 0 R >>", num + 1));
+        }
+        objects.push("<< /Title (x) >>".to_string());
+        let objects: Vec<&str> = objects.iter().map(String::as_str).collect();
+        let pdf = build_pdf(&objects);
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert!(doc.outline().is_err());
+    }
+
+    #[test]
+    fn test_resolve_named_dest() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R /Names << /Dests 5 0 R >> /Dests << /old [3 0 R] >> >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+            "<< /Kids [6 0 R 7 0 R] >>",
+            "<< /Limits [(alpha) (beta)] /Names [(alpha) [3 0 R /Fit] (beta) 8 0 R] >>",
+            "<< /Limits [(gamma) (zeta)] /Names [(gamma) << /D [4 0 R /XYZ 0 0 0] >>] >>",
+            "[4 0 R /Fit]",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        assert_eq!(doc.resolve_named_dest("alpha").unwrap(), Some(0));
+        assert_eq!(doc.resolve_named_dest("beta").unwrap(), Some(1));
+        assert_eq!(doc.resolve_named_dest("gamma").unwrap(), Some(1));
+        assert_eq!(doc.resolve_named_dest("old").unwrap(), Some(0));
+        assert_eq!(doc.resolve_named_dest("delta").unwrap(), None);
+    }
+
+    #[test]
+    fn test_extract_page_links() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R /Dests << /end [4 0 R /Fit] >> >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 2 0 R /Annots [5 0 R 6 0 R 7 0 R 8 0 R] >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+            "<< /Type /Annot /Subtype /Link /Rect [10 20 110 40] \
+             /A << /S /URI /URI (https://example.com) >> >>",
+            "<< /Type /Annot /Subtype /Link /Rect [200 60 100 50] /Dest /end >>",
+            "<< /Type /Annot /Subtype /Link /Rect [0 0 1 1] /A << /S /GoTo /D [3 0 R /Fit] >> >>",
+            "<< /Type /Annot /Subtype /Text /Rect [0 0 1 1] >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        let links = doc.extract_page_links(0).unwrap();
+
+        assert_eq!(
+            links,
+            [
+                Link {
+                    rect: [10.0, 20.0, 110.0, 40.0],
+                    target: LinkTarget::Uri("https://example.com".into()),
+                },
+                Link {
+                    rect: [100.0, 50.0, 200.0, 60.0],
+                    target: LinkTarget::Page(1),
+                },
+                Link {
+                    rect: [0.0, 0.0, 1.0, 1.0],
+                    target: LinkTarget::Page(0),
+                },
+            ]
+        );
+        assert!(doc.extract_page_links(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_extract_page_annotations() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Annots [4 0 R 5 0 R 6 0 R 7 0 R] >>",
+            "<< /Type /Annot /Subtype /Text /Rect [300 700 320 680] \
+             /Contents <FEFF00A70020004E006F007400650020201C0061201D> /T (Reviewer) \
+             /Popup 7 0 R >>",
+            "<< /Type /Annot /Subtype /Highlight /Rect [72 500 200 512] /QuadPoints [] >>",
+            "<< /Type /Annot /Subtype /Link /Rect [0 0 1 1] /A << /S /URI /URI (x) >> >>",
+            "<< /Type /Annot /Subtype /Popup /Rect [0 0 100 100] /Parent 4 0 R >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        assert_eq!(
+            doc.extract_page_annotations(0).unwrap(),
+            [
+                Annotation {
+                    subtype: "Text".into(),
+                    contents: Some("\u{a7} Note \u{201c}a\u{201d}".into()),
+                    rect: [300.0, 680.0, 320.0, 700.0],
+                    author: Some("Reviewer".into()),
+                },
+                Annotation {
+                    subtype: "Highlight".into(),
+                    contents: None,
+                    rect: [72.0, 500.0, 200.0, 512.0],
+                    author: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_page_dimensions_inherited() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 /MediaBox [0 0 595 842] >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /CropBox [10 10 602 782] >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let first = doc.page_dimensions(0).unwrap();
+        assert_eq!(first.media_box, [0.0, 0.0, 595.0, 842.0]);
+        assert_eq!(first.crop_box, first.media_box);
+        assert_eq!(first.width(), 595.0);
+
+        let second = doc.page_dimensions(1).unwrap();
+        assert_eq!(second.media_box, [0.0, 0.0, 612.0, 792.0]);
+        assert_eq!(second.crop_box, [10.0, 10.0, 602.0, 782.0]);
+    }
+
+    #[test]
+    fn test_inherited_resources() {
+        // The font dictionary only exists on the root Pages node
+        let content = stream_obj("", "BT /F1 12 Tf 100 700 Td (Price \\200) Tj ET");
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 /MediaBox [0 0 595 842] /Rotate 90 \
+             /Resources << /Font << /F1 6 0 R >> >> >>",
+            "<< /Type /Pages /Parent 2 0 R /Kids [4 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 3 0 R /Contents 5 0 R >>",
+            &content,
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let page = doc.get_page(0).unwrap();
+        let dict = page.as_dict().unwrap();
+        assert!(dict.contains_key("Resources"));
+        assert_eq!(dict.get("Rotate"), Some(&PdfObject::Int(90)));
+        assert_eq!(doc.page_dimensions(0).unwrap().media_box, [0.0, 0.0, 595.0, 842.0]);
+
+        let spans = doc.extract_page_text(0).unwrap();
+        assert_eq!(spans[0].text, "Price \u{20ac}");
+    }
+
+    #[test]
+    fn test_rotated_page_clusters_like_upright() {
+        let font = "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>";
+        let upright = "BT /F1 12 Tf \
+            1 0 0 1 100 500 Tm (Name) Tj 1 0 0 1 300 500 Tm (Qty) Tj \
+            1 0 0 1 100 480 Tm (Apple) Tj 1 0 0 1 300 480 Tm (3) Tj ET";
+        // Same layout drawn for /Rotate 90: display (X, Y) is user (612 - Y, X)
+        let rotated = "BT /F1 12 Tf \
+            0 1 -1 0 112 100 Tm (Name) Tj 0 1 -1 0 112 300 Tm (Qty) Tj \
+            0 1 -1 0 132 100 Tm (Apple) Tj 0 1 -1 0 132 300 Tm (3) Tj ET";
+
+        let pdf = single_page_pdf(font, upright);
+        let expected = Document::parse(&pdf).unwrap().extract_page_text(0).unwrap();
+        let pdf = single_page_pdf_with(font, rotated, "/Rotate 90 ");
+        let spans = Document::parse(&pdf).unwrap().extract_page_text(0).unwrap();
+
+        let summary = |spans: &[TextSpan]| -> Vec<(String, f64, f64)> {
+            spans.iter().map(|s| (s.text.clone(), s.x, s.y)).collect()
+        };
+        assert_eq!(summary(&spans), summary(&expected));
+
+        let rows = crate::Table::from_spans(spans).rows;
+        assert_eq!(rows, crate::Table::from_spans(expected).rows);
+        assert_eq!(rows[0], vec!["Name", "Qty"]);
+    }
+
+    #[test]
+    fn test_pages_iterator() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 5 0 R >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 6 0 R >>",
+            &stream_obj("", "BT /F1 12 Tf 100 700 Td (First) Tj ET"),
+            &stream_obj("", "BT /F1 12 Tf 100 700 Td (Second) Tj ET"),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let texts: Vec<String> = doc
+            .pages()
+            .map(|spans| spans.unwrap()[0].text.clone())
+            .collect();
+        assert_eq!(texts, vec!["First", "Second"]);
+    }
+
+    #[test]
+    fn test_extract_page_words() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /FirstChar 32 \
+             /Widths [250 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 \
+             0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 500 500] >>",
+            "BT /F1 10 Tf 100 700 Td (ab ba) Tj ET",
+        );
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let words = doc.extract_page_words(0).unwrap();
+        assert_eq!(words.len(), 2);
+        assert_eq!((words[0].text.as_str(), words[0].x), ("ab", 100.0));
+        // "ab ba" spans 22.5pt, shared evenly between its five characters
+        assert_eq!((words[1].text.as_str(), words[1].x), ("ba", 113.5));
+        assert_eq!(words[1].width, 9.0);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_extract_all_pages_parallel() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 5 0 R >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 6 0 R >>",
+            &stream_obj("", "BT /F1 12 Tf 100 700 Td (First) Tj ET"),
+            &stream_obj("", "BT /F1 12 Tf 100 700 Td (Second) Tj ET"),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let pages = doc.extract_all_pages_parallel().unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0][0].text, "First");
+        assert_eq!(pages[1][0].text, "Second");
+    }
+
+    #[test]
+    fn test_font_cache_shared_across_pages() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 6 0 R \
+             /Resources << /Font << /F1 5 0 R >> >> >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 7 0 R \
+             /Resources << /Font << /F9 5 0 R >> >> >>",
+            "<< /Type /Font /Subtype /Type1 /Encoding << /Differences [65 /eacute] >> >>",
+            &stream_obj("", "BT /F1 12 Tf 100 700 Td (A) Tj ET"),
+            &stream_obj("", "BT /F9 12 Tf 100 700 Td (AA) Tj ET"),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        assert_eq!(doc.extract_page_text(0).unwrap()[0].text, "\u{e9}");
+        assert_eq!(doc.extract_page_text(1).unwrap()[0].text, "\u{e9}\u{e9}");
+        assert_eq!(doc.font_cache.len(), 1);
+        assert!(doc.font_cache.contains_key(&ObjRef::new(5, 0)));
+    }
+
+    #[test]
+    fn test_lenient_parsing_skips_stray_operators() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R BT /Contents 4 0 R >>",
+            &stream_obj("", "BT /F1 12 Tf 100 700 Td (Hello) Tj ET"),
+        ]);
+
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.extract_page_text(0).unwrap()[0].text, "Hello");
+
+        let mut strict = Document::parse_with_options(&pdf, ParseOptions { lenient: false });
+        assert!(strict.as_mut().unwrap().extract_page_text(0).is_err());
+    }
+
+    #[test]
+    fn test_page_content_stream() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 6 0 R >>",
+            "<< /Type /Page /Parent 2 0 R /Contents [6 0 R 7 0 R] >>",
+            "<< /Type /Page /Parent 2 0 R >>",
+            &stream_obj("", "BT (A) Tj ET"),
+            &stream_obj("", "BT (B) Tj ET"),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        assert_eq!(doc.page_content_stream(0).unwrap(), b"BT (A) Tj ET");
+        assert_eq!(doc.page_content_stream(1).unwrap(), b"BT (A) Tj ET\nBT (B) Tj ET");
+        assert!(doc.page_content_stream(2).unwrap().is_empty());
+        assert!(doc.extract_page_text(2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_content_streams_split_mid_operation() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents [4 0 R 5 0 R 6 0 R] >>",
+            &stream_obj("", "BT 12 Tf 100 700 Td [(Hel)"),
+            &stream_obj("", "-10 (lo)] TJ (, world)"),
+            &stream_obj("", "Tj ET"),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let content = doc.page_content_stream(0).unwrap();
+        assert_eq!(content, b"BT 12 Tf 100 700 Td [(Hel)-10 (lo)] TJ (, world)Tj ET");
+        assert_eq!(doc.extract_page_text_string(0).unwrap(), "Hello, world");
+    }
+
+    #[test]
+    fn test_extract_region() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+            "BT /F1 10 Tf 50 750 Td (Header) Tj 0 -700 Td (Footer) Tj 400 0 Td (Total: 42) Tj \
+             0 400 Td -20 Ts (Note) Tj ET",
+        );
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let texts = |spans: Vec<TextSpan>| -> Vec<String> {
+            spans.into_iter().map(|s| s.text).collect()
+        };
+        let footer = doc.extract_region(0, [0.0, 0.0, 612.0, 100.0]).unwrap();
+        assert_eq!(texts(footer), ["Footer", "Total: 42"]);
+        // Corners may be given in any order
+        let corner = doc.extract_region(0, [612.0, 60.0, 400.0, 0.0]).unwrap();
+        assert_eq!(texts(corner), ["Total: 42"]);
+        // The box reaches above the baseline, so a band just over it still hits
+        assert_eq!(texts(doc.extract_region(0, [0.0, 755.0, 612.0, 792.0]).unwrap()), ["Header"]);
+        assert!(doc.extract_region(0, [200.0, 300.0, 300.0, 400.0]).unwrap().is_empty());
+        // Text rise moves the glyphs, not the baseline the box starts from
+        let note = doc.extract_region(0, [400.0, 452.0, 612.0, 455.0]).unwrap();
+        assert_eq!(texts(note), ["Note"]);
+        assert!(doc.extract_region(0, [400.0, 432.0, 612.0, 435.0]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_extract_page_text_string_with() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+            "BT /F1 10 Tf 50 700 Td (Name) Tj 200 0 Td (Qty) Tj -200 -20 Td (Pen) Tj \
+             200 -4 Td (2) Tj ET",
+        );
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert_eq!(doc.extract_page_text_string(0).unwrap(), "Name Qty\nPen\n2");
+
+        let options = LineJoinOptions {
+            intra_line_sep: "\t".into(),
+            inter_line_sep: "\n\n".into(),
+            y_tolerance: 5.0,
+        };
+        let text = doc.extract_page_text_string_with(0, &options).unwrap();
+        assert_eq!(text, "Name\tQty\n\nPen\t2");
+    }
+
+    #[test]
+    fn test_extract_text_dedup_headers() {
+        let page = |body: &str, number: usize| {
+            stream_obj(
+                "",
+                &format!(
+                    "BT 50 760 Td (ACME Report) Tj 0 -360 Td ({}) Tj 250 -370 Td (Page {}) Tj ET",
+                    body, number
+                ),
+            )
+        };
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 6 0 R >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 7 0 R >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 8 0 R >>",
+            &page("Alpha", 1),
+            &page("Beta", 2),
+            &page("Page 10 of the index", 3),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        assert_eq!(
+            doc.extract_text_dedup_headers().unwrap(),
+            "Alpha\n\nBeta\n\nPage 10 of the index"
+        );
+        assert_eq!(
+            doc.extract_text_dedup_headers_with(true).unwrap(),
+            "ACME Report\nAlpha\nPage 1\n\nBeta\n\nPage 10 of the index"
+        );
+        assert_eq!(line_template("Page  12 of 3a"), "Page # of #a");
+    }
+
+    #[test]
+    fn test_search() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+            "BT /F1 10 Tf 100 700 Td (Hello big) Tj 60 0 Td (World) Tj 0 -20 Td (hello) Tj ET",
+        );
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let hits = doc.search("hello", true).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!((hits[0].x, hits[0].y), (100.0, 700.0));
+        assert_eq!((hits[1].text.as_str(), hits[1].x, hits[1].y), ("hello", 160.0, 680.0));
+        assert_eq!(doc.search("hello", false).unwrap().len(), 1);
+
+        // Matches cross word and span boundaries and start mid-word
+        let hits = doc.search("ig world", true).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].page, hits[0].text.as_str()), (0, "ig World"));
+        assert_eq!(hits[0].x, 135.0);
+    }
+
+    #[test]
+    fn test_indirect_length_with_endstream_in_data() {
+        let content = "BT /F1 12 Tf 100 700 Td (endstream) Tj ET";
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 4 0 R >>",
+            &format!("<< /Length 5 0 R >>\nstream\n{}\nendstream", content),
+            &content.len().to_string(),
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let spans = doc.extract_page_text(0).unwrap();
+        assert_eq!(spans[0].text, "endstream");
+    }
+
+    #[test]
+    fn test_rebuild_xref_on_wrong_offsets() {
+        let pdf = single_page_pdf("<< /Type /Font /Subtype /Type1 >>", "BT 100 700 Td (Hi) Tj ET");
+        // Shift every object so the xref offsets no longer land on them,
+        // keeping startxref pointing at the (moved) xref table
+        let padding = "% padding\n";
+        let text = String::from_utf8(pdf).unwrap();
+        let (body, tail) = text.split_once("startxref\n").unwrap();
+        let xref_offset: usize = tail.lines().next().unwrap().parse().unwrap();
+        let shifted = format!(
+            "%PDF-1.4\n{}{}startxref\n{}\n%%EOF\n",
+            padding,
+            &body["%PDF-1.4\n".len()..],
+            xref_offset + padding.len()
+        )
+        .into_bytes();
+
+        let mut strict = Document::parse_strict(&shifted).unwrap();
+        assert!(strict.extract_page_text(0).is_err());
+
+        let mut doc = Document::parse(&shifted).unwrap();
+        assert_eq!(doc.extract_page_text(0).unwrap()[0].text, "Hi");
+    }
+
+    #[test]
+    fn test_rebuild_xref_without_xref_table() {
+        let pdf = single_page_pdf("<< /Type /Font /Subtype /Type1 >>", "BT 100 700 Td (Hi) Tj ET");
+        let xref_start = pdf.windows(5).position(|w| w == b"xref\n").unwrap();
+
+        // Trailer dictionary intact, xref table and startxref gone
+        let mut damaged = pdf[..xref_start].to_vec();
+        damaged.extend_from_slice(b"trailer\n<< /Root 1 0 R >>\n%%EOF\n");
+        assert!(Document::parse_strict(&damaged).is_err());
+        let mut doc = Document::parse(&damaged).unwrap();
+        assert_eq!(doc.xref_kind(), XrefKind::Rebuilt);
+        assert_eq!(doc.page_count().unwrap(), 1);
+
+        // No trailer either: the catalog is found by its /Type
+        let truncated = &pdf[..xref_start];
+        let mut doc = Document::parse(truncated).unwrap();
+        assert_eq!(doc.extract_page_text(0).unwrap()[0].text, "Hi");
+    }
+
+    #[test]
+    fn test_garbage_after_eof() {
+        let pdf = single_page_pdf("<< /Type /Font /Subtype /Type1 >>", "BT 100 700 Td (Hi) Tj ET");
+        let noise: Vec<u8> = (0..4096u32).map(|i| (i * 7 % 251) as u8).collect();
+
+        let mut noisy = pdf.clone();
+        noisy.extend(&noise);
+        assert!(matches!(Document::parse_strict(&noisy), Err(PdfError::MissingEof)));
+        let mut doc = Document::parse(&noisy).unwrap();
+        assert!(!doc.rebuilt);
+        assert_eq!(doc.extract_page_text(0).unwrap()[0].text, "Hi");
+        assert_eq!(doc.take_diagnostics()[0].kind, DiagnosticKind::Xref);
+
+        // Without startxref, the xref table itself is found
+        let startxref = pdf.windows(9).rposition(|w| w == b"startxref").unwrap();
+        let mut truncated = pdf[..startxref].to_vec();
+        truncated.extend(&noise);
+        let mut doc = Document::parse(&truncated).unwrap();
+        assert!(!doc.rebuilt);
+        assert_eq!(doc.extract_page_text(0).unwrap()[0].text, "Hi");
+    }
+
+    /// RC4 128-bit (V2 R3) file with an empty user password; the content
+    /// stream and title were encrypted with the keys for objects 4 and 6
+    fn encrypted_pdf(u: &str) -> Vec<u8> {
+        let ciphertext = "9193b4784d9cfe978cdbb2527e15433011c63b9135714f8d\
+                          960efb2ea2ebe31ae2f91aa3d96d";
+        let placeholder = "X".repeat(ciphertext.len() / 2);
+        let encrypt = format!(
+            "<< /Filter /Standard /V 2 /R 3 /Length 128 /P -3904 /O <{}> /U <{}> >>",
+            "42".repeat(32),
+            u
+        );
+        let mut pdf = build_pdf_with_trailer(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+                "<< /Type /Page /Parent 2 0 R /Contents 4 0 R \
+                 /Resources << /Font << /F1 5 0 R >> >> >>",
+                &stream_obj("", &placeholder),
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+                "<< /Title <8d064ea4d1cd8358b370356a> >>",
+                &encrypt,
+            ],
+            "/Encrypt 7 0 R /Info 6 0 R /ID [<30313233343536373839616263646566> <00>] ",
+        );
+
+        let start = pdf
+            .windows(placeholder.len())
+            .position(|w| w == placeholder.as_bytes())
+            .unwrap();
+        for (i, byte) in pdf[start..start + placeholder.len()].iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&ciphertext[i * 2..i * 2 + 2], 16).unwrap();
+        }
+        pdf
+    }
+
+    #[test]
+    fn test_encrypted_with_empty_password() {
+        let pdf = encrypted_pdf("7572d8db7da08d0a200bc2a0bc6f3c97");
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        assert_eq!(doc.extract_page_text(0).unwrap()[0].text, "Secret");
+        assert_eq!(doc.info().unwrap().title.as_deref(), Some("Hidden title"));
+    }
+
+    #[test]
+    fn test_encrypted_with_user_password() {
+        let pdf = encrypted_pdf("00112233445566778899aabbccddeeff");
+        assert!(matches!(Document::parse(&pdf), Err(PdfError::PasswordRequired)));
+    }
+
+    #[test]
+    fn test_encryption_info() {
+        let pdf = encrypted_pdf("7572d8db7da08d0a200bc2a0bc6f3c97");
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert!(doc.is_encrypted());
+        let info = EncryptionInfo {
+            version: 2,
+            revision: 3,
+            key_bits: 128,
+            requires_password: false,
+        };
+        assert_eq!(doc.encryption_info(), Some(info));
+
+        let pdf = single_page_pdf("<< /Type /Font /Subtype /Type1 >>", "");
+        let mut doc = Document::parse(&pdf).unwrap();
+        assert!(!doc.is_encrypted());
+        assert_eq!(doc.encryption_info(), None);
+    }
+
+    #[test]
+    fn test_extract_page_images() {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        // 2x1 RGB image: red, blue
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[255, 0, 0, 0, 0, 255]).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let jpeg = b"\xFF\xD8\xFF\xE0fake jpeg";
+        let objects = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            b"<< /Type /Page /Parent 2 0 R \
+              /Resources << /XObject << /Im1 4 0 R /Im2 5 0 R /Fm1 6 0 R /Im3 8 0 R >> >> >>"
+                .to_vec(),
+            binary_stream_obj(
+                "/Type /XObject /Subtype /Image /Width 640 /Height 480 \
+                 /ColorSpace [/ICCBased 7 0 R] /BitsPerComponent 8 /Filter /DCTDecode",
+                jpeg,
+            ),
+            binary_stream_obj(
+                "/Type /XObject /Subtype /Image /Width 2 /Height 1 /ColorSpace /DeviceRGB \
+                 /BitsPerComponent 8 /Filter /FlateDecode",
+                &compressed,
+            ),
+            stream_obj("/Type /XObject /Subtype /Form /BBox [0 0 1 1]", "").into_bytes(),
+            stream_obj("/N 1", "").into_bytes(),
+            binary_stream_obj(
+                "/Type /XObject /Subtype /Image /Width 1728 /Height 2 /BitsPerComponent 1 \
+                 /Filter /CCITTFaxDecode /DecodeParms << /K -1 /Columns 1728 /BlackIs1 true >>",
+                b"\x26\xa0\x00\x10",
+            ),
+        ];
+        let pdf = build_pdf_bytes(&objects, "");
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let images = doc.extract_page_images(0).unwrap();
+        assert_eq!(images.len(), 3);
+
+        assert_eq!(images[0].name, "Im1");
+        assert_eq!(images[0].format, ImageFormat::Jpeg);
+        assert_eq!((images[0].width, images[0].height), (640, 480));
+        assert_eq!(images[0].color_space.as_deref(), Some("DeviceGray"));
+        assert_eq!(images[0].data, jpeg);
+
+        assert_eq!(images[1].format, ImageFormat::Raw);
+        assert_eq!(images[1].color_space.as_deref(), Some("DeviceRGB"));
+        assert_eq!(images[1].bits_per_component, Some(8));
+        assert_eq!(images[1].data, vec![255, 0, 0, 0, 0, 255]);
+
+        let params = CcittParams { k: -1, columns: 1728, rows: None, black_is_1: true };
+        assert_eq!(images[2].format, ImageFormat::Ccitt(params));
+        assert_eq!(images[2].data, b"\x26\xa0\x00\x10");
+    }
+
+    #[test]
+    fn test_iter_objects_and_get() {
+        let pdf = single_page_pdf("<< /Type /Font /Subtype /Type1 >>", "BT ET");
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let objects: Vec<(ObjRef, Result<PdfObject>)> = doc.iter_objects().collect();
+        let numbers: Vec<u32> = objects.iter().map(|(r, _)| r.obj_num).collect();
+        assert_eq!(numbers, vec![1, 2, 3, 4, 5]);
+        assert!(objects.iter().all(|(_, obj)| obj.is_ok()));
+
+        let font = doc.get(5, 0).unwrap();
+        assert_eq!(
+            font.as_dict().and_then(|d| d.get("Type")),
+            Some(&PdfObject::Name("Font".into()))
+        );
+        assert!(doc.get(42, 0).is_err());
+
+        // A dangling reference is null, and keeps the xref
+        assert_eq!(doc.resolve(ObjRef::new(42, 0)).unwrap(), &PdfObject::Null);
+        assert!(!doc.rebuilt);
+    }
+
+    #[test]
+    fn test_form_fields() {
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R /AcroForm << /Fields [4 0 R 7 0 R 8 0 R] >> >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Annots [5 0 R 6 0 R 7 0 R] >>",
+            // Parent node "applicant" with two text fields
+            "<< /T (applicant) /FT /Tx /Kids [5 0 R 6 0 R] >>",
+            "<< /T (name) /V <FEFF004A006F00EB> /Parent 4 0 R /Subtype /Widget >>",
+            "<< /T (city) /Parent 4 0 R /Subtype /Widget >>",
+            // Checkbox with its widget merged into the field
+            "<< /T (agree) /FT /Btn /V /Yes /AS /Yes /Subtype /Widget >>",
+            // Multi-select choice field with a separate widget kid
+            "<< /T (colors) /FT /Ch /V [(Red) (Blue)] /Kids [9 0 R] >>",
+            "<< /Subtype /Widget /Parent 8 0 R >>",
+        ]);
+        let mut doc = Document::parse(&pdf).unwrap();
+
+        let fields = doc.form_fields().unwrap();
+        let summary: Vec<(&str, Option<&str>, &str)> = fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.value.as_deref(), f.field_type.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("applicant.name", Some("Jo\u{eb}"), "Tx"),
+                ("applicant.city", None, "Tx"),
+                ("agree", Some("Yes"), "Btn"),
+                ("colors", Some("Red, Blue"), "Ch"),
+            ]
+        );
+    }
+
+    /// Run everything a caller would typically do with an opened document
+    fn exercise(data: &[u8]) {
+        for options in [ParseOptions::default(), ParseOptions { lenient: false }] {
+            if let Ok(mut doc) = Document::parse_with_options(data, options) {
+                for i in 0..doc.page_count().unwrap_or(0).min(4) {
+                    let _ = doc.extract_page_text(i);
+                    let _ = doc.extract_page_links(i);
+                }
+                let _ = doc.info();
+                let _ = doc.outline();
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncated_input_does_not_panic() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type1 /Widths [500] /FirstChar 72 >>",
+            "BT /F1 12 Tf 72 720 Td [(He) -20 (llo)] TJ ET",
+        );
+        let startxref = pdf.windows(9).rposition(|w| w == b"startxref").unwrap();
+        for len in 0..pdf.len() {
+            if len < startxref {
+                assert!(Document::parse_strict(&pdf[..len]).is_err(), "length {}", len);
+            }
+            exercise(&pdf[..len]);
+        }
+    }
+
+    #[test]
+    fn test_mutated_input_does_not_panic() {
+        let pdf = single_page_pdf(
+            "<< /Type /Font /Subtype /Type1 /Encoding << /Differences [65 /B] >> >>",
+            "BT /F1 12 Tf 72 720 Td (A) Tj 0 -14 TD (x) ' ET",
+        );
+        for pos in 0..pdf.len() {
+            for byte in [b'-', b'9', b'(', b'[', b'>', 0xFF] {
+                let mut mutated = pdf.clone();
+                mutated[pos] = byte;
+                exercise(&mutated);
+            }
+        }
+
+        // Adversarial numbers where lengths and offsets are expected
+        let pdf = build_pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 4 0 R >>",
+            "<< /Length -1 >>\nstream\nBT ET\nendstream",
+        ]);
+        assert!(Document::parse(&pdf).unwrap().extract_page_text(0).is_err());
+        let pdf = build_pdf_with_trailer(&["<< /Type /Catalog >>"], "/Prev -1");
+        exercise(&pdf);
+        let mut pdf = build_pdf(&["<< /Type /Catalog >>"]);
+        let xref = pdf.windows(6).position(|w| w == b"\n0 2\n0").unwrap();
+        pdf.splice(xref + 1..xref + 4, b"4294967295 2".iter().copied());
+        exercise(&pdf);
+    }
+
+    #[test]
+    fn test_garbage_input_is_rejected() {
+        let mut seed = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..200 {
+            let mut data = b"%PDF-1.4\n".to_vec();
+            for _ in 0..512 {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                data.push(seed as u8);
+            }
+            assert!(Document::parse(&data).is_err());
+            assert!(Document::parse_strict(&data).is_err());
+        }
     }
 }
@@ -26,6 +26,12 @@ pub enum PdfError {
     #[error("Decompression failed: {0}")]
     DecompressError(String),
 
+    #[error("Encrypted PDF requires a password")]
+    PasswordRequired,
+
+    #[error("Unsupported encryption: {0}")]
+    UnsupportedEncryption(String),
+
     #[error("Invalid UTF-8 in string")]
     InvalidUtf8,
 
@@ -0,0 +1,162 @@
+use crate::extract::layout::PageElement;
+use crate::extract::Table;
+
+/// Render page elements as an HTML fragment.
+pub fn elements_to_html(elements: &[PageElement]) -> String {
+    let mut out = String::new();
+
+    for element in elements {
+        match element {
+            PageElement::Heading { level, text, .. } => {
+                let level = (*level).clamp(1, 6);
+                out.push_str(&format!("<h{}>{}</h{}>\n", level, escape_html(text), level));
+            }
+            PageElement::Paragraph { text, .. } => {
+                out.push_str(&format!("<p>{}</p>\n", escape_html(text)));
+            }
+            PageElement::Table { table, .. } => {
+                out.push_str(&table_to_html(table));
+            }
+            PageElement::List { ordered, items, .. } => {
+                let tag = if *ordered { "ol" } else { "ul" };
+                out.push_str(&format!("<{}>\n", tag));
+                for item in items {
+                    out.push_str(&format!("<li>{}</li>\n", escape_html(item)));
+                }
+                out.push_str(&format!("</{}>\n", tag));
+            }
+            PageElement::CodeBlock { text, .. } => {
+                out.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(text)));
+            }
+        }
+    }
+
+    out
+}
+
+/// Convert a Table to an HTML table, one `<td>` per cell. Spanning cells
+/// become a single `<td colspan>`.
+fn table_to_html(table: &Table) -> String {
+    if table.rows.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::from("<table>\n");
+    for (r, row) in table.rows.iter().enumerate() {
+        out.push_str("<tr>");
+        let cells = table.cells.get(r).filter(|cells| cells.len() == row.len());
+        let mut i = 0;
+        while i < row.len() {
+            let span = cells.map_or(1, |cells| cells[i].col_span_hint.clamp(1, row.len() - i));
+            if span > 1 {
+                out.push_str(&format!("<td colspan=\"{}\">{}</td>", span, escape_html(&row[i])));
+            } else {
+                out.push_str(&format!("<td>{}</td>", escape_html(&row[i])));
+            }
+            i += span;
+        }
+        // Pad short rows so every row has the same number of cells
+        for _ in row.len()..table.num_columns {
+            out.push_str("<td></td>");
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::TextSpan;
+
+    #[test]
+    fn test_headings_and_paragraphs() {
+        let elements = vec![
+            PageElement::Heading {
+                level: 2,
+                text: "Results".to_string(),
+                gap_before: 0.0,
+            },
+            PageElement::Paragraph {
+                text: "Revenue grew.".to_string(),
+                runs: Vec::new(),
+                gap_before: 0.0,
+            },
+        ];
+        assert_eq!(
+            elements_to_html(&elements),
+            "<h2>Results</h2>\n<p>Revenue grew.</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_html_table() {
+        let table = Table {
+            rows: vec![
+                vec!["Name".to_string(), "Age".to_string()],
+                vec!["Alice".to_string()],
+            ],
+            num_columns: 2,
+            cells: Vec::new(),
+            header_rows: 1,
+        };
+        let html = elements_to_html(&[PageElement::Table { table, gap_before: 0.0 }]);
+        assert_eq!(
+            html,
+            "<table>\n<tr><td>Name</td><td>Age</td></tr>\n<tr><td>Alice</td><td></td></tr>\n</table>\n"
+        );
+    }
+
+    #[test]
+    fn test_html_colspan() {
+        let span = |text: &str, x: f64, y: f64, width: f64| TextSpan {
+            text: text.to_string(),
+            x,
+            y,
+            font_size: 12.0,
+            font_name: None,
+            rise: 0.0,
+            width,
+            height: 0.0,
+            color: None,
+            bold: false,
+            italic: false,
+            monospace: false,
+        };
+        let table = Table::from_spans(vec![
+            span("Label", 10.0, 100.0, 30.0),
+            span("Both columns", 10.0, 80.0, 120.0),
+            span("A", 10.0, 60.0, 6.0),
+            span("B", 100.0, 60.0, 6.0),
+            span("C", 10.0, 40.0, 6.0),
+            span("D", 100.0, 40.0, 6.0),
+        ]);
+        let html = elements_to_html(&[PageElement::Table { table, gap_before: 0.0 }]);
+        assert!(html.contains("<tr><td colspan=\"2\">Both columns</td></tr>"));
+        assert!(html.contains("<tr><td>A</td><td>B</td></tr>"));
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(
+            escape_html(r#"a < b && c > "d" 'e'"#),
+            "a &lt; b &amp;&amp; c &gt; &quot;d&quot; &#39;e&#39;"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Serialize, Serializer};
+
+use crate::content::TextSpan;
+
+/// JSON shape of a single span
+#[derive(Serialize)]
+struct JsonSpan<'a> {
+    /// 1-based page number, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<usize>,
+    text: &'a str,
+    #[serde(serialize_with = "round2")]
+    x: f64,
+    #[serde(serialize_with = "round2")]
+    y: f64,
+    #[serde(serialize_with = "round2")]
+    width: f64,
+    #[serde(serialize_with = "round2")]
+    font_size: f64,
+    font_name: Option<&'a str>,
+    /// RGB fill color, when the content stream set one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<[u8; 3]>,
+}
+
+impl<'a> JsonSpan<'a> {
+    fn new(page: Option<usize>, span: &'a TextSpan) -> Self {
+        JsonSpan {
+            page,
+            text: &span.text,
+            x: span.x,
+            y: span.y,
+            width: span.width,
+            font_size: span.font_size,
+            font_name: span.font_name.as_deref(),
+            color: span.color,
+        }
+    }
+}
+
+/// Coordinates are rounded to 2 decimals to keep output stable and compact
+fn round2<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64((value * 100.0).round() / 100.0)
+}
+
+/// Render spans as a JSON array of `{text, x, y, width, font_size, font_name}`,
+/// plus `color` as `[r, g, b]` for spans drawn in a set fill color
+pub fn spans_to_json(spans: &[TextSpan]) -> String {
+    let items: Vec<JsonSpan> = spans.iter().map(|s| JsonSpan::new(None, s)).collect();
+    serde_json::to_string(&items).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Render spans from several pages as one JSON array, tagging each span
+/// with its 1-based `page` number
+pub fn pages_to_json(pages: &[(usize, Vec<TextSpan>)]) -> String {
+    let items: Vec<JsonSpan> = pages
+        .iter()
+        .flat_map(|(page, spans)| spans.iter().map(|s| JsonSpan::new(Some(*page), s)))
+        .collect();
+    serde_json::to_string(&items).unwrap_or_else(|_| "[]".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_span(text: &str, x: f64, y: f64) -> TextSpan {
+        TextSpan {
+            text: text.to_string(),
+            x,
+            y,
+            font_size: 12.0,
+            font_name: Some("F1".to_string()),
+            rise: 0.0,
+            width: 30.123456,
+            height: 12.0,
+            color: None,
+            bold: false,
+            italic: false,
+            monospace: false,
+        }
+    }
+
+    #[test]
+    fn test_spans_to_json() {
+        let json = spans_to_json(&[make_span("Hello \"world\"", 72.0, 700.5)]);
+        assert_eq!(
+            json,
+            r#"[{"text":"Hello \"world\"","x":72.0,"y":700.5,"width":30.12,"font_size":12.0,"font_name":"F1"}]"#
+        );
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["text"], "Hello \"world\"");
+    }
+
+    #[test]
+    fn test_pages_to_json() {
+        let pages = vec![(1, vec![make_span("A", 1.0, 2.0)]), (2, vec![make_span("B", 3.0, 4.0)])];
+        let value: serde_json::Value = serde_json::from_str(&pages_to_json(&pages)).unwrap();
+
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        assert_eq!(value[1]["page"], 2);
+        assert_eq!(value[1]["text"], "B");
+        assert!(value[0]["font_name"].is_string());
+    }
+
+    #[test]
+    fn test_span_color() {
+        let red = TextSpan { color: Some([255, 0, 0]), ..make_span("-5.00", 1.0, 2.0) };
+        let json = spans_to_json(&[red]);
+        assert!(json.ends_with(r#""font_name":"F1","color":[255,0,0]}]"#));
+    }
+}
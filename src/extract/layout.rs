@@ -1,16 +1,88 @@
 use crate::content::TextSpan;
-use crate::extract::Table;
+use crate::extract::{Table, TableOptions};
 
-/// A classified page element
+/// A classified page element. `gap_before` is the vertical distance from
+/// the last line of the previous element to the first line of this one, or
+/// 0 for the first element of a column.
 #[derive(Debug, Clone)]
 pub enum PageElement {
-    Heading { level: u8, text: String },
-    Paragraph { text: String },
-    Table { table: Table },
+    Heading { level: u8, text: String, gap_before: f64 },
+    /// `runs` split `text` into pieces of the same bold/italic style
+    Paragraph { text: String, runs: Vec<TextRun>, gap_before: f64 },
+    Table { table: Table, gap_before: f64 },
+    List { ordered: bool, items: Vec<String>, gap_before: f64 },
+    /// Monospace lines with their line breaks and indentation kept
+    CodeBlock { text: String, gap_before: f64 },
+}
+
+impl PageElement {
+    /// Vertical distance from the previous element, 0 when unknown
+    pub fn gap_before(&self) -> f64 {
+        match self {
+            PageElement::Heading { gap_before, .. }
+            | PageElement::Paragraph { gap_before, .. }
+            | PageElement::Table { gap_before, .. }
+            | PageElement::List { gap_before, .. }
+            | PageElement::CodeBlock { gap_before, .. } => *gap_before,
+        }
+    }
+}
+
+/// A piece of paragraph text set in one style
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl TextRun {
+    /// Whether the run is bold or italic
+    pub fn is_styled(&self) -> bool {
+        self.bold || self.italic
+    }
+}
+
+/// Append `text` in the given style, extending the last run if it matches
+fn push_run(runs: &mut Vec<TextRun>, text: &str, bold: bool, italic: bool) {
+    match runs.last_mut() {
+        Some(last) if last.bold == bold && last.italic == italic => last.text.push_str(text),
+        _ if text.is_empty() => {}
+        _ => runs.push(TextRun { text: text.to_string(), bold, italic }),
+    }
+}
+
+/// Options for turning spans into page elements
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutOptions {
+    /// Rejoin words hyphenated at a line break ("inter-" + "national"). When
+    /// off, a line ending in a hyphen is joined to the next without a space
+    /// but keeps the hyphen ("well-" + "known" -> "well-known").
+    pub dehyphenate: bool,
+    /// Order text by recursive XY-cut segmentation instead of the column
+    /// detector, for layouts with sidebars or boxes beside the main text
+    pub xy_cut: bool,
+    /// How table columns are detected
+    pub table: TableOptions,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self { dehyphenate: true, xy_cut: false, table: TableOptions::default() }
+    }
 }
 
 /// Classify text spans into structured page elements (headings, paragraphs, tables).
+/// Multi-column pages are read one column at a time, left to right.
 pub fn classify_spans(spans: Vec<TextSpan>) -> Vec<PageElement> {
+    classify_spans_with_options(spans, LayoutOptions::default())
+}
+
+/// Classify text spans into page elements with custom options
+pub fn classify_spans_with_options(
+    spans: Vec<TextSpan>,
+    options: LayoutOptions,
+) -> Vec<PageElement> {
     let spans: Vec<_> = spans
         .into_iter()
         .filter(|s| !s.text.trim().is_empty())
@@ -21,29 +93,217 @@ pub fn classify_spans(spans: Vec<TextSpan>) -> Vec<PageElement> {
     }
 
     let avg_font_size =
-        spans.iter().map(|s| s.font_size).sum::<f64>() / spans.len() as f64;
+        spans.iter().map(|s| s.font_size.abs()).sum::<f64>() / spans.len() as f64;
     let row_tolerance = avg_font_size * 0.5;
 
+    // Body font size is shared by all columns: most frequent font size
+    // weighted by character count
+    let body_font_size = compute_body_font_size(std::slice::from_ref(&spans));
+
+    let regions = if options.xy_cut {
+        let mut regions = Vec::new();
+        xy_cut(spans, avg_font_size, row_tolerance, 0, &mut regions);
+        regions
+    } else {
+        split_columns(spans, avg_font_size)
+    };
+    regions
+        .into_iter()
+        .flat_map(|region| classify_region(region, row_tolerance, body_font_size, options))
+        .collect()
+}
+
+/// Classify the spans of a single reading column
+fn classify_region(
+    spans: Vec<TextSpan>,
+    row_tolerance: f64,
+    body_font_size: f64,
+    options: LayoutOptions,
+) -> Vec<PageElement> {
     // Group spans into lines by Y coordinate
     let lines = cluster_into_lines(spans, row_tolerance);
 
-    // Compute body font size: most frequent font size weighted by character count
-    let body_font_size = compute_body_font_size(&lines);
-
     // Classify each line
     let classified: Vec<ClassifiedLine> = lines
         .into_iter()
-        .map(|line| classify_line(line, body_font_size))
+        .map(|line| classify_line(line, body_font_size, options.table))
         .collect();
 
     // Merge consecutive lines into elements
-    merge_lines(classified, body_font_size)
+    merge_lines(classified, body_font_size, options)
+}
+
+/// Split a page into 1-3 column regions separated by vertical whitespace
+/// gutters, in reading order. Spans too wide to belong to one column (such
+/// as a title across the page) form their own region before or after the
+/// columns. Returns the spans unchanged when no column layout is detected.
+fn split_columns(spans: Vec<TextSpan>, avg_font_size: f64) -> Vec<Vec<TextSpan>> {
+    let min_x = spans.iter().map(|s| s.x).fold(f64::INFINITY, f64::min);
+    let max_x = spans.iter().map(|s| s.right()).fold(f64::NEG_INFINITY, f64::max);
+    let content_width = max_x - min_x;
+    if !content_width.is_finite() || content_width <= 0.0 || content_width > 10_000.0 {
+        return vec![spans];
+    }
+
+    // Horizontal coverage histogram in 1pt bins, ignoring page-wide spans
+    let is_narrow = |s: &TextSpan| s.right() - s.x < content_width * 0.5;
+    let bins = content_width.ceil() as usize + 1;
+    let mut coverage = vec![0usize; bins];
+    for span in spans.iter().filter(|s| is_narrow(s)) {
+        let start = (span.x - min_x).floor().max(0.0) as usize;
+        let end = ((span.right() - min_x).ceil() as usize).min(bins - 1);
+        for bin in &mut coverage[start..=end] {
+            *bin += 1;
+        }
+    }
+
+    // Interior empty runs at least one em wide are gutter candidates
+    let min_gutter = avg_font_size.max(1.0).ceil() as usize;
+    let mut gutters: Vec<f64> = Vec::new();
+    let mut run_start: Option<usize> = None;
+    for (i, &count) in coverage.iter().enumerate() {
+        match (count, run_start) {
+            (0, None) => run_start = Some(i),
+            (c, Some(start)) if c > 0 => {
+                if start > 0 && i - start >= min_gutter {
+                    gutters.push(min_x + (start + i) as f64 / 2.0);
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if gutters.is_empty() || gutters.len() > 2 {
+        return vec![spans];
+    }
+
+    let column_of = |x: f64| gutters.iter().filter(|&&g| x > g).count();
+    let mut columns: Vec<Vec<TextSpan>> = vec![Vec::new(); gutters.len() + 1];
+    let mut spanning: Vec<TextSpan> = Vec::new();
+    for span in spans.iter() {
+        if is_narrow(span) {
+            columns[column_of(span.x)].push(span.clone());
+        } else {
+            spanning.push(span.clone());
+        }
+    }
+
+    // Tables also have gutters: only accept wide columns of several prose lines
+    let looks_like_prose = |column: &[TextSpan]| {
+        let lines = cluster_into_lines(column.to_vec(), avg_font_size * 0.5);
+        let chars: usize = column.iter().map(|s| s.text.chars().count()).sum();
+        let left = column.iter().map(|s| s.x).fold(f64::INFINITY, f64::min);
+        let right = column.iter().map(|s| s.right()).fold(f64::NEG_INFINITY, f64::max);
+        lines.len() >= 3
+            && chars as f64 / lines.len() as f64 >= 20.0
+            && right - left >= content_width * 0.2
+    };
+    if !columns.iter().all(|c| looks_like_prose(c)) {
+        return vec![spans];
+    }
+
+    // Page-wide spans above the columns (titles) come first, the rest last
+    let columns_top = columns
+        .iter()
+        .flatten()
+        .map(|s| s.baseline())
+        .fold(f64::NEG_INFINITY, f64::max);
+    let (above, below): (Vec<TextSpan>, Vec<TextSpan>) =
+        spanning.into_iter().partition(|s| s.baseline() > columns_top);
+
+    let mut regions = Vec::new();
+    if !above.is_empty() {
+        regions.push(above);
+    }
+    regions.extend(columns);
+    if !below.is_empty() {
+        regions.push(below);
+    }
+    regions
 }
 
+/// Widest gap between the intervals covered by `extents`, as `(width, mid)`
+fn widest_gap(mut extents: Vec<(f64, f64)>) -> Option<(f64, f64)> {
+    extents.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mut best: Option<(f64, f64)> = None;
+    let mut covered_to = extents.first()?.1;
+    for &(start, end) in &extents[1..] {
+        let gap = start - covered_to;
+        if gap > 0.0 && best.is_none_or(|(width, _)| gap > width) {
+            best = Some((gap, covered_to + gap / 2.0));
+        }
+        covered_to = covered_to.max(end);
+    }
+    best
+}
+
+/// Recursive XY-cut: split the spans at the widest horizontal or vertical
+/// whitespace band, recursing into each side, and push the leaves in
+/// reading order (top before bottom, left before right). Vertical cuts
+/// through tables, whose rows line up across the cut, are not made.
+fn xy_cut(
+    spans: Vec<TextSpan>,
+    avg_font_size: f64,
+    row_tolerance: f64,
+    depth: usize,
+    out: &mut Vec<Vec<TextSpan>>,
+) {
+    if depth >= MAX_XY_CUT_DEPTH {
+        out.push(spans);
+        return;
+    }
+
+    // Extents are normalized: a negative font size or width flips them
+    let extent = |a: f64, b: f64| (a.min(b), a.max(b));
+    let x_gap = widest_gap(spans.iter().map(|s| extent(s.x, s.right())).collect())
+        .filter(|&(width, _)| width >= avg_font_size * 1.5);
+    let y_extents = spans
+        .iter()
+        .map(|s| extent(s.baseline(), s.baseline() + s.font_size))
+        .collect();
+    let y_gap = widest_gap(y_extents).filter(|&(width, _)| width >= avg_font_size);
+
+    let x_gap = x_gap.filter(|&(_, mid)| {
+        let (left, right): (Vec<&TextSpan>, Vec<&TextSpan>) =
+            spans.iter().partition(|s| s.x < mid);
+        let same_row = |l: &TextSpan, r: &TextSpan| {
+            (r.baseline() - l.baseline()).abs() <= row_tolerance
+        };
+        let aligned = left.iter().filter(|l| right.iter().any(|r| same_row(l, r))).count();
+        let short_lines = |side: &[&TextSpan]| {
+            let chars: usize = side.iter().map(|s| s.text.chars().count()).sum();
+            let side: Vec<TextSpan> = side.iter().map(|&s| s.clone()).collect();
+            let lines = cluster_into_lines(side, row_tolerance);
+            (chars as f64) < lines.len() as f64 * 15.0
+        };
+        aligned * 2 < left.len() || !(short_lines(&left) || short_lines(&right))
+    });
+
+    let (first, second): (Vec<TextSpan>, Vec<TextSpan>) = match (x_gap, y_gap) {
+        (Some((x_width, mid)), y) if y.is_none_or(|(y_width, _)| x_width >= y_width) => {
+            spans.into_iter().partition(|s| s.x < mid)
+        }
+        (_, Some((_, mid))) => spans.into_iter().partition(|s| s.baseline() > mid),
+        _ => (spans, Vec::new()),
+    };
+    // A cut that leaves one side empty would recurse on the same spans
+    if first.is_empty() || second.is_empty() {
+        out.push([first, second].concat());
+        return;
+    }
+    xy_cut(first, avg_font_size, row_tolerance, depth + 1, out);
+    xy_cut(second, avg_font_size, row_tolerance, depth + 1, out);
+}
+
+/// Levels of XY-cut recursion before a region is kept whole
+const MAX_XY_CUT_DEPTH: usize = 64;
+
 #[derive(Debug)]
 enum LineKind {
     Heading { level: u8 },
     TableCandidate,
+    ListItem { ordered: bool },
+    Code,
     Paragraph,
 }
 
@@ -52,13 +312,21 @@ struct ClassifiedLine {
     kind: LineKind,
     spans: Vec<TextSpan>,
     y: f64,
+    /// Left edge of the line
+    x: f64,
     text: String,
+    /// `text` split by span style
+    runs: Vec<TextRun>,
 }
 
-/// Group spans into lines by Y coordinate (same logic as table.rs cluster_into_rows)
+/// Group spans into lines by Y coordinate (same logic as table.rs cluster_into_rows).
+/// On slightly skewed pages, such as OCR'd scans, Y is measured along the
+/// dominant baseline slope so a tilted line is not split.
 fn cluster_into_lines(mut spans: Vec<TextSpan>, tolerance: f64) -> Vec<Vec<TextSpan>> {
+    let slope = estimate_baseline_slope(&spans);
+    let level = |span: &TextSpan| span.baseline() - slope * span.x;
     spans.sort_by(|a, b| {
-        b.y.partial_cmp(&a.y)
+        level(b).partial_cmp(&level(a))
             .unwrap_or(std::cmp::Ordering::Equal)
             .then(a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal))
     });
@@ -69,14 +337,14 @@ fn cluster_into_lines(mut spans: Vec<TextSpan>, tolerance: f64) -> Vec<Vec<TextS
 
     for span in spans {
         match current_y {
-            Some(y) if (span.y - y).abs() <= tolerance => {
+            Some(y) if (level(&span) - y).abs() <= tolerance => {
                 current_line.push(span);
             }
             _ => {
                 if !current_line.is_empty() {
                     lines.push(current_line);
                 }
-                current_y = Some(span.y);
+                current_y = Some(level(&span));
                 current_line = vec![span];
             }
         }
@@ -89,6 +357,36 @@ fn cluster_into_lines(mut spans: Vec<TextSpan>, tolerance: f64) -> Vec<Vec<TextS
     lines
 }
 
+/// Dominant baseline slope (dy/dx) of the page: the median slope from each
+/// span to its nearest neighbour on the right within the same line height.
+/// Slopes steeper than about 5 degrees are not skew, and a negligible
+/// result is treated as level.
+fn estimate_baseline_slope(spans: &[TextSpan]) -> f64 {
+    const MAX_SLOPE: f64 = 0.09;
+
+    let mut slopes: Vec<f64> = spans
+        .iter()
+        .filter_map(|span| {
+            spans
+                .iter()
+                .filter(|other| other.x > span.x)
+                .filter(|other| {
+                    (other.baseline() - span.baseline()).abs() < span.font_size * 0.8
+                })
+                .min_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|next| (next.baseline() - span.baseline()) / (next.x - span.x))
+        })
+        .filter(|slope| slope.abs() <= MAX_SLOPE)
+        .collect();
+    if slopes.len() < 3 {
+        return 0.0;
+    }
+
+    slopes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let median = slopes[slopes.len() / 2];
+    if median.abs() < 0.002 { 0.0 } else { median }
+}
+
 /// Compute body font size as the most frequent font size weighted by character count
 fn compute_body_font_size(lines: &[Vec<TextSpan>]) -> f64 {
     use std::collections::BTreeMap;
@@ -109,44 +407,70 @@ fn compute_body_font_size(lines: &[Vec<TextSpan>]) -> f64 {
         .unwrap_or(12.0)
 }
 
-/// Count distinct X-position clusters in a line
-fn count_x_clusters(spans: &[TextSpan]) -> usize {
+/// Count distinct X-position clusters in a line.
+/// A span starting where the previous span's measured box ends continues
+/// the same cluster, however far it is from the cluster start.
+fn count_x_clusters(spans: &[TextSpan], options: TableOptions) -> usize {
     if spans.is_empty() {
         return 0;
     }
 
-    let mut xs: Vec<f64> = spans.iter().map(|s| s.x).collect();
-    xs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mut sorted: Vec<&TextSpan> = spans.iter().collect();
+    sorted.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal));
 
-    let tolerance = 10.0;
+    let avg_font_size = spans.iter().map(|s| s.font_size).sum::<f64>() / spans.len() as f64;
+    let tolerance = options.tolerance(avg_font_size);
     let mut clusters = 1;
-    let mut last_x = xs[0];
+    let mut last_x = sorted[0].x;
+    let mut prev = sorted[0];
 
-    for &x in &xs[1..] {
-        if (x - last_x).abs() > tolerance {
+    for &span in &sorted[1..] {
+        if !continues_box(prev, span) && (span.x - last_x).abs() > tolerance {
             clusters += 1;
-            last_x = x;
+            last_x = span.x;
         }
+        prev = span;
     }
 
     clusters
 }
 
+/// Whether `next` starts within half an em of the end of `prev`'s measured box
+pub(crate) fn continues_box(prev: &TextSpan, next: &TextSpan) -> bool {
+    let gap = next.x - prev.right();
+    prev.width > 0.0 && gap.abs() < next.font_size * 0.5
+}
+
 /// Classify a single line based on font size and X-position clustering
-fn classify_line(mut spans: Vec<TextSpan>, body_font_size: f64) -> ClassifiedLine {
+fn classify_line(
+    mut spans: Vec<TextSpan>,
+    body_font_size: f64,
+    table_options: TableOptions,
+) -> ClassifiedLine {
     spans.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal));
 
-    let y = spans.iter().map(|s| s.y).sum::<f64>() / spans.len() as f64;
+    let y = spans.iter().map(|s| s.baseline()).sum::<f64>() / spans.len() as f64;
     let max_font_size = spans
         .iter()
         .map(|s| s.font_size)
         .fold(0.0_f64, f64::max);
-    let x_clusters = count_x_clusters(&spans);
+    let x_clusters = count_x_clusters(&spans, table_options);
     let text = spans
         .iter()
         .map(|s| s.text.trim().to_string())
         .collect::<Vec<_>>()
         .join(" ");
+    let mut runs = Vec::new();
+    for (i, span) in spans.iter().enumerate() {
+        if i > 0 {
+            // The joining space keeps the style of the span before it
+            match runs.last_mut() {
+                Some(TextRun { text, .. }) => text.push(' '),
+                None => push_run(&mut runs, " ", false, false),
+            }
+        }
+        push_run(&mut runs, span.text.trim(), span.bold, span.italic);
+    }
 
     let ratio = if body_font_size > 0.0 {
         max_font_size / body_font_size
@@ -165,29 +489,184 @@ fn classify_line(mut spans: Vec<TextSpan>, body_font_size: f64) -> ClassifiedLin
         LineKind::Heading { level }
     } else if x_clusters >= 3 {
         LineKind::TableCandidate
+    } else if is_monospace_line(&spans) {
+        LineKind::Code
+    } else if let Some((ordered, _)) = parse_list_marker(&text) {
+        LineKind::ListItem { ordered }
     } else {
         LineKind::Paragraph
     };
 
     ClassifiedLine {
         kind,
+        x: spans[0].x,
         spans,
         y,
         text,
+        runs,
+    }
+}
+
+/// Whether a font name belongs to a monospace family
+fn is_monospace_font(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["courier", "mono", "consolas"].iter().any(|family| name.contains(family))
+}
+
+/// Average advance per character of a span with a measured width
+fn char_advance(span: &TextSpan) -> Option<f64> {
+    let chars = span.text.chars().count();
+    (span.width > 0.0 && chars > 0).then(|| span.width / chars as f64)
+}
+
+/// A line is monospace when all its spans are set in fixed-pitch fonts:
+/// fonts flagged or measured as such, or named after a monospace family.
+/// Advances estimated for fonts without widths are not used, as they are
+/// the same for every glyph.
+fn is_monospace_line(spans: &[TextSpan]) -> bool {
+    spans
+        .iter()
+        .all(|s| s.monospace || s.font_name.as_deref().is_some_and(is_monospace_font))
+}
+
+/// Rebuild a monospace line, turning its indent from `left` and the gaps
+/// between spans into spaces of one character advance each
+fn code_line_text(spans: &[TextSpan], left: f64) -> String {
+    let advance = spans
+        .iter()
+        .find_map(char_advance)
+        .unwrap_or_else(|| spans[0].font_size * 0.6)
+        .max(0.1);
+    let mut text = String::new();
+    let mut cursor = left;
+    for span in spans {
+        let spaces = ((span.x - cursor) / advance).round().max(0.0) as usize;
+        let spaces = if text.is_empty() { spaces } else { spaces.max(1) };
+        text.push_str(&" ".repeat(spaces));
+        text.push_str(&span.text);
+        cursor = span.x + char_advance(span).unwrap_or(advance) * span.text.chars().count() as f64;
+    }
+    text
+}
+
+/// Split a leading list marker off a line: bullets (`•`, `◦`, `-`, `*`) or
+/// enumerators (`1.`, `a)`, `iv.`). Returns whether the list is ordered and
+/// the item text.
+fn parse_list_marker(text: &str) -> Option<(bool, &str)> {
+    let (marker, rest) = text.split_once(' ')?;
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+
+    if matches!(marker, "\u{2022}" | "\u{25e6}" | "\u{25aa}" | "\u{2023}" | "-" | "*") {
+        return Some((false, rest));
+    }
+
+    let label = marker
+        .strip_suffix('.')
+        .or_else(|| marker.strip_suffix(')'))?;
+    let is_number =
+        !label.is_empty() && label.len() <= 3 && label.chars().all(|c| c.is_ascii_digit());
+    let is_letter = label.len() == 1 && label.chars().all(|c| c.is_ascii_lowercase());
+    let is_roman = matches!(
+        label,
+        "i" | "ii" | "iii" | "iv" | "v" | "vi" | "vii" | "viii" | "ix" | "x"
+    );
+
+    if is_number || is_letter || is_roman {
+        Some((true, rest))
+    } else {
+        None
+    }
+}
+
+/// Append a wrapped line to the text of a paragraph or list item. A soft
+/// hyphen at the end of the text always joins the words; a hard hyphen
+/// after a letter joins them without a space, and is dropped before a
+/// lowercase letter when `dehyphenate` is set.
+fn append_wrapped_line(text: &mut String, line: &str, dehyphenate: bool) {
+    match wrap_join(text, line, dehyphenate) {
+        WrapJoin::DropLast => {
+            text.pop();
+        }
+        WrapJoin::Space => text.push(' '),
+        WrapJoin::Direct => {}
+    }
+    text.push_str(line);
+}
+
+/// Like [`append_wrapped_line`] for styled runs, where `text` is the runs
+/// joined so far
+fn append_wrapped_runs(
+    runs: &mut Vec<TextRun>,
+    text: &str,
+    line: &ClassifiedLine,
+    dehyphenate: bool,
+) {
+    match wrap_join(text, &line.text, dehyphenate) {
+        WrapJoin::DropLast => {
+            if let Some(last) = runs.last_mut() {
+                last.text.pop();
+            }
+        }
+        WrapJoin::Space => {
+            if let Some(last) = runs.last_mut() {
+                last.text.push(' ');
+            }
+        }
+        WrapJoin::Direct => {}
+    }
+    for run in &line.runs {
+        push_run(runs, &run.text, run.bold, run.italic);
+    }
+}
+
+/// How a wrapped line attaches to the text before it
+enum WrapJoin {
+    /// Drop the hyphen ending the text
+    DropLast,
+    Space,
+    Direct,
+}
+
+fn wrap_join(text: &str, line: &str, dehyphenate: bool) -> WrapJoin {
+    if text.is_empty() {
+        return WrapJoin::Direct;
+    }
+
+    let hyphenated = text.ends_with('-')
+        && text.chars().rev().nth(1).is_some_and(char::is_alphabetic);
+    if text.ends_with('\u{AD}') {
+        WrapJoin::DropLast
+    } else if hyphenated {
+        if dehyphenate && line.chars().next().is_some_and(char::is_lowercase) {
+            WrapJoin::DropLast
+        } else {
+            WrapJoin::Direct
+        }
+    } else {
+        WrapJoin::Space
     }
 }
 
 /// Merge consecutive classified lines into page elements
-fn merge_lines(lines: Vec<ClassifiedLine>, body_font_size: f64) -> Vec<PageElement> {
+fn merge_lines(
+    lines: Vec<ClassifiedLine>,
+    body_font_size: f64,
+    options: LayoutOptions,
+) -> Vec<PageElement> {
     let mut elements: Vec<PageElement> = Vec::new();
     let mut i = 0;
 
     while i < lines.len() {
+        let gap_before = if i > 0 { (lines[i - 1].y - lines[i].y).abs() } else { 0.0 };
         match &lines[i].kind {
             LineKind::Heading { level } => {
                 elements.push(PageElement::Heading {
                     level: *level,
                     text: lines[i].text.clone(),
+                    gap_before,
                 });
                 i += 1;
             }
@@ -205,40 +684,105 @@ fn merge_lines(lines: Vec<ClassifiedLine>, body_font_size: f64) -> Vec<PageEleme
                         .iter()
                         .flat_map(|l| l.spans.clone())
                         .collect();
-                    let table = Table::from_spans(all_spans);
-                    elements.push(PageElement::Table { table });
+                    let table = Table::from_spans_with(all_spans, options.table);
+                    elements.push(PageElement::Table { table, gap_before });
                 } else {
                     // Single table-candidate line: check column count
-                    let x_clusters = count_x_clusters(&lines[start].spans);
+                    let x_clusters = count_x_clusters(&lines[start].spans, options.table);
                     if x_clusters >= 4 {
-                        let table = Table::from_spans(lines[start].spans.clone());
-                        elements.push(PageElement::Table { table });
+                        let spans = lines[start].spans.clone();
+                        let table = Table::from_spans_with(spans, options.table);
+                        elements.push(PageElement::Table { table, gap_before });
                     } else {
                         elements.push(PageElement::Paragraph {
                             text: lines[start].text.clone(),
+                            runs: lines[start].runs.clone(),
+                            gap_before,
                         });
                     }
                 }
             }
+            LineKind::ListItem { ordered } => {
+                // Collect items at the same indent; deeper-indented plain
+                // lines continue the previous (wrapped) item
+                let ordered = *ordered;
+                let indent = lines[i].x;
+                let mut items: Vec<String> = Vec::new();
+                let mut prev_y = lines[i].y;
+
+                while i < lines.len() {
+                    let line = &lines[i];
+                    if !items.is_empty() && (prev_y - line.y).abs() > body_font_size * 1.5 {
+                        break;
+                    }
+                    match line.kind {
+                        LineKind::ListItem { ordered: o }
+                            if o == ordered && (line.x - indent).abs() <= 5.0 =>
+                        {
+                            let item = parse_list_marker(&line.text).map_or("", |(_, t)| t);
+                            items.push(item.to_string());
+                        }
+                        LineKind::Paragraph if line.x > indent + 5.0 => {
+                            if let Some(last) = items.last_mut() {
+                                append_wrapped_line(last, &line.text, options.dehyphenate);
+                            }
+                        }
+                        _ => break,
+                    }
+                    prev_y = line.y;
+                    i += 1;
+                }
+
+                let items = items.into_iter().map(|item| item.replace('\u{AD}', "")).collect();
+                elements.push(PageElement::List { ordered, items, gap_before });
+            }
+            LineKind::Code => {
+                // Collect consecutive code lines, keeping one blank line
+                // where the listing has a vertical gap
+                let start = i;
+                while i < lines.len() && matches!(lines[i].kind, LineKind::Code) {
+                    i += 1;
+                }
+                let block = &lines[start..i];
+                let left = block.iter().map(|l| l.x).fold(f64::INFINITY, f64::min);
+                let mut text = String::new();
+                for (j, line) in block.iter().enumerate() {
+                    if j > 0 {
+                        text.push('\n');
+                        let font_size = line.spans[0].font_size;
+                        if (block[j - 1].y - line.y).abs() > font_size.max(1.0) * 1.8 {
+                            text.push('\n');
+                        }
+                    }
+                    text.push_str(code_line_text(&line.spans, left).trim_end());
+                }
+                elements.push(PageElement::CodeBlock { text, gap_before });
+            }
             LineKind::Paragraph => {
                 // Collect consecutive paragraph lines
-                let mut paragraph_parts: Vec<String> = Vec::new();
+                let mut text = String::new();
+                let mut runs = Vec::new();
                 let mut prev_y = lines[i].y;
 
                 while i < lines.len() && matches!(lines[i].kind, LineKind::Paragraph) {
                     let gap = (prev_y - lines[i].y).abs();
                     // Large Y-gap means paragraph break (> 1.5x body font size)
-                    if !paragraph_parts.is_empty() && gap > body_font_size * 1.5 {
+                    if !text.is_empty() && gap > body_font_size * 1.5 {
                         break;
                     }
-                    paragraph_parts.push(lines[i].text.clone());
+                    append_wrapped_runs(&mut runs, &text, &lines[i], options.dehyphenate);
+                    append_wrapped_line(&mut text, &lines[i].text, options.dehyphenate);
                     prev_y = lines[i].y;
                     i += 1;
                 }
 
-                let text = paragraph_parts.join(" ");
+                // Remaining soft hyphens are invisible break hints
+                let text = text.replace('\u{AD}', "");
+                for run in &mut runs {
+                    run.text = run.text.replace('\u{AD}', "");
+                }
                 if !text.trim().is_empty() {
-                    elements.push(PageElement::Paragraph { text });
+                    elements.push(PageElement::Paragraph { text, runs, gap_before });
                 }
             }
         }
@@ -258,9 +802,36 @@ mod tests {
             y,
             font_size,
             font_name: None,
+            rise: 0.0,
+            width: 0.0,
+            height: 0.0,
+            color: None,
+            bold: false,
+            italic: false,
+            monospace: false,
         }
     }
 
+    #[test]
+    fn test_skewed_baselines_stay_one_line() {
+        // Both lines rise 1pt every 20pt, as on a slightly rotated scan
+        let mut spans = Vec::new();
+        for (i, word) in ["Scanned", "text", "drifts", "upward"].iter().enumerate() {
+            let x = 50.0 + i as f64 * 100.0;
+            spans.push(make_span(word, x, 700.0 + x / 20.0, 12.0));
+            spans.push(make_span(word, x, 680.0 + x / 20.0, 12.0));
+        }
+
+        let lines = cluster_into_lines(spans.clone(), 6.0);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() == 4));
+        assert!(lines[0].iter().all(|s| s.y > 700.0));
+
+        // Level text is left alone
+        let level: Vec<_> = spans.iter().map(|s| TextSpan { y: 700.0, ..s.clone() }).collect();
+        assert_eq!(estimate_baseline_slope(&level), 0.0);
+    }
+
     #[test]
     fn test_heading_detection() {
         // Large font = heading, normal font = paragraph
@@ -271,8 +842,14 @@ mod tests {
 
         let elements = classify_spans(spans);
         assert_eq!(elements.len(), 2);
-        assert!(matches!(&elements[0], PageElement::Heading { level: 1, text } if text == "Title"));
-        assert!(matches!(&elements[1], PageElement::Paragraph { text } if text == "Normal text here."));
+        assert!(matches!(
+            &elements[0],
+            PageElement::Heading { level: 1, text, .. } if text == "Title"
+        ));
+        assert!(matches!(
+            &elements[1],
+            PageElement::Paragraph { text, .. } if text == "Normal text here."
+        ));
     }
 
     #[test]
@@ -304,7 +881,7 @@ mod tests {
         let elements = classify_spans(spans);
         assert_eq!(elements.len(), 1);
         assert!(matches!(&elements[0], PageElement::Paragraph { .. }));
-        if let PageElement::Paragraph { text } = &elements[0] {
+        if let PageElement::Paragraph { text, .. } = &elements[0] {
             assert!(text.contains("First line"));
             assert!(text.contains("third line"));
         }
@@ -331,6 +908,8 @@ mod tests {
         assert!(matches!(&elements[0], PageElement::Heading { .. }));
         assert!(matches!(&elements[1], PageElement::Paragraph { .. }));
         assert!(matches!(&elements[2], PageElement::Table { .. }));
+        let gaps: Vec<f64> = elements.iter().map(PageElement::gap_before).collect();
+        assert_eq!(gaps[..3], [0.0, 40.0, 30.0]);
     }
 
     #[test]
@@ -359,6 +938,253 @@ mod tests {
             make_span("C", 200.0, 500.0, 12.0),
             make_span("D", 350.0, 500.0, 12.0),
         ];
-        assert_eq!(count_x_clusters(&spans), 3);
+        assert_eq!(count_x_clusters(&spans, TableOptions::default()), 3);
+    }
+
+    #[test]
+    fn test_measured_spans_continue_cluster() {
+        // Three font runs of one sentence would otherwise look like 3 columns
+        let runs = [("The quick", 100.0, 50.0), ("brown", 153.0, 30.0), ("fox", 186.0, 18.0)];
+        let spans: Vec<TextSpan> = runs
+            .iter()
+            .map(|&(text, x, width)| TextSpan {
+                width,
+                ..make_span(text, x, 700.0, 12.0)
+            })
+            .collect();
+
+        let elements = classify_spans(spans);
+        assert_eq!(elements.len(), 1);
+        assert!(matches!(&elements[0], PageElement::Paragraph { .. }));
+    }
+
+    #[test]
+    fn test_paragraph_keeps_inline_styles() {
+        let styled = |text: &str, x: f64, y: f64, width: f64, bold: bool, italic: bool| {
+            TextSpan { width, bold, italic, ..make_span(text, x, y, 12.0) }
+        };
+        let spans = vec![
+            styled("Net income was", 50.0, 700.0, 80.0, false, false),
+            styled("negative", 133.0, 700.0, 50.0, true, false),
+            styled("this", 186.0, 700.0, 21.0, false, false),
+            styled("quarter, see", 50.0, 686.0, 66.0, false, false),
+            styled("Note 4.", 119.0, 686.0, 38.0, false, true),
+        ];
+
+        let elements = classify_spans(spans);
+        let [PageElement::Paragraph { text, runs, .. }] = elements.as_slice() else {
+            panic!("expected one paragraph, got {:?}", elements);
+        };
+        assert_eq!(text, "Net income was negative this quarter, see Note 4.");
+        assert_eq!(runs.iter().map(|r| r.text.as_str()).collect::<String>(), *text);
+        let styled: Vec<_> = runs
+            .iter()
+            .filter(|r| r.is_styled())
+            .map(|r| (r.text.trim(), r.bold, r.italic))
+            .collect();
+        assert_eq!(styled, vec![("negative", true, false), ("Note 4.", false, true)]);
+    }
+
+    #[test]
+    fn test_two_column_layout() {
+        let mut spans = vec![make_span("A Study of Two Column Layouts", 50.0, 760.0, 20.0)];
+        for (i, y) in [700.0, 686.0, 672.0, 658.0].iter().enumerate() {
+            let left = format!("Left column sentence number {} goes", i);
+            let right = format!("Right column sentence number {} goes", i);
+            spans.push(make_span(&left, 50.0, *y, 10.0));
+            spans.push(make_span(&right, 320.0, *y, 10.0));
+        }
+
+        let elements = classify_spans(spans);
+        let texts: Vec<&str> = elements
+            .iter()
+            .map(|e| match e {
+                PageElement::Heading { text, .. } => text.as_str(),
+                PageElement::Paragraph { text, .. } => text.as_str(),
+                _ => "<other>",
+            })
+            .collect();
+
+        assert_eq!(texts.len(), 3);
+        assert_eq!(texts[0], "A Study of Two Column Layouts");
+        assert!(texts[1].starts_with("Left column sentence number 0"));
+        assert!(texts[1].contains("number 3") && !texts[1].contains("Right"));
+        assert!(texts[2].starts_with("Right column sentence number 0"));
+    }
+
+    #[test]
+    fn test_list_detection() {
+        let spans = vec![
+            make_span("Requirements:", 50.0, 700.0, 12.0),
+            make_span("\u{2022} Rust 1.85 or newer", 50.0, 686.0, 12.0),
+            make_span("\u{2022} A PDF file whose text is long enough", 50.0, 672.0, 12.0),
+            make_span("to wrap onto a second line", 62.0, 658.0, 12.0),
+            make_span("1. Build", 50.0, 630.0, 12.0),
+            make_span("2. Run", 50.0, 616.0, 12.0),
+        ];
+
+        let elements = classify_spans(spans);
+        assert_eq!(elements.len(), 3);
+        assert!(matches!(&elements[0], PageElement::Paragraph { .. }));
+        match &elements[1] {
+            PageElement::List { ordered, items, .. } => {
+                assert!(!ordered);
+                assert_eq!(items[0], "Rust 1.85 or newer");
+                assert_eq!(
+                    items[1],
+                    "A PDF file whose text is long enough to wrap onto a second line"
+                );
+            }
+            other => panic!("expected list, got {:?}", other),
+        }
+        assert!(matches!(
+            &elements[2],
+            PageElement::List { ordered: true, items, .. } if items.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_list_markers() {
+        assert_eq!(parse_list_marker("a) first"), Some((true, "first")));
+        assert_eq!(parse_list_marker("iv. fourth"), Some((true, "fourth")));
+        assert_eq!(parse_list_marker("- dash"), Some((false, "dash")));
+        assert_eq!(parse_list_marker("2024. A year"), None);
+        assert_eq!(parse_list_marker("Mr. Smith"), None);
+        assert_eq!(parse_list_marker("\u{2022}"), None);
+    }
+
+    fn paragraph_text(spans: Vec<TextSpan>, options: LayoutOptions) -> String {
+        match classify_spans_with_options(spans, options).as_slice() {
+            [PageElement::Paragraph { text, .. }] => text.clone(),
+            other => panic!("expected one paragraph, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_dehyphenation() {
+        let spans = vec![
+            make_span("An inter-", 50.0, 500.0, 12.0),
+            make_span("national treaty was sign\u{AD}", 50.0, 486.0, 12.0),
+            make_span("ed in Ge\u{AD}neva", 50.0, 472.0, 12.0),
+        ];
+        assert_eq!(
+            paragraph_text(spans, LayoutOptions::default()),
+            "An international treaty was signed in Geneva"
+        );
+    }
+
+    #[test]
+    fn test_dehyphenation_disabled_keeps_hyphen() {
+        let spans = vec![
+            make_span("a well-", 50.0, 500.0, 12.0),
+            make_span("known result", 50.0, 486.0, 12.0),
+        ];
+        assert_eq!(
+            paragraph_text(spans, LayoutOptions { dehyphenate: false, ..Default::default() }),
+            "a well-known result"
+        );
+    }
+
+    #[test]
+    fn test_dehyphenation_keeps_hyphen_before_capital() {
+        let spans = vec![
+            make_span("the Franco-", 50.0, 500.0, 12.0),
+            make_span("Prussian war", 50.0, 486.0, 12.0),
+        ];
+        assert_eq!(
+            paragraph_text(spans, LayoutOptions::default()),
+            "the Franco-Prussian war"
+        );
+    }
+
+    #[test]
+    fn test_code_block() {
+        let code = |text: &str, x: f64, y: f64| TextSpan {
+            font_name: Some("Courier".to_string()),
+            width: text.chars().count() as f64 * 6.0,
+            ..make_span(text, x, y, 10.0)
+        };
+        let spans = vec![
+            make_span("Run the following program:", 50.0, 700.0, 10.0),
+            code("fn main() {", 50.0, 680.0),
+            code("println!(\"hi\");", 74.0, 668.0),
+            code("}", 50.0, 656.0),
+            code("main();", 50.0, 632.0),
+            make_span("It prints a greeting.", 50.0, 610.0, 10.0),
+        ];
+
+        let elements = classify_spans(spans);
+        assert_eq!(elements.len(), 3);
+        assert!(matches!(&elements[0], PageElement::Paragraph { .. }));
+        assert!(matches!(
+            &elements[1],
+            PageElement::CodeBlock { text, .. }
+                if text == "fn main() {\n    println!(\"hi\");\n}\n\nmain();"
+        ));
+        assert!(matches!(&elements[2], PageElement::Paragraph { .. }));
+    }
+
+    #[test]
+    fn test_xy_cut_keeps_sidebar_apart() {
+        let mut spans = Vec::new();
+        for i in 0..8 {
+            let y = 700.0 - i as f64 * 12.0;
+            let text = format!("Main column line {} of the article", i);
+            spans.push(make_span(&text, 50.0, y, 10.0));
+        }
+        for i in 0..4 {
+            // Sidebar lines fall between the main lines
+            let y = 694.0 - i as f64 * 14.0;
+            spans.push(make_span(&format!("Note {}", i), 400.0, y, 10.0));
+        }
+
+        let options = LayoutOptions { xy_cut: true, ..Default::default() };
+        let elements = classify_spans_with_options(spans.clone(), options);
+        let texts: Vec<&str> = elements
+            .iter()
+            .map(|e| match e {
+                PageElement::Paragraph { text, .. } => text.as_str(),
+                _ => "<other>",
+            })
+            .collect();
+        assert_eq!(texts.len(), 2);
+        assert!(texts[0].starts_with("Main column line 0") && !texts[0].contains("Note"));
+        assert!(texts[0].ends_with("line 7 of the article"));
+        assert_eq!(texts[1], "Note 0 Note 1 Note 2 Note 3");
+
+        // The flat ordering interleaves the two blocks
+        let elements = classify_spans(spans);
+        assert!(matches!(
+            &elements[0],
+            PageElement::Paragraph { text, .. } if text.contains("Note 0")
+        ));
+    }
+
+    #[test]
+    fn test_xy_cut_negative_font_size() {
+        // `-12 Tf` flips the vertical extents of every span
+        let span = |text: &str, x: f64, y: f64| TextSpan {
+            width: 30.0,
+            ..make_span(text, x, y, -12.0)
+        };
+        let spans = vec![span("Hello", 50.0, 700.0), span("world", 82.0, 700.0)];
+        let options = LayoutOptions { xy_cut: true, ..Default::default() };
+        assert_eq!(paragraph_text(spans, options), "Hello world");
+    }
+
+    #[test]
+    fn test_xy_cut_keeps_tables_whole() {
+        let mut spans = Vec::new();
+        let rows = [["Date", "Amount"], ["01/02", "10.00"], ["03/04", "25.50"]];
+        for (i, row) in rows.iter().enumerate() {
+            let y = 700.0 - i as f64 * 14.0;
+            spans.push(make_span(row[0], 50.0, y, 10.0));
+            spans.push(make_span(row[1], 200.0, y, 10.0));
+            spans.push(make_span("x", 350.0, y, 10.0));
+        }
+        let options = LayoutOptions { xy_cut: true, ..Default::default() };
+        let elements = classify_spans_with_options(spans, options);
+        assert_eq!(elements.len(), 1);
+        assert!(matches!(&elements[0], PageElement::Table { table, .. } if table.rows.len() == 3));
     }
 }
@@ -1,4 +1,4 @@
-use crate::extract::layout::PageElement;
+use crate::extract::layout::{PageElement, TextRun};
 use crate::extract::Table;
 
 /// Render page elements as Markdown.
@@ -7,21 +7,41 @@ pub fn elements_to_markdown(elements: &[PageElement]) -> String {
 
     for element in elements {
         match element {
-            PageElement::Heading { level, text } => {
+            PageElement::Heading { level, text, .. } => {
                 let prefix = "#".repeat(*level as usize);
                 out.push_str(&prefix);
                 out.push(' ');
                 out.push_str(text);
                 out.push_str("\n\n");
             }
-            PageElement::Paragraph { text } => {
-                out.push_str(text);
+            PageElement::Paragraph { text, runs, .. } => {
+                if runs.iter().any(TextRun::is_styled) {
+                    out.push_str(&styled_markdown(runs));
+                } else {
+                    out.push_str(text);
+                }
                 out.push_str("\n\n");
             }
-            PageElement::Table { table } => {
+            PageElement::Table { table, .. } => {
                 out.push_str(&table_to_markdown(table));
                 out.push_str("\n\n");
             }
+            PageElement::List { ordered, items, .. } => {
+                for (i, item) in items.iter().enumerate() {
+                    if *ordered {
+                        out.push_str(&format!("{}. {}\n", i + 1, item));
+                    } else {
+                        out.push_str(&format!("- {}\n", item));
+                    }
+                }
+                out.push('\n');
+            }
+            PageElement::CodeBlock { text, .. } => {
+                // The fence must be longer than any backtick run inside
+                let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+                let fence = "`".repeat(longest_run.max(2) + 1);
+                out.push_str(&format!("{}\n{}\n{}\n\n", fence, text, fence));
+            }
         }
     }
 
@@ -33,6 +53,32 @@ pub fn elements_to_markdown(elements: &[PageElement]) -> String {
     }
 }
 
+/// Join runs, wrapping bold ones in `**` and italic ones in `*`. Whitespace
+/// at the edges of a run stays outside the markers.
+fn styled_markdown(runs: &[TextRun]) -> String {
+    let mut out = String::new();
+    for run in runs {
+        let marker = match (run.bold, run.italic) {
+            (true, true) => "***",
+            (true, false) => "**",
+            (false, true) => "*",
+            (false, false) => "",
+        };
+        let inner = run.text.trim();
+        if marker.is_empty() || inner.is_empty() {
+            out.push_str(&run.text);
+            continue;
+        }
+        let start = run.text.len() - run.text.trim_start().len();
+        out.push_str(&run.text[..start]);
+        out.push_str(marker);
+        out.push_str(inner);
+        out.push_str(marker);
+        out.push_str(&run.text[start + inner.len()..]);
+    }
+    out
+}
+
 /// Convert a Table to a Markdown table string.
 fn table_to_markdown(table: &Table) -> String {
     if table.rows.is_empty() {
@@ -40,10 +86,12 @@ fn table_to_markdown(table: &Table) -> String {
     }
 
     let mut out = String::new();
+    // Markdown cannot merge cells, so spanning text is repeated
+    let rows = table.spanned_rows();
 
     // Compute column widths for alignment
     let mut widths: Vec<usize> = vec![3; table.num_columns]; // minimum width 3 for "---"
-    for row in &table.rows {
+    for row in &rows {
         for (i, cell) in row.iter().enumerate() {
             if i < widths.len() {
                 let escaped_len = escape_pipe(cell).chars().count();
@@ -52,10 +100,12 @@ fn table_to_markdown(table: &Table) -> String {
         }
     }
 
-    // Header row
-    let header = &table.rows[0];
-    out.push_str(&format_md_row(header, &widths));
-    out.push('\n');
+    // Header rows
+    let header_rows = table.header_rows.clamp(1, rows.len());
+    for row in &rows[..header_rows] {
+        out.push_str(&format_md_row(row, &widths));
+        out.push('\n');
+    }
 
     // Separator row
     let sep: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
@@ -68,7 +118,7 @@ fn table_to_markdown(table: &Table) -> String {
     out.push('\n');
 
     // Data rows
-    for row in table.rows.iter().skip(1) {
+    for row in rows.iter().skip(header_rows) {
         out.push_str(&format_md_row(row, &widths));
         out.push('\n');
     }
@@ -85,8 +135,7 @@ fn format_md_row(row: &[String], widths: &[usize]) -> String {
         out.push_str(&format!(" {:<width$} |", escaped, width = width));
     }
     // Pad missing columns
-    for i in row.len()..widths.len() {
-        let width = widths[i];
+    for &width in widths.iter().skip(row.len()) {
         out.push_str(&format!(" {:<width$} |", "", width = width));
     }
     out
@@ -106,14 +155,17 @@ mod tests {
             PageElement::Heading {
                 level: 1,
                 text: "Title".to_string(),
+                gap_before: 0.0,
             },
             PageElement::Heading {
                 level: 2,
                 text: "Subtitle".to_string(),
+                gap_before: 0.0,
             },
             PageElement::Heading {
                 level: 3,
                 text: "Section".to_string(),
+                gap_before: 0.0,
             },
         ];
 
@@ -127,11 +179,34 @@ mod tests {
     fn test_paragraph() {
         let elements = vec![PageElement::Paragraph {
             text: "Hello world.".to_string(),
+            runs: Vec::new(),
+            gap_before: 0.0,
         }];
         let md = elements_to_markdown(&elements);
         assert_eq!(md, "Hello world.\n");
     }
 
+    #[test]
+    fn test_paragraph_inline_styles() {
+        let run = |text: &str, bold: bool, italic: bool| TextRun {
+            text: text.to_string(),
+            bold,
+            italic,
+        };
+        let elements = vec![PageElement::Paragraph {
+            text: "Net income was negative, see Note 4.".to_string(),
+            runs: vec![
+                run("Net income was ", false, false),
+                run("negative", true, false),
+                run(", see ", false, false),
+                run("Note 4. ", true, true),
+            ],
+            gap_before: 0.0,
+        }];
+        let md = elements_to_markdown(&elements);
+        assert_eq!(md, "Net income was **negative**, see ***Note 4.***\n");
+    }
+
     #[test]
     fn test_markdown_table() {
         let table = Table {
@@ -140,9 +215,11 @@ mod tests {
                 vec!["Alice".to_string(), "30".to_string()],
             ],
             num_columns: 2,
+            cells: Vec::new(),
+            header_rows: 1,
         };
 
-        let elements = vec![PageElement::Table { table }];
+        let elements = vec![PageElement::Table { table, gap_before: 0.0 }];
         let md = elements_to_markdown(&elements);
         assert!(md.contains("| Name"));
         assert!(md.contains("| ---"));
@@ -169,17 +246,22 @@ mod tests {
                 vec!["A".to_string(), "B".to_string()],
             ],
             num_columns: 2,
+            cells: Vec::new(),
+            header_rows: 1,
         };
 
         let elements = vec![
             PageElement::Heading {
                 level: 1,
                 text: "Report".to_string(),
+                gap_before: 0.0,
             },
             PageElement::Paragraph {
                 text: "Summary of data.".to_string(),
+                runs: Vec::new(),
+                gap_before: 0.0,
             },
-            PageElement::Table { table },
+            PageElement::Table { table, gap_before: 0.0 },
         ];
 
         let md = elements_to_markdown(&elements);
@@ -187,4 +269,55 @@ mod tests {
         assert!(md.contains("Summary of data."));
         assert!(md.contains("| Col1"));
     }
+
+    #[test]
+    fn test_lists() {
+        let elements = vec![
+            PageElement::List {
+                ordered: false,
+                items: vec!["Apples".to_string(), "Pears".to_string()],
+                gap_before: 0.0,
+            },
+            PageElement::List {
+                ordered: true,
+                items: vec!["Wash".to_string(), "Eat".to_string()],
+                gap_before: 0.0,
+            },
+        ];
+        let md = elements_to_markdown(&elements);
+        assert_eq!(md, "- Apples\n- Pears\n\n1. Wash\n2. Eat\n");
+    }
+
+    #[test]
+    fn test_multi_row_header() {
+        let table = Table {
+            rows: vec![
+                vec!["Region".to_string(), "2024".to_string()],
+                vec!["".to_string(), "(USD)".to_string()],
+                vec!["North".to_string(), "10".to_string()],
+            ],
+            num_columns: 2,
+            cells: Vec::new(),
+            header_rows: 2,
+        };
+        let md = table_to_markdown(&table);
+        let lines: Vec<&str> = md.lines().collect();
+        assert!(lines[2].starts_with("| ---"));
+        assert!(lines[3].starts_with("| North"));
+    }
+
+    #[test]
+    fn test_code_block_fence() {
+        let elements = vec![PageElement::CodeBlock {
+            text: "fn main() {\n    run();\n}".to_string(),
+            gap_before: 0.0,
+        }];
+        assert_eq!(elements_to_markdown(&elements), "```\nfn main() {\n    run();\n}\n```\n");
+
+        let elements = vec![PageElement::CodeBlock {
+            text: "let s = \"```\";".to_string(),
+            gap_before: 0.0,
+        }];
+        assert!(elements_to_markdown(&elements).starts_with("````\n"));
+    }
 }
@@ -1,9 +1,19 @@
+pub mod html;
+#[cfg(feature = "serde")]
+pub mod json;
 pub mod layout;
 pub mod markdown;
 mod table;
+pub mod tsv;
 pub mod txt;
 
-pub use layout::{classify_spans, PageElement};
+pub use html::elements_to_html;
+#[cfg(feature = "serde")]
+pub use json::{pages_to_json, spans_to_json};
+pub use layout::{
+    classify_spans, classify_spans_with_options, LayoutOptions, PageElement, TextRun,
+};
 pub use markdown::elements_to_markdown;
-pub use table::Table;
+pub use table::{ColumnType, Table, TableCell, TableOptions};
+pub use tsv::{elements_to_csv, elements_to_tsv};
 pub use txt::elements_to_txt;
@@ -1,15 +1,88 @@
+use std::collections::HashMap;
+
 use crate::content::TextSpan;
+use crate::extract::layout::continues_box;
 
 /// Extracted table with rows and columns
 #[derive(Debug, Clone)]
 pub struct Table {
     pub rows: Vec<Vec<String>>,
     pub num_columns: usize,
+    /// Positioned cells parallel to `rows`; empty when the table was not
+    /// built from spans
+    pub cells: Vec<Vec<TableCell>>,
+    /// Number of leading rows that form the header (at least 1 when the
+    /// table has rows)
+    pub header_rows: usize,
+}
+
+/// Settings for building a [`Table`] from spans
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableOptions {
+    /// How far apart span edges may be, as a fraction of the average font
+    /// size, and still line up in one column
+    pub column_tolerance_ratio: f64,
+    /// Fixed column tolerance in PDF units, used instead of the ratio
+    pub column_tolerance: Option<f64>,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        // 10 units for 12pt text
+        Self { column_tolerance_ratio: 10.0 / 12.0, column_tolerance: None }
+    }
+}
+
+impl TableOptions {
+    /// Column tolerance for text of the given average font size; text
+    /// without a usable size is treated as 12pt
+    pub(crate) fn tolerance(&self, avg_font_size: f64) -> f64 {
+        let font_size = if avg_font_size > 0.0 { avg_font_size } else { 12.0 };
+        self.column_tolerance.unwrap_or(font_size * self.column_tolerance_ratio)
+    }
+}
+
+/// A table cell with the position of its text
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableCell {
+    pub text: String,
+    /// Left edge of the cell's text, or the column position when empty
+    pub x: f64,
+    /// Baseline of the cell's row
+    pub y: f64,
+    /// Number of columns this cell covers: more than 1 for a merged or
+    /// spanning cell, whose text overlaps the following columns while their
+    /// cells in this row are empty. Covered cells stay empty in
+    /// [`Table::rows`].
+    pub col_span_hint: usize,
+}
+
+/// Kind of values held in a table column, from [`Table::infer_column_types`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Integer,
+    Float,
+    /// Amounts with a currency symbol, e.g. `$1,200.00` or `45 €`
+    Currency,
+    Date,
+    Text,
+}
+
+impl ColumnType {
+    /// Whether values of this type are numbers
+    pub fn is_numeric(self) -> bool {
+        matches!(self, ColumnType::Integer | ColumnType::Float | ColumnType::Currency)
+    }
 }
 
 impl Table {
     /// Build a table from text spans
     pub fn from_spans(spans: Vec<TextSpan>) -> Self {
+        Self::from_spans_with(spans, TableOptions::default())
+    }
+
+    /// Build a table from text spans with custom options
+    pub fn from_spans_with(spans: Vec<TextSpan>, options: TableOptions) -> Self {
         // Filter empty spans
         let spans: Vec<_> = spans
             .into_iter()
@@ -20,6 +93,8 @@ impl Table {
             return Table {
                 rows: Vec::new(),
                 num_columns: 0,
+                cells: Vec::new(),
+                header_rows: 0,
             };
         }
 
@@ -30,27 +105,144 @@ impl Table {
         // Cluster into rows by Y coordinate
         let mut rows = cluster_into_rows(spans, row_tolerance);
 
-        // Sort within each row by X coordinate
+        // Sort within each row by X coordinate, joining spans whose boxes abut
         for row in &mut rows {
             row.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal));
+            *row = join_continuations(std::mem::take(row));
         }
 
-        // Detect column boundaries
-        let columns = detect_columns(&rows);
+        // Detect column boundaries and header rows
+        let columns = detect_columns(&rows, options.tolerance(avg_font_size));
+        let header_rows = detect_header_rows(&rows);
 
-        // Assign spans to grid cells
-        let grid = assign_to_columns(rows, &columns);
+        // Assign spans to grid cells, then flatten to text
+        let cells = assign_to_columns(rows, &columns);
+        let rows = cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.text.clone()).collect())
+            .collect();
 
         Table {
             num_columns: columns.len(),
-            rows: grid,
+            rows,
+            cells,
+            header_rows,
+        }
+    }
+
+    /// Rows with the text of each spanning cell repeated in every column it
+    /// covers, for formats that cannot merge cells
+    pub fn spanned_rows(&self) -> Vec<Vec<String>> {
+        if self.cells.len() != self.rows.len() {
+            return self.rows.clone();
+        }
+        self.cells
+            .iter()
+            .map(|row| {
+                let mut texts = Vec::with_capacity(row.len());
+                let mut i = 0;
+                while i < row.len() {
+                    let span = row[i].col_span_hint.clamp(1, row.len() - i);
+                    texts.extend(std::iter::repeat_n(row[i].text.clone(), span));
+                    i += span;
+                }
+                texts
+            })
+            .collect()
+    }
+
+    /// Swap rows and columns, e.g. to turn a key/value layout running down
+    /// the page into a single header row. Ragged rows are padded with empty
+    /// cells. The result has no positioned cells and one header row.
+    pub fn transpose(&self) -> Table {
+        let rows: Vec<Vec<String>> = (0..self.num_columns)
+            .map(|col| {
+                self.rows
+                    .iter()
+                    .map(|row| row.get(col).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        Table {
+            num_columns: self.rows.len(),
+            header_rows: usize::from(!rows.is_empty()),
+            rows,
+            cells: Vec::new(),
+        }
+    }
+
+    /// Map each row below `header_row` to a record keyed by that row's
+    /// cells. Rows are padded or truncated to `num_columns`; empty headers
+    /// are named `column_N` and repeated ones get a `_2`, `_3`... suffix.
+    pub fn to_records(&self, header_row: usize) -> Vec<HashMap<String, String>> {
+        let Some(header) = self.rows.get(header_row) else {
+            return Vec::new();
+        };
+
+        let mut keys: Vec<String> = Vec::with_capacity(self.num_columns);
+        for col in 0..self.num_columns {
+            let name = match header.get(col).map(|h| h.trim()) {
+                Some(h) if !h.is_empty() => h.to_string(),
+                _ => format!("column_{}", col + 1),
+            };
+            let mut key = name.clone();
+            let mut n = 1;
+            while keys.contains(&key) {
+                n += 1;
+                key = format!("{}_{}", name, n);
+            }
+            keys.push(key);
         }
+
+        self.rows[header_row + 1..]
+            .iter()
+            .map(|row| {
+                keys.iter()
+                    .enumerate()
+                    .map(|(col, key)| (key.clone(), row.get(col).cloned().unwrap_or_default()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Classify each column by the values in its data rows, ignoring the
+    /// header rows and empty cells. Integers mixed with decimals give
+    /// `Float`, plain numbers mixed with amounts give `Currency`, and any
+    /// other mix, or a column with no values, gives `Text`.
+    pub fn infer_column_types(&self) -> Vec<ColumnType> {
+        (0..self.num_columns)
+            .map(|col| {
+                let mut column_type = None;
+                for row in self.rows.iter().skip(self.header_rows) {
+                    let Some(cell) = row.get(col).filter(|c| !c.trim().is_empty()) else {
+                        continue;
+                    };
+                    column_type = Some(match (column_type, classify_cell(cell)) {
+                        (None, t) => t,
+                        (Some(a), b) if a == b => a,
+                        (Some(ColumnType::Integer | ColumnType::Float), ColumnType::Float)
+                        | (Some(ColumnType::Float), ColumnType::Integer) => ColumnType::Float,
+                        (Some(a), b) if a.is_numeric() && b.is_numeric() => ColumnType::Currency,
+                        _ => return ColumnType::Text,
+                    });
+                }
+                column_type.unwrap_or(ColumnType::Text)
+            })
+            .collect()
     }
 
     /// Convert table to CSV string
     pub fn to_csv(&self) -> String {
+        self.to_csv_with_headers(true)
+    }
+
+    /// Convert table to CSV string, leaving out the header rows when
+    /// `include_headers` is false
+    pub fn to_csv_with_headers(&self, include_headers: bool) -> String {
+        let skip = if include_headers { 0 } else { self.header_rows };
         self.rows
             .iter()
+            .skip(skip)
             .map(|row| {
                 row.iter()
                     .map(|cell| escape_csv(cell))
@@ -75,7 +267,8 @@ impl Table {
             .join("\n")
     }
 
-    /// Convert table to plain text with aligned columns
+    /// Convert table to plain text with aligned columns; numeric columns
+    /// are right-aligned
     pub fn to_text(&self) -> String {
         if self.rows.is_empty() {
             return String::new();
@@ -92,6 +285,7 @@ impl Table {
         }
 
         // Build output with padding
+        let types = self.infer_column_types();
         self.rows
             .iter()
             .map(|row| {
@@ -99,7 +293,11 @@ impl Table {
                     .enumerate()
                     .map(|(i, cell)| {
                         let width = widths.get(i).copied().unwrap_or(0);
-                        format!("{:<width$}", cell, width = width)
+                        if types.get(i).is_some_and(|t| t.is_numeric()) {
+                            format!("{:>width$}", cell, width = width)
+                        } else {
+                            format!("{:<width$}", cell, width = width)
+                        }
                     })
                     .collect::<Vec<_>>()
                     .join("  ")
@@ -115,7 +313,7 @@ impl Table {
 fn cluster_into_rows(mut spans: Vec<TextSpan>, tolerance: f64) -> Vec<Vec<TextSpan>> {
     // Sort by Y descending (top to bottom), then X ascending
     spans.sort_by(|a, b| {
-        b.y.partial_cmp(&a.y)
+        b.baseline().partial_cmp(&a.baseline())
             .unwrap_or(std::cmp::Ordering::Equal)
             .then(a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal))
     });
@@ -126,7 +324,7 @@ fn cluster_into_rows(mut spans: Vec<TextSpan>, tolerance: f64) -> Vec<Vec<TextSp
 
     for span in spans {
         match current_y {
-            Some(y) if (span.y - y).abs() <= tolerance => {
+            Some(y) if (span.baseline() - y).abs() <= tolerance => {
                 // Same row
                 current_row.push(span);
             }
@@ -135,7 +333,7 @@ fn cluster_into_rows(mut spans: Vec<TextSpan>, tolerance: f64) -> Vec<Vec<TextSp
                 if !current_row.is_empty() {
                     rows.push(current_row);
                 }
-                current_y = Some(span.y);
+                current_y = Some(span.baseline());
                 current_row = vec![span];
             }
         }
@@ -148,58 +346,182 @@ fn cluster_into_rows(mut spans: Vec<TextSpan>, tolerance: f64) -> Vec<Vec<TextSp
     rows
 }
 
-/// Detect column boundaries from X positions
-fn detect_columns(rows: &[Vec<TextSpan>]) -> Vec<f64> {
-    // Collect all X positions
-    let mut x_positions: Vec<f64> = rows
-        .iter()
-        .flat_map(|row| row.iter().map(|s| s.x))
-        .collect();
+/// Join spans that continue the previous span's measured box into one cell
+fn join_continuations(row: Vec<TextSpan>) -> Vec<TextSpan> {
+    let mut joined: Vec<TextSpan> = Vec::new();
+
+    for span in row {
+        if let Some(last) = joined.last_mut() {
+            if span.width > 0.0 && continues_box(last, &span) {
+                // A visible gap between the boxes is a word space
+                if span.x - last.right() > span.font_size * 0.1 {
+                    last.text.push(' ');
+                }
+                last.text.push_str(&span.text);
+                last.width = last.width.max(span.x + span.width - last.x);
+                continue;
+            }
+        }
+        joined.push(span);
+    }
+
+    joined
+}
+
+/// Count the leading rows styled differently from the table body: larger
+/// text, a bold font, or fonts the body doesn't use. Defaults to 1.
+fn detect_header_rows(rows: &[Vec<TextSpan>]) -> usize {
+    let Some(body) = rows.last() else {
+        return 0;
+    };
+    let body_size = body.iter().map(|s| s.font_size).fold(0.0, f64::max);
+    let body_bold = body.iter().all(is_bold);
 
-    if x_positions.is_empty() {
-        return Vec::new();
+    let styled = |row: &Vec<TextSpan>| {
+        let size = row.iter().map(|s| s.font_size).fold(0.0, f64::max);
+        let larger = size > body_size * 1.1;
+        let bold = !body_bold && row.iter().all(is_bold);
+        let own_fonts = row
+            .iter()
+            .all(|s| s.font_name.is_some() && body.iter().all(|b| b.font_name != s.font_name));
+        larger || bold || own_fonts
+    };
+
+    match rows.iter().take_while(|row| styled(row)).count() {
+        0 => 1,
+        n if n >= rows.len() => 1,
+        n => n,
+    }
+}
+
+/// Whether a span's font name suggests a bold face
+fn is_bold(span: &TextSpan) -> bool {
+    span.font_name.as_deref().is_some_and(|name| {
+        let name = name.to_ascii_lowercase();
+        ["bold", "black", "heavy", "semibold"].iter().any(|w| name.contains(w))
+    })
+}
+
+/// A detected column and the edge its text lines up on
+#[derive(Debug, Clone, Copy)]
+struct Column {
+    /// Where the column's text starts
+    left: f64,
+    /// Average aligned edge: the left edge, or the right edge when
+    /// `right_aligned`
+    anchor: f64,
+    right_aligned: bool,
+}
+
+impl Column {
+    /// Distance from a span's aligned edge to this column
+    fn distance(&self, span: &TextSpan) -> f64 {
+        let edge = if self.right_aligned { span.right() } else { span.x };
+        (edge - self.anchor).abs()
     }
+}
+
+/// Detect columns by clustering both left and right span edges. The
+/// cluster holding the most unassigned spans becomes a column until every
+/// span has one, so right-aligned numbers with staggered left edges still
+/// share a column. Left alignment wins ties.
+fn detect_columns(rows: &[Vec<TextSpan>], tolerance: f64) -> Vec<Column> {
+    let spans: Vec<&TextSpan> = rows.iter().flatten().collect();
 
-    x_positions.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let lefts = cluster_edges(spans.iter().enumerate().map(|(i, s)| (s.x, i)), tolerance);
+    let rights = cluster_edges(
+        spans
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.width > 0.0)
+            .map(|(i, s)| (s.right(), i)),
+        tolerance,
+    );
+    let candidates: Vec<(bool, Vec<(f64, usize)>)> = lefts
+        .into_iter()
+        .map(|cluster| (false, cluster))
+        .chain(rights.into_iter().map(|cluster| (true, cluster)))
+        .collect();
 
-    // Cluster X positions
-    let tolerance = 10.0;
-    let mut columns: Vec<f64> = Vec::new();
-    let mut cluster: Vec<f64> = Vec::new();
+    let mut claimed = vec![false; spans.len()];
+    let mut columns: Vec<Column> = Vec::new();
+    let mut sizes: Vec<usize> = Vec::new();
+    loop {
+        let best = candidates
+            .iter()
+            .map(|(right_aligned, cluster)| {
+                let members: Vec<(f64, usize)> =
+                    cluster.iter().copied().filter(|&(_, i)| !claimed[i]).collect();
+                (*right_aligned, members)
+            })
+            .max_by_key(|(right_aligned, members)| (members.len(), !right_aligned));
+        let Some((right_aligned, members)) = best.filter(|(_, m)| !m.is_empty()) else {
+            break;
+        };
 
-    for x in x_positions {
-        if cluster.is_empty() {
-            cluster.push(x);
+        let anchor = members.iter().map(|(edge, _)| edge).sum::<f64>() / members.len() as f64;
+        let left = if right_aligned {
+            members.iter().map(|&(_, i)| spans[i].x).fold(f64::INFINITY, f64::min)
         } else {
-            let last = cluster.last().unwrap();
-            if (x - last).abs() <= tolerance {
-                cluster.push(x);
-            } else {
-                // End cluster, take average as column position
-                let avg = cluster.iter().sum::<f64>() / cluster.len() as f64;
-                columns.push(avg);
-                cluster = vec![x];
-            }
+            anchor
+        };
+        for &(_, i) in &members {
+            claimed[i] = true;
         }
-    }
 
-    // Don't forget last cluster
-    if !cluster.is_empty() {
-        let avg = cluster.iter().sum::<f64>() / cluster.len() as f64;
-        columns.push(avg);
+        // Text that runs across the start of a larger column, like a header
+        // centered over a column group, spans columns rather than adding one
+        let spanning = members.iter().all(|&(_, i)| {
+            columns.iter().zip(&sizes).any(|(column, &size)| {
+                size > members.len() && spans[i].x < column.left && column.left < spans[i].right()
+            })
+        });
+        if !spanning {
+            columns.push(Column { left, anchor, right_aligned });
+            sizes.push(members.len());
+        }
     }
 
+    columns.sort_by(|a, b| a.left.partial_cmp(&b.left).unwrap_or(std::cmp::Ordering::Equal));
     columns
 }
 
-/// Assign spans to grid cells based on nearest column
-fn assign_to_columns(rows: Vec<Vec<TextSpan>>, columns: &[f64]) -> Vec<Vec<String>> {
-    let num_cols = columns.len();
+/// Group `(edge, span index)` pairs into clusters of nearby edges
+fn cluster_edges(
+    edges: impl Iterator<Item = (f64, usize)>,
+    tolerance: f64,
+) -> Vec<Vec<(f64, usize)>> {
+    let mut edges: Vec<_> = edges.collect();
+    edges.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut clusters: Vec<Vec<(f64, usize)>> = Vec::new();
+    for edge in edges {
+        match clusters.last_mut() {
+            Some(cluster) if edge.0 - cluster[cluster.len() - 1].0 <= tolerance => {
+                cluster.push(edge)
+            }
+            _ => clusters.push(vec![edge]),
+        }
+    }
+    clusters
+}
 
+/// Assign spans to grid cells based on nearest column
+fn assign_to_columns(rows: Vec<Vec<TextSpan>>, columns: &[Column]) -> Vec<Vec<TableCell>> {
     rows.into_iter()
         .map(|row| {
-            // Create row with empty cells
-            let mut cells: Vec<String> = vec![String::new(); num_cols];
+            // Create row with empty cells at the column positions
+            let y = row.first().map(|s| s.baseline()).unwrap_or(0.0);
+            let mut cells: Vec<TableCell> = columns
+                .iter()
+                .map(|column| TableCell {
+                    text: String::new(),
+                    x: column.left,
+                    y,
+                    col_span_hint: 1,
+                })
+                .collect();
+            let mut rights: Vec<f64> = vec![f64::MIN; columns.len()];
 
             for span in row {
                 // Find nearest column
@@ -207,25 +529,168 @@ fn assign_to_columns(rows: Vec<Vec<TextSpan>>, columns: &[f64]) -> Vec<Vec<Strin
                     .iter()
                     .enumerate()
                     .min_by(|(_, a), (_, b)| {
-                        let diff_a = (span.x - **a).abs();
-                        let diff_b = (span.x - **b).abs();
-                        diff_a.partial_cmp(&diff_b).unwrap_or(std::cmp::Ordering::Equal)
+                        a.distance(&span)
+                            .partial_cmp(&b.distance(&span))
+                            .unwrap_or(std::cmp::Ordering::Equal)
                     })
                     .map(|(i, _)| i)
                     .unwrap_or(0);
 
                 // Append to cell (may have multiple spans in same cell)
-                if !cells[col_idx].is_empty() {
-                    cells[col_idx].push(' ');
+                let cell = &mut cells[col_idx];
+                if cell.text.is_empty() {
+                    cell.x = span.x;
+                } else {
+                    cell.text.push(' ');
+                    cell.x = cell.x.min(span.x);
                 }
-                cells[col_idx].push_str(&span.text);
+                cell.text.push_str(&span.text);
+                rights[col_idx] = rights[col_idx].max(span.right());
             }
 
+            mark_spanning_cells(&mut cells, &rights, columns);
             cells
         })
         .collect()
 }
 
+/// Merge a cell with the empty cells of the columns its text overlaps. A
+/// cell may also reach back over empty columns that start inside its text,
+/// as a header centered over several columns does; its text then moves to
+/// the first covered column.
+fn mark_spanning_cells(cells: &mut [TableCell], rights: &[f64], columns: &[Column]) {
+    let mut covered = vec![false; cells.len()];
+    for i in 0..cells.len() {
+        if cells[i].text.is_empty() || covered[i] {
+            continue;
+        }
+
+        let mut first = i;
+        while first > 0
+            && cells[first - 1].text.is_empty()
+            && !covered[first - 1]
+            && cells[i].x <= columns[first - 1].left
+        {
+            first -= 1;
+        }
+        let mut last = i;
+        while last + 1 < cells.len()
+            && cells[last + 1].text.is_empty()
+            && columns[last + 1].left < rights[i]
+        {
+            last += 1;
+        }
+
+        if first < i {
+            let text = std::mem::take(&mut cells[i].text);
+            cells[first].text = text;
+            cells[first].x = cells[i].x;
+            cells[i].x = columns[i].left;
+        }
+        cells[first].col_span_hint = last - first + 1;
+        for flag in &mut covered[first..=last] {
+            *flag = true;
+        }
+    }
+}
+
+const CURRENCY_SYMBOLS: [char; 7] = ['$', '€', '£', '¥', '₹', '₩', '₽'];
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Classify a single non-empty cell value
+fn classify_cell(text: &str) -> ColumnType {
+    let text = text.trim();
+    // Accounting negatives are written in parentheses
+    let amount = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')).unwrap_or(text);
+
+    if let Some(fractional) = parse_number(amount) {
+        return if fractional { ColumnType::Float } else { ColumnType::Integer };
+    }
+    let unsigned = amount.strip_prefix(['-', '+']).unwrap_or(amount);
+    let value = unsigned
+        .strip_prefix(CURRENCY_SYMBOLS)
+        .or_else(|| unsigned.strip_suffix(CURRENCY_SYMBOLS));
+    if value.is_some_and(|v| parse_number(v.trim()).is_some()) {
+        return ColumnType::Currency;
+    }
+    if is_date(text) {
+        return ColumnType::Date;
+    }
+    ColumnType::Text
+}
+
+/// Parse a number such as `-1,234.50`, returning whether it has a
+/// fractional part. Thousands separators must group digits by three.
+fn parse_number(s: &str) -> Option<bool> {
+    let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (int, frac) = match s.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (s, None),
+    };
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    let mut groups = int.split(',');
+    let first = groups.next().unwrap_or_default();
+    let int_ok = if int.contains(',') {
+        digits(first) && first.len() <= 3 && groups.all(|g| g.len() == 3 && digits(g))
+    } else {
+        int.is_empty() || digits(int)
+    };
+    match frac {
+        Some(frac) if int_ok && digits(frac) => Some(true),
+        None if int_ok && !int.is_empty() => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether `s` is a date like `2024-05-01`, `01/05/2024`, `01/05`,
+/// `1 May 2024` or `May 1, 2024`
+fn is_date(s: &str) -> bool {
+    let number = |s: &str, max: u32| {
+        (1..=2).contains(&s.len()) && s.parse::<u32>().is_ok_and(|n| (1..=max).contains(&n))
+    };
+    let day = |s: &str| number(s, 31);
+    let month = |s: &str| number(s, 12);
+    let year = |s: &str| s.len() == 4 && s.bytes().all(|b| b.is_ascii_digit());
+
+    for sep in ['-', '/', '.'] {
+        let parts: Vec<&str> = s.split(sep).collect();
+        match parts[..] {
+            [y, m, d] if year(y) => return month(m) && day(d),
+            [a, b, y] if year(y) || (y.len() == 2 && y.bytes().all(|b| b.is_ascii_digit())) => {
+                return (day(a) && month(b)) || (month(a) && day(b));
+            }
+            [a, b] if sep == '/' => return (day(a) && month(b)) || (month(a) && day(b)),
+            _ => {}
+        }
+    }
+
+    let month_name = |s: &str| {
+        let s = s.trim_end_matches('.').to_lowercase();
+        s.len() >= 3 && MONTHS.iter().any(|m| m.starts_with(&s))
+    };
+    let words: Vec<&str> = s.split([' ', ',']).filter(|w| !w.is_empty()).collect();
+    match words[..] {
+        [d, m, y] if day(d) && month_name(m) => year(y),
+        [m, d, y] if month_name(m) && day(d) => year(y),
+        _ => false,
+    }
+}
+
 /// Escape a string for CSV output
 fn escape_csv(s: &str) -> String {
     if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
@@ -246,6 +711,13 @@ mod tests {
             y,
             font_size: 12.0,
             font_name: None,
+            rise: 0.0,
+            width: 0.0,
+            height: 0.0,
+            color: None,
+            bold: false,
+            italic: false,
+            monospace: false,
         }
     }
 
@@ -266,6 +738,35 @@ mod tests {
         assert_eq!(table.rows[1], vec!["1", "2"]);
     }
 
+    #[test]
+    fn test_column_tolerance_follows_font_size() {
+        let sized = |text: &str, x: f64, y: f64, font_size: f64| TextSpan {
+            font_size,
+            ..make_span(text, x, y)
+        };
+
+        // 6pt cells 8 units apart are separate columns
+        let small: Vec<TextSpan> = [100.0, 80.0]
+            .iter()
+            .flat_map(|&y| (0..3).map(move |i| sized("9", 100.0 + 8.0 * i as f64, y, 6.0)))
+            .collect();
+        assert_eq!(Table::from_spans(small.clone()).num_columns, 3);
+
+        // 18pt cells whose left edges wander by 12 units share a column
+        let large = vec![
+            sized("Item", 100.0, 300.0, 18.0),
+            sized("Cost", 300.0, 300.0, 18.0),
+            sized("Pen", 112.0, 270.0, 18.0),
+            sized("2", 312.0, 270.0, 18.0),
+        ];
+        let table = Table::from_spans(large);
+        assert_eq!(table.rows, [["Item", "Cost"], ["Pen", "2"]]);
+
+        // A fixed tolerance overrides the font size
+        let options = TableOptions { column_tolerance: Some(10.0), ..Default::default() };
+        assert_eq!(Table::from_spans_with(small, options).num_columns, 1);
+    }
+
     #[test]
     fn test_csv_output() {
         let spans = vec![
@@ -312,4 +813,215 @@ mod tests {
         assert!(tsv.contains("Col1\tCol2"));
         assert!(tsv.contains("Data1\tData2"));
     }
+
+    #[test]
+    fn test_abutting_boxes_share_a_cell() {
+        let measured = |text: &str, x: f64, y: f64, width: f64| TextSpan {
+            width,
+            ..make_span(text, x, y)
+        };
+        let spans = vec![
+            measured("Unit", 10.0, 100.0, 24.0),
+            measured("price", 37.0, 100.0, 30.0),
+            measured("Qty", 120.0, 100.0, 18.0),
+            measured("4.50", 10.0, 80.0, 24.0),
+            measured("2", 120.0, 80.0, 6.0),
+        ];
+
+        let table = Table::from_spans(spans);
+        assert_eq!(table.num_columns, 2);
+        assert_eq!(table.rows[0], vec!["Unit price", "Qty"]);
+        assert_eq!(table.rows[1], vec!["4.50", "2"]);
+    }
+
+    #[test]
+    fn test_cell_positions() {
+        let measured = |text: &str, x: f64, y: f64, width: f64| TextSpan {
+            width,
+            ..make_span(text, x, y)
+        };
+        let spans = vec![
+            measured("Item", 10.0, 100.0, 24.0),
+            measured("Price", 80.0, 100.0, 30.0),
+            measured("Tax", 150.0, 100.0, 18.0),
+            measured("Shipping and handling", 10.0, 80.0, 160.0),
+            measured("Pen", 10.0, 60.0, 18.0),
+            measured("1.50", 80.0, 60.0, 24.0),
+        ];
+
+        let table = Table::from_spans(spans);
+        assert_eq!(table.cells.len(), 3);
+        assert_eq!(table.rows[2], vec!["Pen", "1.50", ""]);
+
+        let price = &table.cells[0][1];
+        assert_eq!((price.text.as_str(), price.x, price.y), ("Price", 80.0, 100.0));
+        assert_eq!(price.col_span_hint, 1);
+
+        // The long label runs across the Price and Tax columns
+        assert_eq!(table.cells[1][0].col_span_hint, 3);
+        // An empty cell sits at its column position
+        assert_eq!((table.cells[2][2].x, table.cells[2][2].col_span_hint), (150.0, 1));
+    }
+
+    #[test]
+    fn test_spanning_header_cell() {
+        let measured = |text: &str, x: f64, y: f64, width: f64| TextSpan {
+            width,
+            ..make_span(text, x, y)
+        };
+        // "First half" is centered over the Q1 and Q2 columns
+        let spans = vec![
+            measured("Region", 10.0, 120.0, 36.0),
+            measured("First half", 95.0, 120.0, 80.0),
+            measured("", 10.0, 100.0, 0.0),
+            measured("Q1", 80.0, 100.0, 12.0),
+            measured("Q2", 150.0, 100.0, 12.0),
+            measured("North", 10.0, 80.0, 30.0),
+            measured("10", 80.0, 80.0, 12.0),
+            measured("12", 150.0, 80.0, 12.0),
+            measured("South", 10.0, 60.0, 30.0),
+            measured("11", 80.0, 60.0, 12.0),
+            measured("13", 150.0, 60.0, 12.0),
+        ];
+
+        let table = Table::from_spans(spans);
+        assert_eq!(table.num_columns, 3);
+        assert_eq!(table.rows[0], vec!["Region", "First half", ""]);
+        assert_eq!(table.rows[1], vec!["", "Q1", "Q2"]);
+        let colspans: Vec<_> = table.cells[0].iter().map(|c| c.col_span_hint).collect();
+        assert_eq!(colspans, [1, 2, 1]);
+        assert!(table.cells[1..].iter().flatten().all(|c| c.col_span_hint == 1));
+        assert_eq!(table.spanned_rows()[0], vec!["Region", "First half", "First half"]);
+    }
+
+    fn table(rows: &[&[&str]], num_columns: usize) -> Table {
+        Table {
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|c| c.to_string()).collect())
+                .collect(),
+            num_columns,
+            cells: Vec::new(),
+            header_rows: 1,
+        }
+    }
+
+    #[test]
+    fn test_transpose() {
+        let pairs = table(&[&["Invoice", "42"], &["Date", "2024-05-01"], &["Total"]], 2);
+        let transposed = pairs.transpose();
+
+        assert_eq!(transposed.num_columns, 3);
+        assert_eq!(transposed.rows[0], vec!["Invoice", "Date", "Total"]);
+        assert_eq!(transposed.rows[1], vec!["42", "2024-05-01", ""]);
+        assert_eq!(transposed.transpose().rows[2], vec!["Total", ""]);
+    }
+
+    #[test]
+    fn test_to_records() {
+        let t = table(
+            &[&["Title"], &["Name", "Qty", "Qty", ""], &["Pen", "2", "3", "x", "extra"], &["Ink"]],
+            4,
+        );
+        let records = t.to_records(1);
+
+        assert_eq!(records.len(), 2);
+        let pen = &records[0];
+        assert_eq!(pen.len(), 4);
+        assert_eq!((pen["Name"].as_str(), pen["Qty"].as_str()), ("Pen", "2"));
+        assert_eq!((pen["Qty_2"].as_str(), pen["column_4"].as_str()), ("3", "x"));
+        assert_eq!(records[1]["Qty"], "");
+        assert!(t.to_records(9).is_empty());
+    }
+
+    #[test]
+    fn test_header_rows() {
+        let styled = |text: &str, x: f64, y: f64, font: &str| TextSpan {
+            font_name: Some(font.to_string()),
+            ..make_span(text, x, y)
+        };
+        let spans = vec![
+            styled("Region", 0.0, 120.0, "Helvetica-Bold"),
+            styled("2024", 50.0, 120.0, "Helvetica-Bold"),
+            styled("", 0.0, 100.0, "Helvetica-Bold"),
+            styled("(USD)", 50.0, 100.0, "Helvetica-Bold"),
+            styled("North", 0.0, 80.0, "Helvetica"),
+            styled("10", 50.0, 80.0, "Helvetica"),
+            styled("South", 0.0, 60.0, "Helvetica"),
+            styled("12", 50.0, 60.0, "Helvetica"),
+        ];
+
+        let table = Table::from_spans(spans);
+        assert_eq!(table.header_rows, 2);
+        assert_eq!(table.to_csv_with_headers(false), "North,10\nSouth,12");
+    }
+
+    #[test]
+    fn test_header_rows_default() {
+        let spans = vec![
+            make_span("A", 0.0, 100.0),
+            make_span("B", 50.0, 100.0),
+            make_span("1", 0.0, 80.0),
+            make_span("2", 50.0, 80.0),
+        ];
+        assert_eq!(Table::from_spans(spans).header_rows, 1);
+    }
+
+    #[test]
+    fn test_infer_column_types() {
+        let t = table(
+            &[
+                &["Date", "Item", "Qty", "Rate", "Price", "Note"],
+                &["2024-05-01", "Pen", "2", "1.5", "$1,200.00", ""],
+                &["03/05/2024", "Ink", "10", "2", "(£3.50)", "12"],
+                &["May 7, 2024", "Paper", "1,000", "-0.25", "-$12", "ok"],
+                &["9 Jun 2024", "Tape", "+4", ".5", "45", ""],
+            ],
+            6,
+        );
+        use ColumnType::*;
+        assert_eq!(t.infer_column_types(), [Date, Text, Integer, Float, Currency, Text]);
+
+        for (value, expected) in [
+            ("12,34", Text),
+            ("1,234,567", Integer),
+            ("12,50", Text),
+            ("45 €", Currency),
+            ("€ 12.50", Currency),
+            ("01/02", Date),
+            ("2024-13-01", Text),
+            ("31.12.24", Date),
+            ("Sept 3 2024", Date),
+            ("1.2.3.4", Text),
+        ] {
+            assert_eq!(classify_cell(value), expected, "{:?}", value);
+        }
+
+        let text = table(&[&["Item", "Price"], &["Pen", "$5.00"], &["Paper", "$12.00"]], 2);
+        assert_eq!(text.to_text(), "Item    Price\nPen     $5.00\nPaper  $12.00");
+    }
+
+    #[test]
+    fn test_right_aligned_column() {
+        let sized = |text: &str, x: f64, y: f64, width: f64| TextSpan {
+            width,
+            ..make_span(text, x, y)
+        };
+        // Amounts share a right edge at 300 but start at different x
+        let spans = vec![
+            sized("Date", 0.0, 100.0, 30.0),
+            sized("Amount", 250.0, 100.0, 50.0),
+            sized("01/02", 0.0, 80.0, 30.0),
+            sized("1.00", 280.0, 80.0, 20.0),
+            sized("10/02", 0.0, 60.0, 30.0),
+            sized("1,234.00", 244.0, 60.0, 56.0),
+            sized("15/02", 0.0, 40.0, 30.0),
+            sized("123,456.00", 226.0, 40.0, 74.0),
+        ];
+
+        let table = Table::from_spans(spans);
+        assert_eq!(table.num_columns, 2);
+        assert_eq!(table.rows[2], vec!["10/02", "1,234.00"]);
+        assert_eq!(table.rows[3], vec!["15/02", "123,456.00"]);
+    }
 }
@@ -0,0 +1,92 @@
+use crate::extract::layout::PageElement;
+
+/// Render page elements as TSV. Tables become tab-separated rows; headings,
+/// paragraphs, lists and code are passed through as `# `-prefixed comment
+/// lines so the text around the tables is kept in reading order.
+pub fn elements_to_tsv(elements: &[PageElement]) -> String {
+    elements_to_delimited(elements, '\t')
+}
+
+/// Render page elements as CSV, like [`elements_to_tsv`] with commas
+pub fn elements_to_csv(elements: &[PageElement]) -> String {
+    elements_to_delimited(elements, ',')
+}
+
+fn elements_to_delimited(elements: &[PageElement], delimiter: char) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let comment = |text: &str, lines: &mut Vec<String>| {
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            lines.push(format!("# {}", line.trim_end()));
+        }
+    };
+
+    for element in elements {
+        match element {
+            PageElement::Heading { text, .. }
+            | PageElement::Paragraph { text, .. }
+            | PageElement::CodeBlock { text, .. } => comment(text, &mut lines),
+            PageElement::List { ordered, items, .. } => {
+                for (i, item) in items.iter().enumerate() {
+                    let marker = if *ordered { format!("{}.", i + 1) } else { "-".to_string() };
+                    comment(&format!("{} {}", marker, item), &mut lines);
+                }
+            }
+            PageElement::Table { table, .. } => {
+                let rows = if delimiter == '\t' { table.to_tsv() } else { table.to_csv() };
+                // Rows without any content would be bare delimiters
+                lines.extend(
+                    rows.lines()
+                        .filter(|row| !row.replace(['"', delimiter], "").trim().is_empty())
+                        .map(str::to_string),
+                );
+            }
+        }
+    }
+
+    if lines.is_empty() {
+        String::new()
+    } else {
+        lines.join("\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::Table;
+
+    #[test]
+    fn test_tables_and_comments() {
+        let table = Table {
+            rows: vec![
+                vec!["Name".to_string(), "Qty".to_string()],
+                vec![String::new(), String::new()],
+                vec!["Apples".to_string(), "3".to_string()],
+            ],
+            num_columns: 2,
+            cells: Vec::new(),
+            header_rows: 1,
+        };
+        let elements = vec![
+            PageElement::Heading { level: 1, text: "Inventory".to_string(), gap_before: 0.0 },
+            PageElement::Table { table, gap_before: 0.0 },
+            PageElement::Paragraph { text: String::new(), runs: Vec::new(), gap_before: 0.0 },
+            PageElement::List {
+                ordered: false,
+                items: vec!["Counted, daily".to_string()],
+                gap_before: 0.0,
+            },
+            PageElement::Table { table: Table::from_spans(Vec::new()), gap_before: 0.0 },
+        ];
+
+        assert_eq!(
+            elements_to_tsv(&elements),
+            "# Inventory\nName\tQty\nApples\t3\n# - Counted, daily\n"
+        );
+        assert_eq!(
+            elements_to_csv(&elements),
+            "# Inventory\nName,Qty\nApples,3\n# - Counted, daily\n"
+        );
+        assert_eq!(elements_to_tsv(&[]), "");
+    }
+}
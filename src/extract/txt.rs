@@ -1,23 +1,58 @@
 use crate::extract::layout::PageElement;
 
-/// Render page elements as plain text.
+/// Largest number of extra blank lines put before an element
+const MAX_EXTRA_BLANK_LINES: usize = 2;
+
+/// Render page elements as plain text. Elements are separated by a blank
+/// line, with one more for each multiple of the typical gap between
+/// elements that a gap reaches beyond the first, so section breaks stand
+/// out from tightly spaced text.
 pub fn elements_to_txt(elements: &[PageElement]) -> String {
     let mut out = String::new();
 
+    let mut gaps: Vec<f64> =
+        elements.iter().map(PageElement::gap_before).filter(|&g| g > 0.0).collect();
+    gaps.sort_by(f64::total_cmp);
+    let typical_gap = gaps.get(gaps.len().saturating_sub(1) / 2).copied();
+
     for element in elements {
+        if let Some(typical) = typical_gap.filter(|_| !out.is_empty()) {
+            let extra = (element.gap_before() / typical) as usize;
+            out.push_str(&"\n".repeat(extra.saturating_sub(1).min(MAX_EXTRA_BLANK_LINES)));
+        }
         match element {
             PageElement::Heading { text, .. } => {
                 out.push_str(text);
                 out.push_str("\n\n");
             }
-            PageElement::Paragraph { text } => {
+            PageElement::Paragraph { text, .. } => {
                 out.push_str(text);
                 out.push_str("\n\n");
             }
-            PageElement::Table { table } => {
+            PageElement::Table { table, .. } => {
                 out.push_str(&table.to_text());
                 out.push_str("\n\n");
             }
+            PageElement::List { ordered, items, .. } => {
+                for (i, item) in items.iter().enumerate() {
+                    if *ordered {
+                        out.push_str(&format!("  {}. {}\n", i + 1, item));
+                    } else {
+                        out.push_str(&format!("  \u{2022} {}\n", item));
+                    }
+                }
+                out.push('\n');
+            }
+            PageElement::CodeBlock { text, .. } => {
+                for line in text.lines() {
+                    if !line.is_empty() {
+                        out.push_str("    ");
+                        out.push_str(line);
+                    }
+                    out.push('\n');
+                }
+                out.push('\n');
+            }
         }
     }
 
@@ -41,9 +76,12 @@ mod tests {
             PageElement::Heading {
                 level: 1,
                 text: "Hello World".to_string(),
+                gap_before: 0.0,
             },
             PageElement::Paragraph {
                 text: "This is a paragraph.".to_string(),
+                runs: Vec::new(),
+                gap_before: 0.0,
             },
         ];
 
@@ -51,6 +89,31 @@ mod tests {
         assert_eq!(txt, "Hello World\n\nThis is a paragraph.\n");
     }
 
+    #[test]
+    fn test_large_gaps_add_blank_lines() {
+        let paragraph = |text: &str, gap_before: f64| PageElement::Paragraph {
+            text: text.to_string(),
+            runs: Vec::new(),
+            gap_before,
+        };
+
+        // Uniform spacing keeps a single blank line
+        let uniform = [paragraph("One", 0.0), paragraph("Two", 20.0), paragraph("Three", 20.0)];
+        assert_eq!(elements_to_txt(&uniform), "One\n\nTwo\n\nThree\n");
+
+        let elements = [
+            paragraph("One", 0.0),
+            paragraph("Two", 18.0),
+            paragraph("Three", 20.0),
+            paragraph("Section", 45.0),
+            paragraph("Far", 400.0),
+        ];
+        assert_eq!(
+            elements_to_txt(&elements),
+            "One\n\nTwo\n\nThree\n\n\nSection\n\n\n\nFar\n"
+        );
+    }
+
     #[test]
     fn test_table_element() {
         let table = Table {
@@ -59,9 +122,11 @@ mod tests {
                 vec!["1".to_string(), "2".to_string()],
             ],
             num_columns: 2,
+            cells: Vec::new(),
+            header_rows: 1,
         };
 
-        let elements = vec![PageElement::Table { table }];
+        let elements = vec![PageElement::Table { table, gap_before: 0.0 }];
         let txt = elements_to_txt(&elements);
         assert!(txt.contains("A"));
         assert!(txt.contains("B"));
@@ -15,6 +15,119 @@ pub fn parse_tounicode_cmap(data: &[u8]) -> Result<HashMap<u16, char>> {
     Ok(map)
 }
 
+/// A range of character codes from a CMap's codespace, e.g. `<00> <80>`
+/// for one-byte codes or `<8140> <9FFC>` for two-byte codes
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodespaceRange {
+    pub low: Vec<u8>,
+    pub high: Vec<u8>,
+}
+
+impl CodespaceRange {
+    /// Code length in bytes
+    pub fn bytes(&self) -> usize {
+        self.low.len()
+    }
+
+    /// Whether `bytes` starts with a code in this range. Each byte is
+    /// checked against the matching byte of the bounds.
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        bytes.len() >= self.bytes()
+            && (0..self.bytes()).all(|i| (self.low[i]..=self.high[i]).contains(&bytes[i]))
+    }
+}
+
+/// Parse the begincodespacerange sections of a CMap. Only one- and two-byte
+/// ranges are kept; longer or malformed ranges are skipped.
+pub fn parse_codespace_ranges(data: &[u8]) -> Vec<CodespaceRange> {
+    let text = String::from_utf8_lossy(data);
+    let mut ranges = Vec::new();
+    let mut remaining = text.as_ref();
+
+    while let Some(start_idx) = remaining.find("begincodespacerange") {
+        remaining = &remaining[start_idx + 19..];
+        let Some(end_idx) = remaining.find("endcodespacerange") else {
+            break;
+        };
+
+        let hex_strings: Vec<Vec<u8>> = remaining[..end_idx]
+            .split('<')
+            .skip(1)
+            .filter_map(|s| s.split_once('>'))
+            .map(|(hex, _)| hex_bytes(hex))
+            .collect();
+        for pair in hex_strings.chunks_exact(2) {
+            let (low, high) = (&pair[0], &pair[1]);
+            if low.len() == high.len() && (1..=2).contains(&low.len()) {
+                ranges.push(CodespaceRange { low: low.clone(), high: high.clone() });
+            }
+        }
+        remaining = &remaining[end_idx + 17..];
+    }
+
+    ranges
+}
+
+/// Character codes `low..=high` mapped to consecutive CIDs from `cid`, from
+/// the cidrange and cidchar sections of a CMap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CidRange {
+    pub low: u16,
+    pub high: u16,
+    pub cid: u16,
+}
+
+/// Parse the begincidrange (`<low> <high> cid`) and begincidchar
+/// (`<code> cid`) sections of an embedded CMap
+pub fn parse_cid_ranges(data: &[u8]) -> Vec<CidRange> {
+    let text = String::from_utf8_lossy(data);
+    let mut ranges = Vec::new();
+
+    let sections = [("begincidrange", "endcidrange", 2), ("begincidchar", "endcidchar", 1)];
+    for (begin, end, codes) in sections {
+        let mut remaining = text.as_ref();
+        while let Some(start_idx) = remaining.find(begin) {
+            remaining = &remaining[start_idx + begin.len()..];
+            let Some(end_idx) = remaining.find(end) else {
+                break;
+            };
+
+            // Entries are hex codes followed by a decimal CID
+            let tokens: Vec<&str> = remaining[..end_idx]
+                .split(|c: char| c.is_whitespace() || c == '<')
+                .filter(|t| !t.is_empty())
+                .collect();
+            for entry in tokens.chunks_exact(codes + 1) {
+                let code = |t: &str| {
+                    let bytes = hex_bytes(t.trim_end_matches('>'));
+                    (bytes.len() <= 2).then(|| bytes.iter().fold(0u16, |c, &b| c << 8 | b as u16))
+                };
+                let (Some(low), Some(high), Ok(cid)) =
+                    (code(entry[0]), code(entry[codes - 1]), entry[codes].parse::<u16>())
+                else {
+                    continue;
+                };
+                if low <= high {
+                    ranges.push(CidRange { low, high, cid });
+                }
+            }
+            remaining = &remaining[end_idx + end.len()..];
+        }
+    }
+
+    ranges
+}
+
+/// Decode the bytes of a hex string, ignoring whitespace
+fn hex_bytes(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .chars()
+        .filter_map(|c| c.to_digit(16))
+        .map(|d| d as u8)
+        .collect();
+    digits.chunks(2).map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0)).collect()
+}
+
 /// Parse beginbfchar...endbfchar sections
 fn parse_bfchar_sections(text: &str, map: &mut HashMap<u16, char>) {
     let mut remaining = text;
@@ -212,4 +325,43 @@ endbfchar
         assert_eq!(map.get(&0x0003), Some(&' '));
         assert_eq!(map.get(&0x0024), Some(&'A'));
     }
+
+    #[test]
+    fn test_parse_codespace_ranges() {
+        let cmap = r#"
+2 begincodespacerange
+<00> <80>
+<8140> <9FFC>
+endcodespacerange
+1 begincodespacerange
+<000000> <FFFFFF>
+endcodespacerange
+"#;
+        let ranges = parse_codespace_ranges(cmap.as_bytes());
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].bytes(), 1);
+        assert!(ranges[1].matches(&[0x81, 0x40, 0x41]));
+        assert!(!ranges[1].matches(&[0x81, 0x20]));
+    }
+
+    #[test]
+    fn test_parse_cid_ranges() {
+        let cmap = r#"
+2 begincidrange
+<0020> <007e> 1
+<8140> <8141> 633
+endcidrange
+1 begincidchar
+<00a5> 97
+endcidchar
+"#;
+        assert_eq!(
+            parse_cid_ranges(cmap.as_bytes()),
+            vec![
+                CidRange { low: 0x20, high: 0x7e, cid: 1 },
+                CidRange { low: 0x8140, high: 0x8141, cid: 633 },
+                CidRange { low: 0xa5, high: 0xa5, cid: 97 },
+            ]
+        );
+    }
 }
@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 
+use crate::font::{glyph_name_to_unicode, CidRange, CodespaceRange};
+use crate::types::PdfObject;
+
 /// Font encoding for translating character codes to Unicode
 #[derive(Debug, Clone)]
 pub struct FontEncoding {
@@ -7,6 +10,48 @@ pub struct FontEncoding {
     map: HashMap<u8, char>,
     /// Map from two-byte CID to Unicode (for Type0/CID fonts)
     cid_map: HashMap<u16, char>,
+    /// Glyph widths in thousandths of a text space unit, keyed by character code
+    widths: HashMap<u16, f64>,
+    /// Width for codes not covered by `widths` (/MissingWidth or /DW)
+    missing_width: Option<f64>,
+    /// Ascent from the font descriptor, in thousandths of a text space unit
+    ascent: Option<f64>,
+    /// Codes are two bytes wide (Type0 composite fonts)
+    two_byte: bool,
+    /// Codespace ranges from the font's CMap, giving the byte length of each
+    /// code; codes outside every range are taken as two bytes
+    codespace: Vec<CodespaceRange>,
+    /// Unmapped byte codes decode to U+FFFD instead of their Latin-1 value
+    strict: bool,
+    /// Code to CID mapping from an embedded CMap; empty means code == CID
+    /// (Identity-H and simple fonts)
+    cid_ranges: Vec<CidRange>,
+    /// CID to glyph index (/CIDToGIDMap); `None` means CID == GID
+    cid_to_gid: Option<Vec<u16>>,
+    /// Glyphs are bold, from the font descriptor or name
+    bold: bool,
+    /// Glyphs are italic or oblique, from the font descriptor or name
+    italic: bool,
+    /// Glyphs share one advance, from the font descriptor or name
+    monospace: bool,
+}
+
+/// Expand Unicode ligature presentation forms (U+FB00-FB06) into their
+/// constituent letters, e.g. `ﬁ` -> `fi`
+pub fn expand_ligatures(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\u{FB00}' => out.push_str("ff"),
+            '\u{FB01}' => out.push_str("fi"),
+            '\u{FB02}' => out.push_str("fl"),
+            '\u{FB03}' => out.push_str("ffi"),
+            '\u{FB04}' => out.push_str("ffl"),
+            '\u{FB05}' | '\u{FB06}' => out.push_str("st"),
+            _ => out.push(ch),
+        }
+    }
+    out
 }
 
 impl Default for FontEncoding {
@@ -25,6 +70,17 @@ impl FontEncoding {
         FontEncoding {
             map,
             cid_map: HashMap::new(),
+            widths: HashMap::new(),
+            missing_width: None,
+            ascent: None,
+            two_byte: false,
+            codespace: Vec::new(),
+            strict: false,
+            cid_ranges: Vec::new(),
+            cid_to_gid: None,
+            bold: false,
+            italic: false,
+            monospace: false,
         }
     }
 
@@ -80,6 +136,17 @@ impl FontEncoding {
         FontEncoding {
             map,
             cid_map: HashMap::new(),
+            widths: HashMap::new(),
+            missing_width: None,
+            ascent: None,
+            two_byte: false,
+            codespace: Vec::new(),
+            strict: false,
+            cid_ranges: Vec::new(),
+            cid_to_gid: None,
+            bold: false,
+            italic: false,
+            monospace: false,
         }
     }
 
@@ -135,6 +202,26 @@ impl FontEncoding {
         FontEncoding {
             map,
             cid_map: HashMap::new(),
+            widths: HashMap::new(),
+            missing_width: None,
+            ascent: None,
+            two_byte: false,
+            codespace: Vec::new(),
+            strict: false,
+            cid_ranges: Vec::new(),
+            cid_to_gid: None,
+            bold: false,
+            italic: false,
+            monospace: false,
+        }
+    }
+
+    /// Encoding without any byte mappings, to be filled from /Differences
+    /// (Type3 fonts). Codes left unmapped decode to U+FFFD.
+    pub fn empty() -> Self {
+        FontEncoding {
+            strict: true,
+            ..Self::from_cid_map(HashMap::new())
         }
     }
 
@@ -143,12 +230,27 @@ impl FontEncoding {
         FontEncoding {
             map: HashMap::new(),
             cid_map,
+            widths: HashMap::new(),
+            missing_width: None,
+            ascent: None,
+            two_byte: false,
+            codespace: Vec::new(),
+            strict: false,
+            cid_ranges: Vec::new(),
+            cid_to_gid: None,
+            bold: false,
+            italic: false,
+            monospace: false,
         }
     }
 
     /// Decode a single byte
     pub fn decode_byte(&self, byte: u8) -> char {
-        self.map.get(&byte).copied().unwrap_or(byte as char)
+        match self.map.get(&byte) {
+            Some(&ch) => ch,
+            None if self.strict => '\u{FFFD}',
+            None => byte as char,
+        }
     }
 
     /// Decode a CID (two bytes)
@@ -161,33 +263,190 @@ impl FontEncoding {
         !self.cid_map.is_empty()
     }
 
+    /// Check if character codes are two bytes wide
+    pub fn is_multibyte(&self) -> bool {
+        self.two_byte || self.has_cid_map()
+    }
+
+    /// Mark the font as using two-byte character codes
+    pub fn set_two_byte(&mut self, two_byte: bool) {
+        self.two_byte = two_byte;
+    }
+
+    /// Use the codespace ranges of the font's CMap to split codes
+    pub fn set_codespace_ranges(&mut self, ranges: Vec<CodespaceRange>) {
+        self.codespace = ranges;
+    }
+
+    /// Split a shown string into the bytes of each character code: single
+    /// bytes for simple fonts, otherwise by codespace range (two bytes when
+    /// no range matches)
+    fn code_chunks<'b>(&self, bytes: &'b [u8]) -> Vec<&'b [u8]> {
+        if !self.is_multibyte() {
+            return bytes.chunks(1).collect();
+        }
+
+        let mut chunks = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let len = self
+                .codespace
+                .iter()
+                .find(|range| range.matches(rest))
+                .map_or(2, |range| range.bytes())
+                .min(rest.len());
+            let (chunk, tail) = rest.split_at(len);
+            chunks.push(chunk);
+            rest = tail;
+        }
+        chunks
+    }
+
+    /// Split a shown string into character codes
+    pub fn char_codes(&self, bytes: &[u8]) -> Vec<u16> {
+        self.code_chunks(bytes)
+            .into_iter()
+            .map(|chunk| chunk.iter().fold(0u16, |code, &b| (code << 8) | b as u16))
+            .collect()
+    }
+
+    /// Map codes to CIDs through the cidrange/cidchar entries of the font's
+    /// encoding CMap
+    pub fn set_cid_ranges(&mut self, ranges: Vec<CidRange>) {
+        self.cid_ranges = ranges;
+    }
+
+    /// Set the CID to glyph index map from a /CIDToGIDMap stream
+    pub fn set_cid_to_gid(&mut self, map: Option<Vec<u16>>) {
+        self.cid_to_gid = map;
+    }
+
+    /// CID of a character code; codes outside every range keep their value
+    pub fn cid(&self, code: u16) -> u16 {
+        self.cid_ranges
+            .iter()
+            .find(|r| (r.low..=r.high).contains(&code))
+            .map_or(code, |r| r.cid.saturating_add(code - r.low))
+    }
+
+    /// Glyph index of a CID in the embedded font program
+    pub fn gid(&self, cid: u16) -> u16 {
+        match &self.cid_to_gid {
+            Some(map) => map.get(cid as usize).copied().unwrap_or(0),
+            None => cid,
+        }
+    }
+
+    /// Set widths for consecutive codes starting at `first_char` (the /Widths array)
+    pub fn set_widths(&mut self, first_char: u16, widths: &[f64]) {
+        for (i, &width) in widths.iter().enumerate() {
+            if let Some(code) = first_char.checked_add(i as u16) {
+                self.widths.insert(code, width);
+            }
+        }
+    }
+
+    /// Set the width of a single code
+    pub fn set_width(&mut self, code: u16, width: f64) {
+        self.widths.insert(code, width);
+    }
+
+    /// Set the width used for codes without an explicit width
+    pub fn set_missing_width(&mut self, width: f64) {
+        self.missing_width = Some(width);
+    }
+
+    /// Set the ascent (/Ascent in the font descriptor)
+    pub fn set_ascent(&mut self, ascent: f64) {
+        self.ascent = Some(ascent);
+    }
+
+    /// Ascent in thousandths of a text space unit, if the font declares one
+    pub fn ascent(&self) -> Option<f64> {
+        self.ascent
+    }
+
+    /// Mark the font as bold and/or italic
+    pub fn set_style(&mut self, bold: bool, italic: bool) {
+        self.bold = bold;
+        self.italic = italic;
+    }
+
+    pub fn is_bold(&self) -> bool {
+        self.bold
+    }
+
+    pub fn is_italic(&self) -> bool {
+        self.italic
+    }
+
+    /// Mark the font as fixed-pitch
+    pub fn set_monospace(&mut self, monospace: bool) {
+        self.monospace = monospace;
+    }
+
+    pub fn is_monospace(&self) -> bool {
+        self.monospace
+    }
+
+    /// Whether at least three glyphs have a width and all widths are the same
+    pub fn has_uniform_widths(&self) -> bool {
+        let mut widths = self.widths.values().filter(|&&w| w > 0.0);
+        match widths.next() {
+            Some(&first) => widths.clone().count() >= 2 && widths.all(|&w| w == first),
+            None => false,
+        }
+    }
+
+    /// Glyph width for a character code, in thousandths of a text space unit.
+    /// Widths of composite fonts are keyed by CID.
+    pub fn glyph_width(&self, code: u16) -> Option<f64> {
+        self.widths.get(&self.cid(code)).copied().or(self.missing_width)
+    }
+
     /// Decode a byte string using this encoding
     pub fn decode_bytes(&self, bytes: &[u8]) -> String {
-        if self.has_cid_map() {
-            // CID font - decode as 2-byte sequences
+        self.decode(bytes, false).0
+    }
+
+    /// Decode a byte string, turning every code without a Unicode mapping
+    /// into U+FFFD instead of guessing from its bytes. Also returns how many
+    /// codes were unmapped.
+    pub fn decode_bytes_strict(&self, bytes: &[u8]) -> (String, usize) {
+        self.decode(bytes, true)
+    }
+
+    fn decode(&self, bytes: &[u8], replace_unmapped: bool) -> (String, usize) {
+        let mut unmapped = 0;
+        if self.is_multibyte() {
+            // CID font - decode code by code, falling back to the separate
+            // bytes of unmapped codes
             let mut result = String::new();
-            let mut i = 0;
-            while i < bytes.len() {
-                if i + 1 < bytes.len() {
-                    let cid = ((bytes[i] as u16) << 8) | (bytes[i + 1] as u16);
-                    if let Some(ch) = self.decode_cid(cid) {
-                        result.push(ch);
-                    } else {
-                        // Fallback: treat as two separate bytes
-                        result.push(self.decode_byte(bytes[i]));
-                        result.push(self.decode_byte(bytes[i + 1]));
+            for (chunk, code) in self.code_chunks(bytes).into_iter().zip(self.char_codes(bytes)) {
+                match self.decode_cid(code) {
+                    Some(ch) => result.push(ch),
+                    None if replace_unmapped => {
+                        result.push('\u{FFFD}');
+                        unmapped += 1;
                     }
-                    i += 2;
-                } else {
-                    // Odd byte at end
-                    result.push(self.decode_byte(bytes[i]));
-                    i += 1;
+                    None => result.extend(chunk.iter().map(|&b| self.decode_byte(b))),
                 }
             }
-            result
+            (result, unmapped)
         } else {
             // Simple encoding - one byte per character
-            bytes.iter().map(|&b| self.decode_byte(b)).collect()
+            let mut result = String::with_capacity(bytes.len());
+            for &b in bytes {
+                match self.map.get(&b) {
+                    Some(&ch) => result.push(ch),
+                    None if replace_unmapped || self.strict => {
+                        result.push('\u{FFFD}');
+                        unmapped += 1;
+                    }
+                    None => result.push(b as char),
+                }
+            }
+            (result, unmapped)
         }
     }
 
@@ -195,6 +454,29 @@ impl FontEncoding {
     pub fn add_cid_mapping(&mut self, cid: u16, unicode: char) {
         self.cid_map.insert(cid, unicode);
     }
+
+    /// Override byte mappings from an /Encoding /Differences array.
+    ///
+    /// The array holds a starting code followed by the glyph names for
+    /// consecutive codes, e.g. `[39 /quoteright 96 /quoteleft /a]`.
+    /// Glyph names without a known Unicode value keep the base mapping.
+    pub fn apply_differences(&mut self, differences: &[PdfObject]) {
+        let mut code: Option<i64> = None;
+
+        for item in differences {
+            match item {
+                PdfObject::Int(start) => code = Some(*start),
+                PdfObject::Name(name) => {
+                    let Some(current) = code else { continue };
+                    if let (Ok(byte), Some(ch)) = (u8::try_from(current), glyph_name_to_unicode(name)) {
+                        self.map.insert(byte, ch);
+                    }
+                    code = Some(current + 1);
+                }
+                _ => {}
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -216,6 +498,50 @@ mod tests {
         assert_eq!(enc.decode_byte(0x99), '\u{2122}'); // TM
     }
 
+    #[test]
+    fn test_apply_differences() {
+        let mut enc = FontEncoding::win_ansi();
+        enc.apply_differences(&[
+            PdfObject::Int(65),
+            PdfObject::Name("eacute".into()),
+            PdfObject::Name("germandbls".into()),
+            PdfObject::Int(0x27),
+            PdfObject::Name("quoteright".into()),
+            PdfObject::Name("g17".into()),
+        ]);
+
+        assert_eq!(enc.decode_bytes(b"AB'"), "é\u{df}\u{2019}");
+        // Unknown glyph name keeps the base mapping
+        assert_eq!(enc.decode_byte(b'('), '(');
+        assert_eq!(enc.decode_byte(b'C'), 'C');
+    }
+
+    #[test]
+    fn test_glyph_widths() {
+        let mut enc = FontEncoding::win_ansi();
+        enc.set_widths(65, &[722.0, 667.0]);
+        assert_eq!(enc.glyph_width(65), Some(722.0));
+        assert_eq!(enc.glyph_width(66), Some(667.0));
+        assert_eq!(enc.glyph_width(67), None);
+
+        enc.set_missing_width(250.0);
+        assert_eq!(enc.glyph_width(67), Some(250.0));
+        assert!(!enc.has_uniform_widths());
+
+        enc.set_widths(32, &[600.0, 600.0, 0.0, 600.0]);
+        enc.set_widths(65, &[600.0, 600.0]);
+        assert!(enc.has_uniform_widths());
+    }
+
+    #[test]
+    fn test_char_codes_two_byte() {
+        let mut enc = FontEncoding::win_ansi();
+        assert_eq!(enc.char_codes(&[0x01, 0x02]), vec![1, 2]);
+
+        enc.set_two_byte(true);
+        assert_eq!(enc.char_codes(&[0x01, 0x02, 0x03]), vec![0x0102, 0x03]);
+    }
+
     #[test]
     fn test_cid_decode() {
         let mut enc = FontEncoding::from_cid_map(HashMap::new());
@@ -236,4 +562,45 @@ mod tests {
         let bytes = [0x00, 0x24, 0x00, 0x25];
         assert_eq!(enc.decode_bytes(&bytes), "AB");
     }
+
+    #[test]
+    fn test_decode_bytes_strict_replaces_unmapped_cids() {
+        let mut enc = FontEncoding::from_cid_map(HashMap::new());
+        enc.add_cid_mapping(0x0024, 'A');
+
+        let bytes = [0x00, 0x24, 0x00, 0x42, 0x00, 0x43];
+        assert_eq!(enc.decode_bytes(&bytes), "A\0B\0C");
+        assert_eq!(enc.decode_bytes_strict(&bytes), ("A\u{FFFD}\u{FFFD}".to_string(), 2));
+    }
+
+    #[test]
+    fn test_mixed_width_codespace() {
+        let cmap = b"2 begincodespacerange <00> <80> <8140> <9FFC> endcodespacerange";
+        let cid_map = HashMap::from([(0x41, 'A'), (0x8140, '\u{4e2d}')]);
+        let mut enc = FontEncoding::from_cid_map(cid_map);
+        enc.set_codespace_ranges(crate::font::parse_codespace_ranges(cmap));
+
+        let bytes = [0x41, 0x81, 0x40, 0x42];
+        assert_eq!(enc.char_codes(&bytes), vec![0x41, 0x8140, 0x42]);
+        assert_eq!(enc.decode_bytes(&bytes), "A\u{4e2d}B");
+
+        // Without ranges, codes are two bytes wide
+        enc.set_codespace_ranges(Vec::new());
+        assert_eq!(enc.char_codes(&bytes), vec![0x4181, 0x4042]);
+    }
+
+    #[test]
+    fn test_cid_keyed_widths() {
+        let mut enc = FontEncoding::from_cid_map(HashMap::new());
+        enc.set_cid_ranges(vec![CidRange { low: 0x20, high: 0x7e, cid: 1 }]);
+        enc.set_widths(1, &[250.0, 333.0]);
+        enc.set_missing_width(1000.0);
+        assert_eq!(enc.cid(0x21), 2);
+        assert_eq!(enc.glyph_width(0x21), Some(333.0));
+        assert_eq!(enc.glyph_width(0x3000), Some(1000.0));
+
+        enc.set_cid_to_gid(Some(vec![0, 7, 9]));
+        assert_eq!(enc.gid(2), 9);
+        assert_eq!(enc.gid(5), 0);
+    }
 }
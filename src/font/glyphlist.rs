@@ -0,0 +1,402 @@
+/// Map a PostScript glyph name to its Unicode character.
+///
+/// Covers the Adobe standard Latin glyph set (Standard, WinAnsi and
+/// MacRoman names) plus the `uniXXXX` / `uXXXX` naming conventions.
+/// Suffixed variants such as `a.sc` or `one.oldstyle` map to their base glyph.
+pub fn glyph_name_to_unicode(name: &str) -> Option<char> {
+    if let Ok(idx) = GLYPH_NAMES.binary_search_by(|(n, _)| n.cmp(&name)) {
+        return Some(GLYPH_NAMES[idx].1);
+    }
+
+    // uniXXXX (exactly four hex digits for a single BMP character)
+    if let Some(hex) = name.strip_prefix("uni") {
+        if hex.len() == 4 {
+            return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+        }
+    }
+
+    // uXXXX to uXXXXXX
+    if let Some(hex) = name.strip_prefix('u') {
+        if (4..=6).contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+        }
+    }
+
+    // Stylistic variants: "a.sc" -> "a"
+    if let Some((base, _)) = name.split_once('.') {
+        if !base.is_empty() {
+            return glyph_name_to_unicode(base);
+        }
+    }
+
+    None
+}
+
+/// Glyph names sorted by byte order for binary search
+static GLYPH_NAMES: &[(&str, char)] = &[
+    ("A", 'A'),
+    ("AE", 'Æ'),
+    ("Aacute", 'Á'),
+    ("Abreve", 'Ă'),
+    ("Acircumflex", 'Â'),
+    ("Adieresis", 'Ä'),
+    ("Agrave", 'À'),
+    ("Amacron", 'Ā'),
+    ("Aogonek", 'Ą'),
+    ("Aring", 'Å'),
+    ("Atilde", 'Ã'),
+    ("B", 'B'),
+    ("C", 'C'),
+    ("Cacute", 'Ć'),
+    ("Ccaron", 'Č'),
+    ("Ccedilla", 'Ç'),
+    ("D", 'D'),
+    ("Dcaron", 'Ď'),
+    ("Dcroat", 'Đ'),
+    ("Delta", '∆'),
+    ("E", 'E'),
+    ("Eacute", 'É'),
+    ("Ecaron", 'Ě'),
+    ("Ecircumflex", 'Ê'),
+    ("Edieresis", 'Ë'),
+    ("Edotaccent", 'Ė'),
+    ("Egrave", 'È'),
+    ("Emacron", 'Ē'),
+    ("Eogonek", 'Ę'),
+    ("Eth", 'Ð'),
+    ("Euro", '€'),
+    ("F", 'F'),
+    ("G", 'G'),
+    ("Gbreve", 'Ğ'),
+    ("Gcommaaccent", 'Ģ'),
+    ("H", 'H'),
+    ("I", 'I'),
+    ("Iacute", 'Í'),
+    ("Icircumflex", 'Î'),
+    ("Idieresis", 'Ï'),
+    ("Idotaccent", 'İ'),
+    ("Igrave", 'Ì'),
+    ("Imacron", 'Ī'),
+    ("Iogonek", 'Į'),
+    ("J", 'J'),
+    ("K", 'K'),
+    ("Kcommaaccent", 'Ķ'),
+    ("L", 'L'),
+    ("Lacute", 'Ĺ'),
+    ("Lcaron", 'Ľ'),
+    ("Lcommaaccent", 'Ļ'),
+    ("Lslash", 'Ł'),
+    ("M", 'M'),
+    ("N", 'N'),
+    ("Nacute", 'Ń'),
+    ("Ncaron", 'Ň'),
+    ("Ncommaaccent", 'Ņ'),
+    ("Ntilde", 'Ñ'),
+    ("O", 'O'),
+    ("OE", 'Œ'),
+    ("Oacute", 'Ó'),
+    ("Ocircumflex", 'Ô'),
+    ("Odieresis", 'Ö'),
+    ("Ograve", 'Ò'),
+    ("Ohungarumlaut", 'Ő'),
+    ("Omacron", 'Ō'),
+    ("Omega", 'Ω'),
+    ("Oslash", 'Ø'),
+    ("Otilde", 'Õ'),
+    ("P", 'P'),
+    ("Q", 'Q'),
+    ("R", 'R'),
+    ("Racute", 'Ŕ'),
+    ("Rcaron", 'Ř'),
+    ("Rcommaaccent", 'Ŗ'),
+    ("S", 'S'),
+    ("Sacute", 'Ś'),
+    ("Scaron", 'Š'),
+    ("Scedilla", 'Ş'),
+    ("T", 'T'),
+    ("Tcaron", 'Ť'),
+    ("Tcommaaccent", 'Ţ'),
+    ("Thorn", 'Þ'),
+    ("U", 'U'),
+    ("Uacute", 'Ú'),
+    ("Ucircumflex", 'Û'),
+    ("Udieresis", 'Ü'),
+    ("Ugrave", 'Ù'),
+    ("Uhungarumlaut", 'Ű'),
+    ("Umacron", 'Ū'),
+    ("Uogonek", 'Ų'),
+    ("Uring", 'Ů'),
+    ("V", 'V'),
+    ("W", 'W'),
+    ("X", 'X'),
+    ("Y", 'Y'),
+    ("Yacute", 'Ý'),
+    ("Ydieresis", 'Ÿ'),
+    ("Z", 'Z'),
+    ("Zacute", 'Ź'),
+    ("Zcaron", 'Ž'),
+    ("Zdotaccent", 'Ż'),
+    ("a", 'a'),
+    ("aacute", 'á'),
+    ("abreve", 'ă'),
+    ("acircumflex", 'â'),
+    ("acute", '´'),
+    ("adieresis", 'ä'),
+    ("ae", 'æ'),
+    ("agrave", 'à'),
+    ("amacron", 'ā'),
+    ("ampersand", '&'),
+    ("aogonek", 'ą'),
+    ("approxequal", '≈'),
+    ("aring", 'å'),
+    ("arrowboth", '↔'),
+    ("arrowdown", '↓'),
+    ("arrowleft", '←'),
+    ("arrowright", '→'),
+    ("arrowup", '↑'),
+    ("asciicircum", '^'),
+    ("asciitilde", '~'),
+    ("asterisk", '*'),
+    ("at", '@'),
+    ("atilde", 'ã'),
+    ("b", 'b'),
+    ("backslash", '\\'),
+    ("bar", '|'),
+    ("braceleft", '{'),
+    ("braceright", '}'),
+    ("bracketleft", '['),
+    ("bracketright", ']'),
+    ("breve", '˘'),
+    ("brokenbar", '¦'),
+    ("bullet", '•'),
+    ("c", 'c'),
+    ("cacute", 'ć'),
+    ("caron", 'ˇ'),
+    ("ccaron", 'č'),
+    ("ccedilla", 'ç'),
+    ("cedilla", '¸'),
+    ("cent", '¢'),
+    ("checkmark", '✓'),
+    ("circumflex", 'ˆ'),
+    ("colon", ':'),
+    ("comma", ','),
+    ("commaaccent", '\u{F6C3}'),
+    ("copyright", '©'),
+    ("currency", '¤'),
+    ("d", 'd'),
+    ("dagger", '†'),
+    ("daggerdbl", '‡'),
+    ("dcaron", 'ď'),
+    ("dcroat", 'đ'),
+    ("degree", '°'),
+    ("dieresis", '¨'),
+    ("divide", '÷'),
+    ("dollar", '$'),
+    ("dotaccent", '˙'),
+    ("dotlessi", 'ı'),
+    ("dotlessj", 'ȷ'),
+    ("e", 'e'),
+    ("eacute", 'é'),
+    ("ecaron", 'ě'),
+    ("ecircumflex", 'ê'),
+    ("edieresis", 'ë'),
+    ("edotaccent", 'ė'),
+    ("egrave", 'è'),
+    ("eight", '8'),
+    ("ellipsis", '…'),
+    ("emacron", 'ē'),
+    ("emdash", '—'),
+    ("endash", '–'),
+    ("eogonek", 'ę'),
+    ("equal", '='),
+    ("estimated", '℮'),
+    ("eth", 'ð'),
+    ("exclam", '!'),
+    ("exclamdown", '¡'),
+    ("f", 'f'),
+    ("ff", 'ﬀ'),
+    ("ffi", 'ﬃ'),
+    ("ffl", 'ﬄ'),
+    ("fi", 'ﬁ'),
+    ("figuredash", '‒'),
+    ("five", '5'),
+    ("fl", 'ﬂ'),
+    ("florin", 'ƒ'),
+    ("four", '4'),
+    ("fraction", '⁄'),
+    ("g", 'g'),
+    ("gbreve", 'ğ'),
+    ("gcommaaccent", 'ģ'),
+    ("germandbls", 'ß'),
+    ("grave", '`'),
+    ("greater", '>'),
+    ("greaterequal", '≥'),
+    ("guillemotleft", '«'),
+    ("guillemotright", '»'),
+    ("guilsinglleft", '‹'),
+    ("guilsinglright", '›'),
+    ("h", 'h'),
+    ("hungarumlaut", '˝'),
+    ("hyphen", '-'),
+    ("i", 'i'),
+    ("iacute", 'í'),
+    ("icircumflex", 'î'),
+    ("idieresis", 'ï'),
+    ("igrave", 'ì'),
+    ("imacron", 'ī'),
+    ("infinity", '∞'),
+    ("integral", '∫'),
+    ("iogonek", 'į'),
+    ("j", 'j'),
+    ("k", 'k'),
+    ("kcommaaccent", 'ķ'),
+    ("l", 'l'),
+    ("lacute", 'ĺ'),
+    ("lcaron", 'ľ'),
+    ("lcommaaccent", 'ļ'),
+    ("less", '<'),
+    ("lessequal", '≤'),
+    ("logicalnot", '¬'),
+    ("lozenge", '◊'),
+    ("lslash", 'ł'),
+    ("m", 'm'),
+    ("macron", '¯'),
+    ("middot", '·'),
+    ("minus", '−'),
+    ("minute", '′'),
+    ("mu", 'µ'),
+    ("multiply", '×'),
+    ("n", 'n'),
+    ("nacute", 'ń'),
+    ("nbspace", '\u{00A0}'),
+    ("ncaron", 'ň'),
+    ("ncommaaccent", 'ņ'),
+    ("nine", '9'),
+    ("notequal", '≠'),
+    ("ntilde", 'ñ'),
+    ("numbersign", '#'),
+    ("numero", '№'),
+    ("o", 'o'),
+    ("oacute", 'ó'),
+    ("ocircumflex", 'ô'),
+    ("odieresis", 'ö'),
+    ("oe", 'œ'),
+    ("ogonek", '˛'),
+    ("ograve", 'ò'),
+    ("ohungarumlaut", 'ő'),
+    ("omacron", 'ō'),
+    ("one", '1'),
+    ("onehalf", '½'),
+    ("onequarter", '¼'),
+    ("onesuperior", '¹'),
+    ("ordfeminine", 'ª'),
+    ("ordmasculine", 'º'),
+    ("oslash", 'ø'),
+    ("otilde", 'õ'),
+    ("p", 'p'),
+    ("paragraph", '¶'),
+    ("parenleft", '('),
+    ("parenright", ')'),
+    ("partialdiff", '∂'),
+    ("percent", '%'),
+    ("period", '.'),
+    ("periodcentered", '·'),
+    ("perthousand", '‰'),
+    ("pi", 'π'),
+    ("plus", '+'),
+    ("plusminus", '±'),
+    ("product", '∏'),
+    ("q", 'q'),
+    ("question", '?'),
+    ("questiondown", '¿'),
+    ("quotedbl", '"'),
+    ("quotedblbase", '„'),
+    ("quotedblleft", '“'),
+    ("quotedblright", '”'),
+    ("quoteleft", '‘'),
+    ("quotereversed", '‛'),
+    ("quoteright", '’'),
+    ("quotesinglbase", '‚'),
+    ("quotesingle", '\''),
+    ("r", 'r'),
+    ("racute", 'ŕ'),
+    ("radical", '√'),
+    ("rcaron", 'ř'),
+    ("rcommaaccent", 'ŗ'),
+    ("registered", '®'),
+    ("ring", '˚'),
+    ("s", 's'),
+    ("sacute", 'ś'),
+    ("scaron", 'š'),
+    ("scedilla", 'ş'),
+    ("second", '″'),
+    ("section", '§'),
+    ("semicolon", ';'),
+    ("seven", '7'),
+    ("sfthyphen", '\u{00AD}'),
+    ("six", '6'),
+    ("slash", '/'),
+    ("softhyphen", '\u{00AD}'),
+    ("space", '\u{0020}'),
+    ("sterling", '£'),
+    ("summation", '∑'),
+    ("t", 't'),
+    ("tcaron", 'ť'),
+    ("tcommaaccent", 'ţ'),
+    ("thorn", 'þ'),
+    ("three", '3'),
+    ("threequarters", '¾'),
+    ("threesuperior", '³'),
+    ("tilde", '˜'),
+    ("trademark", '™'),
+    ("two", '2'),
+    ("twosuperior", '²'),
+    ("u", 'u'),
+    ("uacute", 'ú'),
+    ("ucircumflex", 'û'),
+    ("udieresis", 'ü'),
+    ("ugrave", 'ù'),
+    ("uhungarumlaut", 'ű'),
+    ("umacron", 'ū'),
+    ("underscore", '_'),
+    ("uogonek", 'ų'),
+    ("uring", 'ů'),
+    ("v", 'v'),
+    ("w", 'w'),
+    ("x", 'x'),
+    ("y", 'y'),
+    ("yacute", 'ý'),
+    ("ydieresis", 'ÿ'),
+    ("yen", '¥'),
+    ("z", 'z'),
+    ("zacute", 'ź'),
+    ("zcaron", 'ž'),
+    ("zdotaccent", 'ż'),
+    ("zero", '0'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_is_sorted() {
+        assert!(GLYPH_NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_common_names() {
+        assert_eq!(glyph_name_to_unicode("A"), Some('A'));
+        assert_eq!(glyph_name_to_unicode("eacute"), Some('é'));
+        assert_eq!(glyph_name_to_unicode("quoteright"), Some('\u{2019}'));
+        assert_eq!(glyph_name_to_unicode("Euro"), Some('€'));
+    }
+
+    #[test]
+    fn test_uni_and_suffix_names() {
+        assert_eq!(glyph_name_to_unicode("uni00E9"), Some('é'));
+        assert_eq!(glyph_name_to_unicode("u1F600"), Some('😀'));
+        assert_eq!(glyph_name_to_unicode("a.sc"), Some('a'));
+        assert_eq!(glyph_name_to_unicode("g123"), None);
+    }
+}
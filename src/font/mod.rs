@@ -1,5 +1,11 @@
 mod encoding;
 mod cmap;
+mod glyphlist;
+mod pdfdoc;
 
-pub use encoding::FontEncoding;
-pub use cmap::parse_tounicode_cmap;
+pub use encoding::{FontEncoding, expand_ligatures};
+pub use cmap::{
+    parse_cid_ranges, parse_codespace_ranges, parse_tounicode_cmap, CidRange, CodespaceRange,
+};
+pub use glyphlist::glyph_name_to_unicode;
+pub use pdfdoc::pdfdoc_decode;
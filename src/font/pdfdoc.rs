@@ -0,0 +1,86 @@
+/// Decode a text string stored in PDFDocEncoding.
+///
+/// PDFDocEncoding matches Latin-1 except for 0x18-0x1F (spacing accents)
+/// and 0x7F-0xA0 (typographic punctuation and a few letters). Codes the
+/// encoding leaves undefined decode to U+FFFD.
+pub fn pdfdoc_decode(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| pdfdoc_char(b)).collect()
+}
+
+fn pdfdoc_char(b: u8) -> char {
+    match b {
+        0x18..=0x1F => SPACING_ACCENTS[(b - 0x18) as usize],
+        0x7F..=0xA0 => HIGH_PUNCTUATION[(b - 0x7F) as usize],
+        _ => b as char,
+    }
+}
+
+/// 0x18-0x1F
+static SPACING_ACCENTS: [char; 8] = [
+    '\u{02D8}', // breve
+    '\u{02C7}', // caron
+    '\u{02C6}', // circumflex
+    '\u{02D9}', // dotaccent
+    '\u{02DD}', // hungarumlaut
+    '\u{02DB}', // ogonek
+    '\u{02DA}', // ring
+    '\u{02DC}', // tilde
+];
+
+/// 0x7F-0xA0
+static HIGH_PUNCTUATION: [char; 34] = [
+    '\u{FFFD}', // undefined
+    '\u{2022}', // bullet
+    '\u{2020}', // dagger
+    '\u{2021}', // daggerdbl
+    '\u{2026}', // ellipsis
+    '\u{2014}', // emdash
+    '\u{2013}', // endash
+    '\u{0192}', // florin
+    '\u{2044}', // fraction
+    '\u{2039}', // guilsinglleft
+    '\u{203A}', // guilsinglright
+    '\u{2212}', // minus
+    '\u{2030}', // perthousand
+    '\u{201E}', // quotedblbase
+    '\u{201C}', // quotedblleft
+    '\u{201D}', // quotedblright
+    '\u{2018}', // quoteleft
+    '\u{2019}', // quoteright
+    '\u{201A}', // quotesinglbase
+    '\u{2122}', // trademark
+    '\u{FB01}', // fi
+    '\u{FB02}', // fl
+    '\u{0141}', // Lslash
+    '\u{0152}', // OE
+    '\u{0160}', // Scaron
+    '\u{0178}', // Ydieresis
+    '\u{017D}', // Zcaron
+    '\u{0131}', // dotlessi
+    '\u{0142}', // lslash
+    '\u{0153}', // oe
+    '\u{0161}', // scaron
+    '\u{017E}', // zcaron
+    '\u{FFFD}', // undefined
+    '\u{20AC}', // Euro
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latin1_range_unchanged() {
+        assert_eq!(pdfdoc_decode(b"Caf\xe9 \xa9"), "Caf\u{e9} \u{a9}");
+    }
+
+    #[test]
+    fn test_codes_that_differ_from_latin1() {
+        assert_eq!(pdfdoc_decode(&[0x18, 0x1F]), "\u{02D8}\u{02DC}");
+        assert_eq!(pdfdoc_decode(&[0x88, 0x89]), "\u{2039}\u{203A}");
+        assert_eq!(pdfdoc_decode(&[0x8D, 0x8E, 0x90]), "\u{201C}\u{201D}\u{2019}");
+        assert_eq!(pdfdoc_decode(&[0x8B, 0x92]), "\u{2030}\u{2122}");
+        assert_eq!(pdfdoc_decode(&[0x80, 0xA0]), "\u{2022}\u{20AC}");
+        assert_eq!(pdfdoc_decode(&[0x7F, 0x9F]), "\u{FFFD}\u{FFFD}");
+    }
+}
@@ -1,75 +1,134 @@
 pub mod content;
+mod crypt;
 pub mod decode;
 pub mod document;
 pub mod error;
 pub mod extract;
 pub mod font;
+pub mod output;
 pub mod parser;
 pub mod types;
 
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
-pub use content::TextSpan;
+pub use content::{MergeConfig, ParseReport, TextSpan, Word};
 pub use decode::decode_stream;
-pub use document::Document;
+pub use document::{
+    Annotation, CcittParams, Diagnostic, DiagnosticKind, Document, DocumentInfo, EncryptionInfo,
+    ExtractOptions, FontInfo, FormField, ImageFormat, LineJoinOptions, Link, LinkTarget, Objects,
+    OutlineItem, OwnedDocument, PageBox, Pages, ParseOptions, PdfImage, SearchHit,
+    ValidationIssue, ValidationIssueKind, XrefKind,
+};
 pub use error::{PdfError, Result};
-pub use extract::{classify_spans, elements_to_markdown, elements_to_txt, PageElement, Table};
+pub use extract::{
+    classify_spans, classify_spans_with_options, elements_to_csv, elements_to_html,
+    elements_to_markdown, elements_to_tsv, elements_to_txt, ColumnType, LayoutOptions,
+    PageElement, Table, TableCell, TableOptions, TextRun,
+};
+#[cfg(feature = "serde")]
+pub use extract::{pages_to_json, spans_to_json};
+pub use output::write_text_layer;
 pub use types::{ObjRef, PdfObject};
 
-/// Extract all text from a PDF as plain text (layout-aware)
-pub fn pdf_to_text(data: &[u8]) -> Result<String> {
+/// Text spans or error of each page in order: parsed lazily one page at a
+/// time, or all at once in parallel with the `parallel` feature
+#[cfg(not(feature = "parallel"))]
+fn page_spans<'d, 'a>(
+    doc: &'d mut Document<'a>,
+    options: &ExtractOptions,
+) -> Result<impl Iterator<Item = Result<Vec<TextSpan>>> + use<'d, 'a>> {
+    // Walk the page tree up front so that a broken one is an error for the
+    // whole document rather than for a page
+    doc.page_count_exact()?;
+    Ok(doc.pages_with(*options))
+}
+
+#[cfg(feature = "parallel")]
+fn page_spans(
+    doc: &mut Document,
+    options: &ExtractOptions,
+) -> Result<impl Iterator<Item = Result<Vec<TextSpan>>>> {
+    Ok(doc.extract_page_results_parallel(options)?.into_iter())
+}
+
+/// Render each page with `render`, one after another. So that one corrupt
+/// page doesn't lose the rest of the document, a page that fails to extract
+/// is replaced by a `[page N could not be extracted: error]` line, passed
+/// through `placeholder`, unless `options.fail_fast` is set.
+fn render_pages(
+    data: &[u8],
+    options: &ExtractOptions,
+    render: impl Fn(Vec<TextSpan>) -> String,
+    placeholder: impl Fn(String) -> String,
+) -> Result<String> {
     let mut doc = Document::parse(data)?;
-    let page_count = doc.page_count()?;
     let mut output = String::new();
 
-    for page_idx in 0..page_count {
-        let spans = doc.extract_page_text(page_idx)?;
-        let elements = classify_spans(spans);
+    for (page_index, spans) in page_spans(&mut doc, options)?.enumerate() {
+        let page = match spans {
+            Ok(spans) => render(spans),
+            Err(e) if !options.fail_fast => placeholder(format!(
+                "[page {} could not be extracted: {}]",
+                page_index + 1,
+                e
+            )),
+            Err(e) => return Err(e),
+        };
 
         if !output.is_empty() {
             output.push('\n');
         }
-        output.push_str(&elements_to_txt(&elements));
+        output.push_str(&page);
     }
 
     Ok(output)
 }
 
-/// Extract all text from a PDF as Markdown (layout-aware)
-pub fn pdf_to_markdown(data: &[u8]) -> Result<String> {
-    let mut doc = Document::parse(data)?;
-    let page_count = doc.page_count()?;
-    let mut output = String::new();
+/// Extract all text from a PDF as plain text (layout-aware)
+pub fn pdf_to_text(data: &[u8]) -> Result<String> {
+    pdf_to_text_with(data, &ExtractOptions::default())
+}
 
-    for page_idx in 0..page_count {
-        let spans = doc.extract_page_text(page_idx)?;
-        let elements = classify_spans(spans);
+/// Extract all text from a PDF as plain text with explicit options
+pub fn pdf_to_text_with(data: &[u8], options: &ExtractOptions) -> Result<String> {
+    render_pages(
+        data,
+        options,
+        |spans| elements_to_txt(&classify_spans_with_options(spans, options.layout_options())),
+        |line| line + "\n",
+    )
+}
 
-        if !output.is_empty() {
-            output.push('\n');
-        }
-        output.push_str(&elements_to_markdown(&elements));
-    }
+/// Extract all text from a PDF as Markdown (layout-aware)
+pub fn pdf_to_markdown(data: &[u8]) -> Result<String> {
+    pdf_to_markdown_with(data, &ExtractOptions::default())
+}
 
-    Ok(output)
+/// Extract all text from a PDF as Markdown with explicit options
+pub fn pdf_to_markdown_with(data: &[u8], options: &ExtractOptions) -> Result<String> {
+    render_pages(
+        data,
+        options,
+        |spans| {
+            elements_to_markdown(&classify_spans_with_options(spans, options.layout_options()))
+        },
+        |line| line + "\n",
+    )
 }
 
 /// Extract all text from a PDF as CSV
 pub fn pdf_to_csv(data: &[u8]) -> Result<String> {
-    let mut doc = Document::parse(data)?;
-    let page_count = doc.page_count()?;
-    let mut output = String::new();
-
-    for page_idx in 0..page_count {
-        let spans = doc.extract_page_text(page_idx)?;
-        let table = Table::from_spans(spans);
-
-        if !output.is_empty() {
-            output.push('\n');
-        }
-        output.push_str(&table.to_csv());
-    }
+    pdf_to_csv_with(data, &ExtractOptions::default())
+}
 
-    Ok(output)
+/// Extract all text from a PDF as CSV with explicit options. A page that
+/// fails to extract is noted in a `#` comment line.
+pub fn pdf_to_csv_with(data: &[u8], options: &ExtractOptions) -> Result<String> {
+    render_pages(
+        data,
+        options,
+        |spans| Table::from_spans_with(spans, options.table_options).to_csv(),
+        |line| format!("# {}", line),
+    )
 }
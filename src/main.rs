@@ -1,20 +1,199 @@
 use std::fs;
-use pdf_text_extract::{classify_spans, elements_to_markdown, elements_to_txt, Document, Table};
+use pdf_text_extract::{
+    classify_spans, elements_to_csv, elements_to_html, elements_to_markdown, elements_to_tsv,
+    elements_to_txt, Document, XrefKind,
+};
+#[cfg(feature = "serde")]
+use pdf_text_extract::pages_to_json;
 
 fn print_usage(program: &str) {
     eprintln!("Usage: {} <pdf-file> [options]", program);
     eprintln!();
     eprintln!("Options:");
-    eprintln!("  --csv       Output as CSV (default)");
-    eprintln!("  --tsv       Output as TSV (tab-separated)");
-    eprintln!("  --text      Output as aligned text");
+    eprintln!("  --csv       Output tables as CSV, other text as # comments (default)");
+    eprintln!("  --tsv       Output tables as TSV, other text as # comments");
+    eprintln!("  --text      Output as plain text with aligned tables (same as --txt)");
     eprintln!("  --txt       Output as plain text (headings, paragraphs, tables)");
     eprintln!("  --md        Output as Markdown");
+    eprintln!("  --html      Output as HTML");
     eprintln!("  --raw       Output raw text spans with positions");
+    eprintln!("  --raw-unmerged  Like --raw, one span per text operator before merging");
+    eprintln!("  --json      Output text spans with positions as JSON (serde feature)");
+    eprintln!("  --dump-content  Output the decoded content stream of each page");
+    eprintln!("  --info      Print page count, version, metadata, encryption, filters");
+    eprintln!("              and xref type instead of extracting text");
+    eprintln!("  --pages R   Extract only pages R, e.g. 1-3,7,9- (1-indexed)");
     eprintln!("  --page N    Extract only page N (1-indexed)");
     eprintln!("  -o FILE     Write output to FILE instead of stdout");
 }
 
+/// Options given after the PDF path
+#[derive(Debug, PartialEq)]
+struct Options {
+    format: &'static str,
+    output_file: Option<String>,
+    page_spec: Option<String>,
+}
+
+/// Parse the options after the PDF path. Page specs are checked here, before
+/// the file is read; whether the pages exist is checked once it has been.
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options { format: "csv", output_file: None, page_spec: None };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--csv" => options.format = "csv",
+            "--tsv" => options.format = "tsv",
+            "--text" => options.format = "text",
+            "--txt" => options.format = "txt",
+            "--md" => options.format = "md",
+            "--html" => options.format = "html",
+            "--raw" => options.format = "raw",
+            "--raw-unmerged" => options.format = "raw-unmerged",
+            #[cfg(feature = "serde")]
+            "--json" => options.format = "json",
+            #[cfg(not(feature = "serde"))]
+            "--json" => return Err("--json requires the serde feature".to_string()),
+            "--dump-content" => options.format = "content",
+            "--info" => options.format = "info",
+            "--pages" | "--page" => {
+                let spec = args.next().ok_or_else(|| format!("{} requires a value", arg))?;
+                parse_page_ranges(spec)?;
+                options.page_spec = Some(spec.clone());
+            }
+            "-o" => {
+                let file = args.next().ok_or("-o requires a file name")?;
+                options.output_file = Some(file.clone());
+            }
+            _ => {}
+        }
+    }
+    Ok(options)
+}
+
+/// Parse a page range spec such as `1-3,7,9-` into 1-based inclusive
+/// ranges, in the order given. An open range (`9-`) has no end.
+fn parse_page_ranges(spec: &str) -> Result<Vec<(usize, Option<usize>)>, String> {
+    let invalid = || format!("invalid page argument: {}", spec);
+    let page = |s: &str| match s.trim().parse::<usize>() {
+        Ok(p) if p >= 1 => Ok(p),
+        _ => Err(invalid()),
+    };
+
+    spec.split(',')
+        .map(|part| {
+            let (first, last) = match part.split_once('-') {
+                Some((first, "")) => (page(first)?, None),
+                Some((first, last)) => (page(first)?, Some(page(last)?)),
+                None => {
+                    let p = page(part)?;
+                    (p, Some(p))
+                }
+            };
+            if last.is_some_and(|last| first > last) {
+                return Err(invalid());
+            }
+            Ok((first, last))
+        })
+        .collect()
+}
+
+/// Parse a page range spec into 0-based page indices, in the order given.
+/// An open range runs to the last page.
+fn parse_page_spec(spec: &str, page_count: usize) -> Result<Vec<usize>, String> {
+    let mut pages = Vec::new();
+    for (first, last) in parse_page_ranges(spec)? {
+        let last = last.unwrap_or(page_count);
+        if let Some(p) = [first, last].into_iter().find(|&p| p > page_count) {
+            return Err(format!("page {} out of range (document has {} pages)", p, page_count));
+        }
+        pages.extend(first - 1..last);
+    }
+    Ok(pages)
+}
+
+/// Key/value lines describing the document, read without extracting any
+/// page so that they are available even when pages fail
+fn info_report(doc: &mut Document) -> String {
+    let mut lines = Vec::new();
+    let mut line = |key: &str, value: String| {
+        lines.push(format!("{:<13}{}", format!("{}:", key), value));
+    };
+
+    match doc.page_count() {
+        Ok(count) => line("Pages", count.to_string()),
+        Err(e) => line("Pages", format!("unknown ({})", e)),
+    }
+    line("PDF version", doc.version());
+    match doc.info() {
+        Ok(info) => {
+            let fields = [
+                ("Title", info.title),
+                ("Author", info.author),
+                ("Subject", info.subject),
+                ("Keywords", info.keywords),
+                ("Creator", info.creator),
+                ("Producer", info.producer),
+                ("Created", info.creation_date),
+                ("Modified", info.mod_date),
+            ];
+            for (key, value) in fields {
+                if let Some(value) = value {
+                    line(key, value);
+                }
+            }
+        }
+        Err(e) => eprintln!("Error reading document info: {}", e),
+    }
+    let encrypted = match doc.encryption_info() {
+        Some(info) => format!(
+            "yes (V {}, R {}, {}-bit key)",
+            info.version, info.revision, info.key_bits
+        ),
+        None if doc.is_encrypted() => "yes (unreadable /Encrypt)".to_string(),
+        None => "no".to_string(),
+    };
+    line("Encrypted", encrypted);
+    let xref = match doc.xref_kind() {
+        XrefKind::Table => "table",
+        XrefKind::Hybrid => "table with xref stream (hybrid)",
+        XrefKind::Stream => "stream",
+        XrefKind::Rebuilt => "rebuilt (damaged)",
+    };
+    line("Xref", xref.to_string());
+    let filters = doc.stream_filters();
+    line("Filters", if filters.is_empty() { "none".to_string() } else { filters.join(", ") });
+
+    lines.join("\n")
+}
+
+/// Print the document's diagnostics to stderr
+fn print_diagnostics(doc: &mut Document) {
+    for diagnostic in doc.take_diagnostics() {
+        match diagnostic.page {
+            Some(page) => eprintln!("Warning (page {}): {}", page + 1, diagnostic.message),
+            None => eprintln!("Warning: {}", diagnostic.message),
+        }
+    }
+}
+
+/// Write the output to a file, or to stdout without one
+fn write_output(output_file: Option<String>, output: &str) {
+    match output_file {
+        Some(path) => {
+            if let Err(e) = fs::write(&path, output) {
+                eprintln!("Failed to write output: {}", e);
+                std::process::exit(1);
+            }
+            eprintln!("Output written to: {}", path);
+        }
+        None => {
+            println!("{}", output);
+        }
+    }
+}
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
@@ -31,35 +210,13 @@ fn main() {
     }
 
     // Parse options
-    let mut format = "csv";
-    let mut output_file: Option<String> = None;
-    let mut page_filter: Option<usize> = None;
-
-    let mut i = 2;
-    while i < args.len() {
-        match args[i].as_str() {
-            "--csv" => format = "csv",
-            "--tsv" => format = "tsv",
-            "--text" => format = "text",
-            "--txt" => format = "txt",
-            "--md" => format = "md",
-            "--raw" => format = "raw",
-            "--page" => {
-                i += 1;
-                if i < args.len() {
-                    page_filter = args[i].parse().ok();
-                }
-            }
-            "-o" => {
-                i += 1;
-                if i < args.len() {
-                    output_file = Some(args[i].clone());
-                }
-            }
-            _ => {}
+    let Options { format, output_file, page_spec } = match parse_options(&args[2..]) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
-        i += 1;
-    }
+    };
 
     // Read PDF
     eprintln!("Reading: {}", path);
@@ -83,26 +240,55 @@ fn main() {
 
     eprintln!("PDF parsed successfully!");
 
-    let page_count = doc.page_count().unwrap_or(0);
+    if format == "info" {
+        let report = info_report(&mut doc);
+        print_diagnostics(&mut doc);
+        write_output(output_file, &report);
+        return;
+    }
+
+    let page_count = doc.page_count_exact().unwrap_or(0);
     eprintln!("Page count: {}", page_count);
 
     // Determine which pages to process
-    let pages: Vec<usize> = match page_filter {
-        Some(p) if p >= 1 && p <= page_count => vec![p - 1],
-        Some(p) => {
-            eprintln!("Invalid page number: {} (document has {} pages)", p, page_count);
-            std::process::exit(1);
-        }
+    let pages: Vec<usize> = match page_spec {
+        Some(spec) => match parse_page_spec(&spec, page_count) {
+            Ok(pages) => pages,
+            Err(e) => {
+                eprintln!("Invalid --pages value: {}", e);
+                std::process::exit(1);
+            }
+        },
         None => (0..page_count).collect(),
     };
 
     // Collect output
     let mut output = String::new();
+    let mut json_pages = Vec::new();
 
     for page_idx in pages {
-        match doc.extract_page_text(page_idx) {
+        if format == "content" {
+            match doc.page_content_stream(page_idx) {
+                Ok(content) => {
+                    output.push_str(&format!("--- Page {} ---\n", page_idx + 1));
+                    output.push_str(&String::from_utf8_lossy(&content));
+                    output.push('\n');
+                }
+                Err(e) => eprintln!("Error reading page {} content: {}", page_idx + 1, e),
+            }
+            continue;
+        }
+
+        let spans = if format == "raw-unmerged" {
+            doc.extract_page_spans_raw(page_idx)
+        } else {
+            doc.extract_page_text(page_idx)
+        };
+        match spans {
             Ok(spans) => {
-                if format == "raw" {
+                if format == "json" {
+                    json_pages.push((page_idx + 1, spans));
+                } else if format == "raw" || format == "raw-unmerged" {
                     // Raw output with positions
                     if !output.is_empty() {
                         output.push_str("\n--- Page {} ---\n");
@@ -113,7 +299,7 @@ fn main() {
                             span.x, span.y, span.font_size, span.text
                         ));
                     }
-                } else if format == "txt" || format == "md" {
+                } else {
                     // Layout-aware extraction
                     let elements = classify_spans(spans);
 
@@ -122,23 +308,11 @@ fn main() {
                     }
 
                     match format {
-                        "txt" => output.push_str(&elements_to_txt(&elements)),
+                        "txt" | "text" => output.push_str(&elements_to_txt(&elements)),
                         "md" => output.push_str(&elements_to_markdown(&elements)),
-                        _ => unreachable!(),
-                    }
-                } else {
-                    // Table extraction
-                    let table = Table::from_spans(spans);
-
-                    if !output.is_empty() {
-                        output.push('\n');
-                    }
-
-                    match format {
-                        "csv" => output.push_str(&table.to_csv()),
-                        "tsv" => output.push_str(&table.to_tsv()),
-                        "text" => output.push_str(&table.to_text()),
-                        _ => output.push_str(&table.to_csv()),
+                        "html" => output.push_str(&elements_to_html(&elements)),
+                        "tsv" => output.push_str(&elements_to_tsv(&elements)),
+                        _ => output.push_str(&elements_to_csv(&elements)),
                     }
                 }
             }
@@ -148,17 +322,63 @@ fn main() {
         }
     }
 
-    // Write output
-    match output_file {
-        Some(path) => {
-            if let Err(e) = fs::write(&path, &output) {
-                eprintln!("Failed to write output: {}", e);
-                std::process::exit(1);
-            }
-            eprintln!("Output written to: {}", path);
+    #[cfg(feature = "serde")]
+    if format == "json" {
+        output = pages_to_json(&json_pages);
+    }
+
+    print_diagnostics(&mut doc);
+    write_output(output_file, &output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page_spec() {
+        assert_eq!(parse_page_spec("3", 10).unwrap(), vec![2]);
+        assert_eq!(parse_page_spec("1-3,7,9-", 10).unwrap(), vec![0, 1, 2, 6, 8, 9]);
+        assert_eq!(parse_page_spec("5, 2-3", 10).unwrap(), vec![4, 1, 2]);
+
+        for bad in ["", "0", "11", "3-2", "a-b", "1,,2", "-2", "2-11", "11-"] {
+            assert!(parse_page_spec(bad, 10).is_err(), "{:?} should be rejected", bad);
         }
-        None => {
-            println!("{}", output);
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let parse = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            parse_options(&args)
+        };
+
+        let options = parse(&["--md", "--page", "2", "-o", "out.md"]).unwrap();
+        assert_eq!(options.format, "md");
+        assert_eq!(options.page_spec.as_deref(), Some("2"));
+        assert_eq!(options.output_file.as_deref(), Some("out.md"));
+        assert_eq!(parse(&["--pages", "1-3,9-"]).unwrap().page_spec.as_deref(), Some("1-3,9-"));
+
+        for bad in ["foo", "0", "-1", "1.5", "2-1", "0-3"] {
+            let err = parse(&["--page", bad]).unwrap_err();
+            assert_eq!(err, format!("invalid page argument: {}", bad));
         }
+        assert_eq!(parse(&["--page"]).unwrap_err(), "--page requires a value");
+        assert_eq!(parse(&["-o"]).unwrap_err(), "-o requires a file name");
+    }
+
+    #[test]
+    fn test_info_report() {
+        let pdf = pdf_text_extract::write_text_layer(&[], [0.0, 0.0, 612.0, 792.0]);
+        let mut doc = Document::parse(&pdf).unwrap();
+        let report = info_report(&mut doc);
+        assert_eq!(
+            report,
+            "Pages:       1\n\
+             PDF version: 1.4\n\
+             Encrypted:   no\n\
+             Xref:        table\n\
+             Filters:     none"
+        );
     }
 }
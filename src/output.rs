@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::content::TextSpan;
+use crate::font::FontEncoding;
+
+/// Build a single-page PDF that places each span's text at its position,
+/// e.g. a searchable text sidecar for a scanned page.
+///
+/// Text is set in the built-in Helvetica font with WinAnsiEncoding, so
+/// characters outside that encoding are written as `?`. Spans with no
+/// usable font size are written at 12pt.
+pub fn write_text_layer(spans: &[TextSpan], media_box: [f64; 4]) -> Vec<u8> {
+    let winansi = winansi_codes();
+    let mut content = Vec::new();
+    for span in spans {
+        let size = if span.font_size > 0.0 { span.font_size } else { 12.0 };
+        content.extend(format!("BT /F0 {} Tf {} {} Td (", size, span.x, span.y).bytes());
+        for ch in span.text.chars() {
+            let byte = winansi.get(&ch).copied().unwrap_or(b'?');
+            if matches!(byte, b'(' | b')' | b'\\') {
+                content.push(b'\\');
+            }
+            content.push(byte);
+        }
+        content.extend(b") Tj ET\n");
+    }
+
+    let [x1, y1, x2, y2] = media_box;
+    let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+    stream.extend(content);
+    stream.extend(b"\nendstream");
+
+    let objects = [
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [{} {} {} {}] /Contents 4 0 R \
+             /Resources << /Font << /F0 5 0 R >> >> >>",
+            x1, y1, x2, y2
+        )
+        .into_bytes(),
+        stream,
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", i + 1).bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+    for offset in offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .bytes(),
+    );
+    pdf
+}
+
+/// Character -> byte code in WinAnsiEncoding
+fn winansi_codes() -> HashMap<char, u8> {
+    let encoding = FontEncoding::win_ansi();
+    (0x20..=0xFF).map(|b| (encoding.decode_byte(b), b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Document;
+
+    fn span(text: &str, x: f64, y: f64, font_size: f64) -> TextSpan {
+        TextSpan {
+            text: text.to_string(),
+            x,
+            y,
+            font_size,
+            font_name: None,
+            rise: 0.0,
+            width: 0.0,
+            height: 0.0,
+            color: None,
+            bold: false,
+            italic: false,
+            monospace: false,
+        }
+    }
+
+    #[test]
+    fn test_text_layer_round_trip() {
+        let spans = [
+            span("Invoice (draft)", 72.0, 720.0, 18.0),
+            span("Total: \u{20AC}1,200 \\ net", 72.5, 690.25, 10.0),
+        ];
+        let pdf = write_text_layer(&spans, [0.0, 0.0, 612.0, 792.0]);
+
+        let mut doc = Document::parse_strict(&pdf).unwrap();
+        let extracted = doc.extract_page_text(0).unwrap();
+        assert_eq!(extracted.len(), 2);
+        for (written, read) in spans.iter().zip(&extracted) {
+            assert_eq!(read.text, written.text);
+            assert_eq!((read.x, read.y), (written.x, written.y));
+            assert_eq!(read.font_size, written.font_size);
+        }
+    }
+
+    #[test]
+    fn test_unencodable_characters() {
+        let pdf = write_text_layer(&[span("\u{4e2d}a", 0.0, 0.0, 0.0)], [0.0, 0.0, 100.0, 100.0]);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("BT /F0 12 Tf 0 0 Td (?a) Tj ET"));
+    }
+}
@@ -38,11 +38,17 @@ pub enum Token {
 pub struct Lexer<'a> {
     data: &'a [u8],
     pos: usize,
+    /// Skip unknown keywords and unexpected bytes instead of failing
+    lenient: bool,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(data: &'a [u8]) -> Self {
-        Self { data, pos: 0 }
+        Self { data, pos: 0, lenient: false }
+    }
+
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
     }
 
     pub fn data(&self) -> &'a [u8] {
@@ -98,50 +104,59 @@ impl<'a> Lexer<'a> {
 
     /// Main entry point - get next token
     pub fn next_token(&mut self) -> Result<Option<Token>> {
-        self.skip_whitespace();
+        loop {
+            self.skip_whitespace();
 
-        let Some(b) = self.peek() else {
-            return Ok(None); // EOF
-        };
+            let Some(b) = self.peek() else {
+                return Ok(None); // EOF
+            };
 
-        match b {
-            b'[' => {
-                self.pos += 1;
-                Ok(Some(Token::ArrayStart))
-            }
-            b']' => {
-                self.pos += 1;
-                Ok(Some(Token::ArrayEnd))
-            }
-            b'<' => {
-                self.pos += 1;
-                if self.peek() == Some(b'<') {
+            match b {
+                b'[' => {
                     self.pos += 1;
-                    Ok(Some(Token::DictStart))
-                } else {
-                    self.read_hex_string().map(|s| Some(Token::HexString(s)))
+                    return Ok(Some(Token::ArrayStart));
                 }
-            }
-            b'>' => {
-                self.pos += 1;
-                if self.peek() == Some(b'>') {
+                b']' => {
+                    self.pos += 1;
+                    return Ok(Some(Token::ArrayEnd));
+                }
+                b'<' => {
+                    self.pos += 1;
+                    if self.peek() == Some(b'<') {
+                        self.pos += 1;
+                        return Ok(Some(Token::DictStart));
+                    }
+                    return self.read_hex_string().map(|s| Some(Token::HexString(s)));
+                }
+                b'>' => {
                     self.pos += 1;
-                    Ok(Some(Token::DictEnd))
-                } else {
-                    Err(PdfError::Parse {
+                    if self.peek() == Some(b'>') {
+                        self.pos += 1;
+                        return Ok(Some(Token::DictEnd));
+                    }
+                    if !self.lenient {
+                        return Err(PdfError::Parse {
+                            position: self.pos,
+                            message: "Unexpected '>'".into(),
+                        });
+                    }
+                }
+                b'(' => return self.read_literal_string().map(|s| Some(Token::String(s))),
+                b'/' => return self.read_name().map(|n| Some(Token::Name(n))),
+                b'+' | b'-' | b'.' | b'0'..=b'9' => return self.read_number().map(Some),
+                b'a'..=b'z' | b'A'..=b'Z' => match self.read_keyword() {
+                    // The keyword has been consumed; move on to the next token
+                    Err(_) if self.lenient => {}
+                    result => return result.map(Some),
+                },
+                _ if self.lenient => self.pos += 1,
+                _ => {
+                    return Err(PdfError::Parse {
                         position: self.pos,
-                        message: "Unexpected '>'".into(),
-                    })
+                        message: format!("Unexpected byte: 0x{:02X}", b),
+                    });
                 }
             }
-            b'(' => self.read_literal_string().map(|s| Some(Token::String(s))),
-            b'/' => self.read_name().map(|n| Some(Token::Name(n))),
-            b'+' | b'-' | b'.' | b'0'..=b'9' => self.read_number().map(Some),
-            b'a'..=b'z' | b'A'..=b'Z' => self.read_keyword().map(Some),
-            _ => Err(PdfError::Parse {
-                position: self.pos,
-                message: format!("Unexpected byte: 0x{:02X}", b),
-            }),
         }
     }
 
@@ -370,6 +385,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_simple_tokens() {
         let mut lexer = Lexer::new(b"42 3.14 true null");
 
@@ -423,4 +439,20 @@ mod tests {
         let token = lexer.next_token().unwrap().unwrap();
         assert_eq!(token, Token::Name("Font Name".into()));
     }
+
+    #[test]
+    fn test_lenient_skips_unknown_input() {
+        let input = b"1 foo } 2 > /N";
+        let mut lexer = Lexer::new(input);
+        assert_eq!(lexer.next_token().unwrap(), Some(Token::Int(1)));
+        assert!(lexer.next_token().is_err());
+
+        let mut lexer = Lexer::new(input);
+        lexer.set_lenient(true);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token().unwrap() {
+            tokens.push(token);
+        }
+        assert_eq!(tokens, vec![Token::Int(1), Token::Int(2), Token::Name("N".into())]);
+    }
 }
@@ -4,10 +4,17 @@ use crate::error::{PdfError, Result};
 use crate::parser::lexer::{Lexer, Token};
 use crate::types::{ObjRef, PdfObject};
 
+/// Looks up the value of an indirect stream /Length
+type LengthResolver<'a> = Box<dyn Fn(ObjRef) -> Option<usize> + 'a>;
+
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
     /// Lookahead buffer for handling "42 0 R" vs "42"
     peeked: Vec<Token>,
+    /// Resolves `/Length n 0 R` so binary streams are read exactly
+    length_resolver: Option<LengthResolver<'a>>,
+    /// Skip stray tokens and malformed entries instead of failing
+    lenient: bool,
 }
 
 impl<'a> Parser<'a> {
@@ -15,9 +22,30 @@ impl<'a> Parser<'a> {
         Self {
             lexer: Lexer::new(data),
             peeked: Vec::new(),
+            length_resolver: None,
+            lenient: false,
         }
     }
 
+    /// Recover from malformed syntax where possible: unknown keywords and
+    /// stray tokens are skipped, unterminated arrays and dictionaries are
+    /// closed, and dictionary entries without a name key or value dropped
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self.lexer.set_lenient(lenient);
+        self
+    }
+
+    /// Resolve indirect stream lengths with `resolver` instead of scanning
+    /// for `endstream`, which may also occur inside the stream data
+    pub fn with_length_resolver(
+        mut self,
+        resolver: impl Fn(ObjRef) -> Option<usize> + 'a,
+    ) -> Self {
+        self.length_resolver = Some(Box::new(resolver));
+        self
+    }
+
     pub fn position(&self) -> usize {
         self.lexer.position()
     }
@@ -43,36 +71,47 @@ impl<'a> Parser<'a> {
 
     /// Parse a single PDF object
     pub fn parse_object(&mut self) -> Result<Option<PdfObject>> {
-        let Some(token) = self.next_token()? else {
-            return Ok(None);
-        };
-
-        match token {
-            Token::Null => Ok(Some(PdfObject::Null)),
-            Token::True => Ok(Some(PdfObject::Bool(true))),
-            Token::False => Ok(Some(PdfObject::Bool(false))),
-            Token::Real(f) => Ok(Some(PdfObject::Real(f))),
-            Token::String(s) => Ok(Some(PdfObject::String(s))),
-            Token::HexString(s) => Ok(Some(PdfObject::String(s))),
-            Token::Name(n) => Ok(Some(PdfObject::Name(n))),
-            Token::ArrayStart => self.parse_array(),
-            Token::DictStart => self.parse_dict_or_stream(),
-
-            Token::Int(n) => {
-                // Could be: Int, or start of "42 0 R" reference
-                self.parse_int_or_ref(n)
-            }
-
-            // Skip "obj" keyword and parse the actual object
-            Token::Obj => self.parse_object(),
-
-            // Skip "endobj" - signals end of indirect object
-            Token::EndObj => Ok(None),
+        loop {
+            let Some(token) = self.next_token()? else {
+                return Ok(None);
+            };
 
-            _ => Err(PdfError::Parse {
-                position: self.position(),
-                message: format!("Unexpected token: {:?}", token),
-            }),
+            return match token {
+                Token::Null => Ok(Some(PdfObject::Null)),
+                Token::True => Ok(Some(PdfObject::Bool(true))),
+                Token::False => Ok(Some(PdfObject::Bool(false))),
+                Token::Real(f) => Ok(Some(PdfObject::Real(f))),
+                Token::String(s) => Ok(Some(PdfObject::String(s))),
+                Token::HexString(s) => Ok(Some(PdfObject::String(s))),
+                Token::Name(n) => Ok(Some(PdfObject::Name(n))),
+                Token::ArrayStart => self.parse_array(),
+                Token::DictStart => self.parse_dict_or_stream(),
+
+                Token::Int(n) => {
+                    // Could be: Int, or start of "42 0 R" reference
+                    self.parse_int_or_ref(n)
+                }
+
+                // Skip "obj" keyword and parse the actual object
+                Token::Obj => continue,
+
+                // Skip "endobj" - signals end of indirect object
+                Token::EndObj => Ok(None),
+
+                // A closing delimiter where a value belongs: leave it for
+                // the enclosing array or dictionary
+                Token::ArrayEnd | Token::DictEnd if self.lenient => {
+                    self.push_back(token);
+                    Ok(None)
+                }
+
+                _ if self.lenient => continue,
+
+                _ => Err(PdfError::Parse {
+                    position: self.position(),
+                    message: format!("Unexpected token: {:?}", token),
+                }),
+            };
         }
     }
 
@@ -115,6 +154,9 @@ impl<'a> Parser<'a> {
 
         loop {
             let Some(token) = self.next_token()? else {
+                if self.lenient {
+                    break;
+                }
                 return Err(PdfError::Parse {
                     position: self.position(),
                     message: "Unterminated array".into(),
@@ -124,6 +166,11 @@ impl<'a> Parser<'a> {
             if token == Token::ArrayEnd {
                 break;
             }
+            if token == Token::DictEnd && self.lenient {
+                // Unterminated array inside a dictionary
+                self.push_back(token);
+                break;
+            }
 
             // Put token back and parse as object
             self.push_back(token);
@@ -141,6 +188,9 @@ impl<'a> Parser<'a> {
 
         loop {
             let Some(token) = self.next_token()? else {
+                if self.lenient {
+                    break;
+                }
                 return Err(PdfError::Parse {
                     position: self.position(),
                     message: "Unterminated dictionary".into(),
@@ -154,6 +204,9 @@ impl<'a> Parser<'a> {
 
             // Key must be a name
             let Token::Name(key) = token else {
+                if self.lenient {
+                    continue;
+                }
                 return Err(PdfError::Parse {
                     position: self.position(),
                     message: format!("Dictionary key must be name, got {:?}", token),
@@ -161,10 +214,16 @@ impl<'a> Parser<'a> {
             };
 
             // Value is any object
-            let value = self.parse_object()?.ok_or_else(|| PdfError::Parse {
-                position: self.position(),
-                message: "Missing dictionary value".into(),
-            })?;
+            let value = match self.parse_object()? {
+                Some(value) => value,
+                None if self.lenient => continue,
+                None => {
+                    return Err(PdfError::Parse {
+                        position: self.position(),
+                        message: "Missing dictionary value".into(),
+                    });
+                }
+            };
 
             dict.insert(key, value);
         }
@@ -185,14 +244,38 @@ impl<'a> Parser<'a> {
 
     /// Read stream data after "stream" keyword
     fn read_stream_data(&mut self, dict: &HashMap<String, PdfObject>) -> Result<Vec<u8>> {
-        // Skip single newline after "stream"
-        self.lexer.skip_whitespace();
+        // Skip the single EOL after "stream"; the data may itself start
+        // with whitespace bytes. Leniently, spaces and tabs before the EOL
+        // are skipped too.
+        let mut pos = self.lexer.position();
+        if self.lenient {
+            let data = self.lexer.data();
+            let blanks = data[pos..].iter().take_while(|&&b| b == b' ' || b == b'\t').count();
+            if matches!(data.get(pos + blanks), Some(b'\r' | b'\n')) {
+                pos += blanks;
+            }
+        }
+        let eol = match &self.lexer.data()[pos..] {
+            [b'\r', b'\n', ..] => 2,
+            [b'\n' | b'\r', ..] => 1,
+            _ => 0,
+        };
+        self.lexer.seek(pos + eol);
 
         // Get length from dictionary
         let length = match dict.get("Length") {
-            Some(PdfObject::Int(n)) => *n as usize,
-            Some(PdfObject::Ref(_)) => {
-                // Length is indirect - for now, search for endstream
+            Some(PdfObject::Int(n)) => usize::try_from(*n).map_err(|_| PdfError::Parse {
+                position: self.position(),
+                message: format!("Invalid stream Length: {}", n),
+            })?,
+            Some(PdfObject::Ref(r)) => {
+                // Prefer the resolved length; scan for endstream as a last resort
+                let start = self.lexer.position();
+                let resolved = self.length_resolver.as_ref().and_then(|resolve| resolve(*r));
+                if let Some(data) = resolved.and_then(|len| self.read_exact_stream(len).ok()) {
+                    return Ok(data);
+                }
+                self.seek(start);
                 return self.read_stream_until_endstream();
             }
             _ => {
@@ -203,22 +286,25 @@ impl<'a> Parser<'a> {
             }
         };
 
+        self.read_exact_stream(length)
+    }
+
+    /// Read `length` bytes of stream data followed by "endstream"
+    fn read_exact_stream(&mut self, length: usize) -> Result<Vec<u8>> {
         // Read exact bytes
         let start = self.lexer.position();
-        let end = start + length;
 
-        // Bounds check
-        let data = self
-            .lexer
-            .data()
-            .get(start..end)
+        // Bounds check; an absurd length must not overflow the end offset
+        let end = start.checked_add(length);
+        let data = end
+            .and_then(|end| self.lexer.data().get(start..end))
             .ok_or_else(|| PdfError::Parse {
                 position: start,
                 message: "Stream data extends past EOF".into(),
             })?;
 
         let result = data.to_vec();
-        self.lexer.seek(end);
+        self.lexer.seek(start + length);
 
         // Expect "endstream"
         self.lexer.skip_whitespace();
@@ -239,9 +325,16 @@ impl<'a> Parser<'a> {
 
         // Search for endstream
         let data = self.lexer.data();
-        for i in start..data.len().saturating_sub(marker.len()) {
-            if &data[i..i + marker.len()] == marker {
-                let stream_data = data[start..i].to_vec();
+        for i in start..=data.len().saturating_sub(marker.len()) {
+            if data[i..].starts_with(marker) {
+                // The EOL before endstream is not part of the data
+                let mut end = i;
+                if data[start..end].ends_with(b"\r\n") {
+                    end -= 2;
+                } else if data[start..end].ends_with(b"\n") || data[start..end].ends_with(b"\r") {
+                    end -= 1;
+                }
+                let stream_data = data[start..end].to_vec();
                 self.lexer.seek(i + marker.len());
                 return Ok(stream_data);
             }
@@ -259,6 +352,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_parse_primitives() {
         let mut parser = Parser::new(b"null true false 42 3.14");
 
@@ -315,4 +409,73 @@ mod tests {
             panic!("Expected Dict");
         }
     }
+
+    #[test]
+    fn test_indirect_length() {
+        let data = b"<< /Length 9 0 R >>\nstream\nendstream!\nendstream";
+
+        // Without a resolver the data is cut at the first endstream
+        let obj = Parser::new(data).parse_object().unwrap().unwrap();
+        assert_eq!(obj.as_stream().unwrap().1, b"");
+
+        let mut parser =
+            Parser::new(data).with_length_resolver(|r| (r.obj_num == 9).then_some(10));
+        let obj = parser.parse_object().unwrap().unwrap();
+        assert_eq!(obj.as_stream().unwrap().1, b"endstream!");
+    }
+
+    #[test]
+    fn test_invalid_stream_length() {
+        for length in ["-5", "9223372036854775807"] {
+            let input = format!("<< /Length {} >>\nstream\nabc\nendstream", length);
+            assert!(Parser::new(input.as_bytes()).parse_object().is_err());
+        }
+    }
+
+    #[test]
+    fn test_lenient_recovers_malformed_dict() {
+        let input = b"<< /A 1 /B bogus 2 7 /C [3 >> /D >>";
+        assert!(Parser::new(input).parse_object().is_err());
+
+        let mut parser = Parser::new(input).lenient(true);
+        let obj = parser.parse_object().unwrap().unwrap();
+        let dict = obj.as_dict().unwrap();
+        assert_eq!(dict.get("A"), Some(&PdfObject::Int(1)));
+        assert_eq!(dict.get("B"), Some(&PdfObject::Int(2)));
+        assert_eq!(dict.get("C"), Some(&PdfObject::Array(vec![PdfObject::Int(3)])));
+        assert_eq!(dict.len(), 3);
+
+        // Both are closed at the end of the data
+        let input = b"<< /A 1 /B [2 3";
+        assert!(Parser::new(input).parse_object().is_err());
+        let obj = Parser::new(input).lenient(true).parse_object().unwrap().unwrap();
+        let dict = obj.as_dict().unwrap();
+        assert_eq!(dict.get("A"), Some(&PdfObject::Int(1)));
+        let items = vec![PdfObject::Int(2), PdfObject::Int(3)];
+        assert_eq!(dict.get("B"), Some(&PdfObject::Array(items)));
+    }
+
+    #[test]
+    fn test_stream_data_starting_with_whitespace() {
+        let input = b"<< /Length 3 >>\nstream\n \x00A\nendstream";
+        let obj = Parser::new(input).parse_object().unwrap().unwrap();
+        assert!(matches!(obj, PdfObject::Stream { data, .. } if data == b" \x00A"));
+
+        // Without a usable length, only one EOL is trimmed before endstream
+        let input = b"<< /Length 9 0 R >>\nstream\r\n x\n\r\nendstream";
+        let obj = Parser::new(input).parse_object().unwrap().unwrap();
+        assert!(matches!(obj, PdfObject::Stream { data, .. } if data == b" x\n"));
+    }
+
+    #[test]
+    fn test_lenient_blanks_after_stream_keyword() {
+        let input = b"<< /Length 3 >>\nstream \t\r\nabc\nendstream";
+        let obj = Parser::new(input).lenient(true).parse_object().unwrap().unwrap();
+        assert!(matches!(obj, PdfObject::Stream { data, .. } if data == b"abc"));
+
+        // Blanks not followed by an EOL are data
+        let input = b"<< /Length 3 >>\nstream  ab\nendstream";
+        let obj = Parser::new(input).lenient(true).parse_object().unwrap().unwrap();
+        assert!(matches!(obj, PdfObject::Stream { data, .. } if data == b"  a"));
+    }
 }
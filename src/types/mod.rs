@@ -1,3 +1,5 @@
+#[cfg(feature = "serde")]
+mod base64;
 mod object;
 
 pub use object::{ObjRef, PdfObject};
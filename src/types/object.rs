@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
+use crate::font::pdfdoc_decode;
+
 /// Reference to an indirect object (e.g., "5 0 R")
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjRef {
     pub obj_num: u32,
     pub gen_num: u16,
@@ -13,19 +16,30 @@ impl ObjRef {
     }
 }
 
+impl std::fmt::Display for ObjRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} R", self.obj_num, self.gen_num)
+    }
+}
+
 /// All possible PDF object types
+///
+/// With the `serde` feature, objects serialize externally tagged
+/// (`{"Int": 3}`), with the bytes of strings and streams as base64.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PdfObject {
     Null,
     Bool(bool),
     Int(i64),
     Real(f64),
-    String(Vec<u8>),
+    String(#[cfg_attr(feature = "serde", serde(with = "super::base64"))] Vec<u8>),
     Name(String),
     Array(Vec<PdfObject>),
     Dict(HashMap<String, PdfObject>),
     Stream {
         dict: HashMap<String, PdfObject>,
+        #[cfg_attr(feature = "serde", serde(with = "super::base64"))]
         data: Vec<u8>,
     },
     Ref(ObjRef),
@@ -62,6 +76,20 @@ impl PdfObject {
         }
     }
 
+    /// Decode a text string: UTF-16BE when it starts with the FE FF byte
+    /// order mark, otherwise PDFDocEncoding
+    pub fn as_text_string(&self) -> Option<String> {
+        let bytes = self.as_string()?;
+        if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+            let units: Vec<u16> = utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            return Some(String::from_utf16_lossy(&units));
+        }
+        Some(pdfdoc_decode(bytes))
+    }
+
     pub fn as_name(&self) -> Option<&str> {
         match self {
             PdfObject::Name(n) => Some(n),
@@ -98,3 +126,47 @@ impl PdfObject {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(bytes: &[u8]) -> Option<String> {
+        PdfObject::String(bytes.to_vec()).as_text_string()
+    }
+
+    #[test]
+    fn test_text_string_utf16_bom() {
+        assert_eq!(text(&[0xFE, 0xFF, 0x00, 0x48, 0x00, 0x69]).as_deref(), Some("Hi"));
+        // U+1F600 as a surrogate pair
+        assert_eq!(
+            text(&[0xFE, 0xFF, 0xD8, 0x3D, 0xDE, 0x00]).as_deref(),
+            Some("\u{1F600}")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let obj = PdfObject::Stream {
+            dict: HashMap::from([
+                ("Title".to_string(), PdfObject::String(b"\xfe\xffA".to_vec())),
+                ("Parent".to_string(), PdfObject::Ref(ObjRef::new(2, 0))),
+            ]),
+            data: b"BT ET".to_vec(),
+        };
+        let json = serde_json::to_string(&obj).unwrap();
+        assert!(json.contains(r#""data":"QlQgRVQ=""#));
+        assert!(json.contains(r#"{"String":"/v9B"}"#));
+        assert_eq!(serde_json::from_str::<PdfObject>(&json).unwrap(), obj);
+    }
+
+    #[test]
+    fn test_text_string_without_bom() {
+        assert_eq!(text(b"Caf\xe9").as_deref(), Some("Caf\u{e9}"));
+        assert_eq!(text(b"").as_deref(), Some(""));
+        assert_eq!(text(b"\x8dHi\x8e").as_deref(), Some("\u{201C}Hi\u{201D}"));
+        assert_eq!(text(&[0xFE, 0xFF]).as_deref(), Some(""));
+        assert_eq!(PdfObject::Int(1).as_text_string(), None);
+    }
+}
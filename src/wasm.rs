@@ -1,6 +1,19 @@
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
-use crate::{classify_spans, elements_to_markdown, elements_to_txt, Document, Table};
+use crate::{
+    classify_spans, elements_to_html, elements_to_markdown, elements_to_txt, Document, Table,
+};
+
+/// A text span as handed to JavaScript
+#[derive(Serialize)]
+struct JsSpan<'a> {
+    text: &'a str,
+    x: f64,
+    y: f64,
+    font_size: f64,
+    font_name: Option<&'a str>,
+}
 
 /// Initialize panic hook for better error messages in browser console
 #[wasm_bindgen(start)]
@@ -15,7 +28,7 @@ pub fn pdf_to_csv(data: &[u8]) -> Result<String, JsValue> {
         Document::parse(data).map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
 
     let page_count = doc
-        .page_count()
+        .page_count_exact()
         .map_err(|e| JsValue::from_str(&format!("Page count error: {}", e)))?;
 
     let mut output = String::new();
@@ -43,7 +56,7 @@ pub fn pdf_to_tsv(data: &[u8]) -> Result<String, JsValue> {
         Document::parse(data).map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
 
     let page_count = doc
-        .page_count()
+        .page_count_exact()
         .map_err(|e| JsValue::from_str(&format!("Page count error: {}", e)))?;
 
     let mut output = String::new();
@@ -88,6 +101,36 @@ pub fn pdf_page_to_csv(data: &[u8], page: usize) -> Result<String, JsValue> {
     Ok(table.to_csv())
 }
 
+/// Parse a single page (0-indexed) and return its text spans as an array of
+/// `{text, x, y, font_size, font_name}` objects; `font_name` is null when
+/// unknown
+#[wasm_bindgen]
+pub fn pdf_page_spans(data: &[u8], page: usize) -> Result<JsValue, JsValue> {
+    let mut doc =
+        Document::parse(data).map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+
+    let spans = doc
+        .extract_page_text(page)
+        .map_err(|e| JsValue::from_str(&format!("Page {} error: {}", page + 1, e)))?;
+
+    let spans: Vec<JsSpan> = spans
+        .iter()
+        .map(|span| JsSpan {
+            text: &span.text,
+            x: span.x,
+            y: span.y,
+            font_size: span.font_size,
+            font_name: span.font_name.as_deref(),
+        })
+        .collect();
+
+    // Without this, None would become undefined rather than null
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+    spans
+        .serialize(&serializer)
+        .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))
+}
+
 /// Parse a PDF and return plain text (layout-aware) for all pages
 #[wasm_bindgen]
 pub fn pdf_to_txt(data: &[u8]) -> Result<String, JsValue> {
@@ -95,7 +138,7 @@ pub fn pdf_to_txt(data: &[u8]) -> Result<String, JsValue> {
         Document::parse(data).map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
 
     let page_count = doc
-        .page_count()
+        .page_count_exact()
         .map_err(|e| JsValue::from_str(&format!("Page count error: {}", e)))?;
 
     let mut output = String::new();
@@ -123,7 +166,7 @@ pub fn pdf_to_md(data: &[u8]) -> Result<String, JsValue> {
         Document::parse(data).map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
 
     let page_count = doc
-        .page_count()
+        .page_count_exact()
         .map_err(|e| JsValue::from_str(&format!("Page count error: {}", e)))?;
 
     let mut output = String::new();
@@ -143,3 +186,31 @@ pub fn pdf_to_md(data: &[u8]) -> Result<String, JsValue> {
 
     Ok(output)
 }
+
+/// Parse a PDF and return HTML (layout-aware) for all pages
+#[wasm_bindgen]
+pub fn pdf_to_html(data: &[u8]) -> Result<String, JsValue> {
+    let mut doc =
+        Document::parse(data).map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+
+    let page_count = doc
+        .page_count_exact()
+        .map_err(|e| JsValue::from_str(&format!("Page count error: {}", e)))?;
+
+    let mut output = String::new();
+
+    for page_idx in 0..page_count {
+        let spans = doc
+            .extract_page_text(page_idx)
+            .map_err(|e| JsValue::from_str(&format!("Page {} error: {}", page_idx + 1, e)))?;
+
+        let elements = classify_spans(spans);
+
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&elements_to_html(&elements));
+    }
+
+    Ok(output)
+}